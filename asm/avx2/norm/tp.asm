@@ -0,0 +1,116 @@
+%include "dav1d_x86inc.asm"
+
+SECTION_RODATA 32
+fir: dd  0.0017089843750, 0.0017089843750, -0.0291748046875, -0.0291748046875
+     dd -0.0189208984375, -0.0189208984375, -0.0083007812500, -0.0083007812500
+     dd  0.0109863281250, 0.0109863281250, 0.0292968750000, 0.0292968750000
+     dd  0.0330810546875, 0.0330810546875, 0.0148925781250, 0.0148925781250
+     dd -0.0196533203125, -0.0196533203125, -0.0517578125000, -0.0517578125000
+     dd -0.0582275390625, -0.0582275390625, -0.0266113281250, -0.0266113281250
+     dd  0.0332031250000, 0.0332031250000, 0.0891113281250, 0.0891113281250
+     dd  0.1015625000000, 0.1015625000000, 0.0476074218750, 0.0476074218750
+     dd -0.0594482421875, -0.0594482421875, -0.1665039062500, -0.1665039062500
+     dd -0.2003173828125, -0.2003173828125, -0.1022949218750, -0.1022949218750
+     dd  0.1373291015625, 0.1373291015625, 0.4650878906250, 0.4650878906250
+     dd  0.7797851562500, 0.7797851562500, 0.9721679687500, 0.9721679687500
+     dd  0.9721679687500, 0.9721679687500, 0.7797851562500, 0.7797851562500
+     dd  0.4650878906250, 0.4650878906250, 0.1373291015625, 0.1373291015625
+     dd -0.1022949218750, -0.1022949218750, -0.2003173828125, -0.2003173828125
+     dd -0.1665039062500, -0.1665039062500, -0.0594482421875, -0.0594482421875
+     dd  0.0476074218750, 0.0476074218750, 0.1015625000000, 0.1015625000000
+     dd  0.0891113281250, 0.0891113281250, 0.0332031250000, 0.0332031250000
+     dd -0.0266113281250, -0.0266113281250, -0.0582275390625, -0.0582275390625
+     dd -0.0517578125000, -0.0517578125000, -0.0196533203125, -0.0196533203125
+     dd  0.0148925781250, 0.0148925781250, 0.0330810546875, 0.0330810546875
+     dd  0.0292968750000, 0.0292968750000, 0.0109863281250, 0.0109863281250
+     dd -0.0083007812500, -0.0083007812500, -0.0189208984375, -0.0189208984375
+     dd -0.0291748046875, -0.0291748046875, 0.0017089843750, 0.0017089843750
+absy: dd 0x7fffffff,0x7fffffff,0x7fffffff,0x7fffffff,0x7fffffff,0x7fffffff,0x7fffffff,0x7fffffff
+gix: dd 0,0,1,1,2,2,3,3
+
+SECTION .text
+INIT_YMM avx2
+
+cglobal tp, 3, 5, 16, src, n, out
+    vmovaps       ymm4, [fir]
+    vmovaps       ymm5, [fir + 0x20]
+    vmovaps       ymm6, [fir + 0x40]
+    vmovaps       ymm7, [fir + 0x60]
+    vmovaps       ymm8, [fir + 0x80]
+    vmovaps       ymm9, [fir + 0xa0]
+    vmovaps       ymm10, [fir + 0xc0]
+    vmovaps       ymm11, [fir + 0xe0]
+    vmovaps       ymm12, [fir + 0x100]
+    vmovaps       ymm13, [fir + 0x120]
+    vmovaps       ymm14, [fir + 0x140]
+    vmovaps       ymm15, [fir + 0x160]
+    xor           eax, eax
+    test          nq, nq
+    jz            .done
+.loop:
+    vbroadcastsd  ymm1, [srcq + rax*8]
+    vmulps        ymm0, ymm1, ymm4
+    vbroadcastsd  ymm2, [srcq + rax*8 - 0x08]
+    vmulps        ymm3, ymm2, ymm5
+    vbroadcastsd  ymm1, [srcq + rax*8 - 0x10]
+    vfmadd231ps   ymm0, ymm1, ymm6
+    vbroadcastsd  ymm2, [srcq + rax*8 - 0x18]
+    vfmadd231ps   ymm3, ymm2, ymm7
+    vbroadcastsd  ymm1, [srcq + rax*8 - 0x20]
+    vfmadd231ps   ymm0, ymm1, ymm8
+    vbroadcastsd  ymm2, [srcq + rax*8 - 0x28]
+    vfmadd231ps   ymm3, ymm2, ymm9
+    vbroadcastsd  ymm1, [srcq + rax*8 - 0x30]
+    vfmadd231ps   ymm0, ymm1, ymm10
+    vbroadcastsd  ymm2, [srcq + rax*8 - 0x38]
+    vfmadd231ps   ymm3, ymm2, ymm11
+    vbroadcastsd  ymm1, [srcq + rax*8 - 0x40]
+    vfmadd231ps   ymm0, ymm1, ymm12
+    vbroadcastsd  ymm2, [srcq + rax*8 - 0x48]
+    vfmadd231ps   ymm3, ymm2, ymm13
+    vbroadcastsd  ymm1, [srcq + rax*8 - 0x50]
+    vfmadd231ps   ymm0, ymm1, ymm14
+    vbroadcastsd  ymm2, [srcq + rax*8 - 0x58]
+    vfmadd231ps   ymm3, ymm2, ymm15
+    vaddps        ymm0, ymm0, ymm3
+    vandps        ymm0, ymm0, [absy]
+    vextractf128  xmm1, ymm0, 1
+    vmaxps        xmm0, xmm0, xmm1
+    vmovhlps      xmm1, xmm0, xmm0
+    vmaxps        xmm0, xmm0, xmm1
+    vmovshdup     xmm1, xmm0
+    vmaxss        xmm0, xmm0, xmm1
+    vmovss        [outq + rax*4], xmm0
+    inc           rax
+    cmp           rax, nq
+    jb            .loop
+.done:
+    RET
+
+cglobal gain, 3, 5, 4, dst, g, n
+    vmovaps       ymm3, [gix]
+    xor           eax, eax
+    mov           r3, nq
+    and           r3, -4
+    jz            .tail
+.loop:
+    vmovups       xmm0, [gq + rax*4]
+    vpermps       ymm0, ymm3, ymm0
+    vmulps        ymm0, ymm0, [dstq + rax*8]
+    vmovups       [dstq + rax*8], ymm0
+    add           rax, 4
+    cmp           rax, r3
+    jb            .loop
+.tail:
+    cmp           rax, nq
+    jae           .done
+.tloop:
+    vbroadcastss  xmm0, [gq + rax*4]
+    vmovsd        xmm1, [dstq + rax*8]
+    vmulps        xmm1, xmm1, xmm0
+    vmovsd        [dstq + rax*8], xmm1
+    inc           rax
+    cmp           rax, nq
+    jb            .tloop
+.done:
+    RET
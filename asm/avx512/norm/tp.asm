@@ -0,0 +1,173 @@
+%include "dav1d_x86inc.asm"
+
+SECTION_RODATA 64
+fir: dd  0.0017089843750, 0.0017089843750, -0.0291748046875, -0.0291748046875
+     dd -0.0189208984375, -0.0189208984375, -0.0083007812500, -0.0083007812500
+     dd  0.0109863281250, 0.0109863281250, 0.0292968750000, 0.0292968750000
+     dd  0.0330810546875, 0.0330810546875, 0.0148925781250, 0.0148925781250
+     dd -0.0196533203125, -0.0196533203125, -0.0517578125000, -0.0517578125000
+     dd -0.0582275390625, -0.0582275390625, -0.0266113281250, -0.0266113281250
+     dd  0.0332031250000, 0.0332031250000, 0.0891113281250, 0.0891113281250
+     dd  0.1015625000000, 0.1015625000000, 0.0476074218750, 0.0476074218750
+     dd -0.0594482421875, -0.0594482421875, -0.1665039062500, -0.1665039062500
+     dd -0.2003173828125, -0.2003173828125, -0.1022949218750, -0.1022949218750
+     dd  0.1373291015625, 0.1373291015625, 0.4650878906250, 0.4650878906250
+     dd  0.7797851562500, 0.7797851562500, 0.9721679687500, 0.9721679687500
+     dd  0.9721679687500, 0.9721679687500, 0.7797851562500, 0.7797851562500
+     dd  0.4650878906250, 0.4650878906250, 0.1373291015625, 0.1373291015625
+     dd -0.1022949218750, -0.1022949218750, -0.2003173828125, -0.2003173828125
+     dd -0.1665039062500, -0.1665039062500, -0.0594482421875, -0.0594482421875
+     dd  0.0476074218750, 0.0476074218750, 0.1015625000000, 0.1015625000000
+     dd  0.0891113281250, 0.0891113281250, 0.0332031250000, 0.0332031250000
+     dd -0.0266113281250, -0.0266113281250, -0.0582275390625, -0.0582275390625
+     dd -0.0517578125000, -0.0517578125000, -0.0196533203125, -0.0196533203125
+     dd  0.0148925781250, 0.0148925781250, 0.0330810546875, 0.0330810546875
+     dd  0.0292968750000, 0.0292968750000, 0.0109863281250, 0.0109863281250
+     dd -0.0083007812500, -0.0083007812500, -0.0189208984375, -0.0189208984375
+     dd -0.0291748046875, -0.0291748046875, 0.0017089843750, 0.0017089843750
+gix: dd 0,0,1,1,2,2,3,3,4,4,5,5,6,6,7,7
+absm: dd 0x7fffffff
+
+SECTION .text
+INIT_ZMM avx512
+
+cglobal tp, 3, 5, 16, src, n, out
+    vbroadcastf64x4 zmm16, [fir]
+    vbroadcastf64x4 zmm17, [fir + 0x20]
+    vbroadcastf64x4 zmm18, [fir + 0x40]
+    vbroadcastf64x4 zmm19, [fir + 0x60]
+    vbroadcastf64x4 zmm20, [fir + 0x80]
+    vbroadcastf64x4 zmm21, [fir + 0xa0]
+    vbroadcastf64x4 zmm22, [fir + 0xc0]
+    vbroadcastf64x4 zmm23, [fir + 0xe0]
+    vbroadcastf64x4 zmm24, [fir + 0x100]
+    vbroadcastf64x4 zmm25, [fir + 0x120]
+    vbroadcastf64x4 zmm26, [fir + 0x140]
+    vbroadcastf64x4 zmm27, [fir + 0x160]
+    vpbroadcastd  zmm28, [absm]
+    mov           r3d, 0xf0
+    kmovd         k1, r3d
+    xor           eax, eax
+    mov           r4, nq
+    and           r4, -2
+    jz            .tail
+.loop:
+    vbroadcastsd  zmm1, [srcq + rax*8]
+    vbroadcastsd  zmm1{k1}, [srcq + rax*8 + 0x08]
+    vmulps        zmm0, zmm1, zmm16
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x08]
+    vbroadcastsd  zmm2{k1}, [srcq + rax*8]
+    vmulps        zmm3, zmm2, zmm17
+    vbroadcastsd  zmm1, [srcq + rax*8 - 0x10]
+    vbroadcastsd  zmm1{k1}, [srcq + rax*8 - 0x08]
+    vfmadd231ps   zmm0, zmm1, zmm18
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x18]
+    vbroadcastsd  zmm2{k1}, [srcq + rax*8 - 0x10]
+    vfmadd231ps   zmm3, zmm2, zmm19
+    vbroadcastsd  zmm1, [srcq + rax*8 - 0x20]
+    vbroadcastsd  zmm1{k1}, [srcq + rax*8 - 0x18]
+    vfmadd231ps   zmm0, zmm1, zmm20
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x28]
+    vbroadcastsd  zmm2{k1}, [srcq + rax*8 - 0x20]
+    vfmadd231ps   zmm3, zmm2, zmm21
+    vbroadcastsd  zmm1, [srcq + rax*8 - 0x30]
+    vbroadcastsd  zmm1{k1}, [srcq + rax*8 - 0x28]
+    vfmadd231ps   zmm0, zmm1, zmm22
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x38]
+    vbroadcastsd  zmm2{k1}, [srcq + rax*8 - 0x30]
+    vfmadd231ps   zmm3, zmm2, zmm23
+    vbroadcastsd  zmm1, [srcq + rax*8 - 0x40]
+    vbroadcastsd  zmm1{k1}, [srcq + rax*8 - 0x38]
+    vfmadd231ps   zmm0, zmm1, zmm24
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x48]
+    vbroadcastsd  zmm2{k1}, [srcq + rax*8 - 0x40]
+    vfmadd231ps   zmm3, zmm2, zmm25
+    vbroadcastsd  zmm1, [srcq + rax*8 - 0x50]
+    vbroadcastsd  zmm1{k1}, [srcq + rax*8 - 0x48]
+    vfmadd231ps   zmm0, zmm1, zmm26
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x58]
+    vbroadcastsd  zmm2{k1}, [srcq + rax*8 - 0x50]
+    vfmadd231ps   zmm3, zmm2, zmm27
+    vaddps        zmm0, zmm0, zmm3
+    vpandd        zmm0, zmm0, zmm28
+    vextractf64x4 ymm1, zmm0, 1
+    vextractf128  xmm2, ymm0, 1
+    vextractf128  xmm3, ymm1, 1
+    vmaxps        xmm0, xmm0, xmm2
+    vmaxps        xmm1, xmm1, xmm3
+    vunpcklpd     xmm2, xmm0, xmm1
+    vunpckhpd     xmm3, xmm0, xmm1
+    vmaxps        xmm2, xmm2, xmm3
+    vmovshdup     xmm3, xmm2
+    vmaxps        xmm2, xmm2, xmm3
+    vshufps       xmm2, xmm2, xmm2, 0x08
+    vmovsd        [outq + rax*4], xmm2
+    add           rax, 2
+    cmp           rax, r4
+    jb            .loop
+.tail:
+    cmp           rax, nq
+    jae           .done
+    vbroadcastsd  zmm1, [srcq + rax*8]
+    vmulps        zmm0, zmm1, zmm16
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x08]
+    vmulps        zmm3, zmm2, zmm17
+    vbroadcastsd  zmm1, [srcq + rax*8 - 0x10]
+    vfmadd231ps   zmm0, zmm1, zmm18
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x18]
+    vfmadd231ps   zmm3, zmm2, zmm19
+    vbroadcastsd  zmm1, [srcq + rax*8 - 0x20]
+    vfmadd231ps   zmm0, zmm1, zmm20
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x28]
+    vfmadd231ps   zmm3, zmm2, zmm21
+    vbroadcastsd  zmm1, [srcq + rax*8 - 0x30]
+    vfmadd231ps   zmm0, zmm1, zmm22
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x38]
+    vfmadd231ps   zmm3, zmm2, zmm23
+    vbroadcastsd  zmm1, [srcq + rax*8 - 0x40]
+    vfmadd231ps   zmm0, zmm1, zmm24
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x48]
+    vfmadd231ps   zmm3, zmm2, zmm25
+    vbroadcastsd  zmm1, [srcq + rax*8 - 0x50]
+    vfmadd231ps   zmm0, zmm1, zmm26
+    vbroadcastsd  zmm2, [srcq + rax*8 - 0x58]
+    vfmadd231ps   zmm3, zmm2, zmm27
+    vaddps        zmm0, zmm0, zmm3
+    vpandd        zmm0, zmm0, zmm28
+    vextractf128  xmm1, ymm0, 1
+    vmaxps        xmm0, xmm0, xmm1
+    vmovhlps      xmm1, xmm0, xmm0
+    vmaxps        xmm0, xmm0, xmm1
+    vmovshdup     xmm1, xmm0
+    vmaxss        xmm0, xmm0, xmm1
+    vmovss        [outq + rax*4], xmm0
+.done:
+    RET
+
+cglobal gain, 3, 5, 4, dst, g, n
+    vmovaps       zmm3, [gix]
+    xor           eax, eax
+    mov           r3, nq
+    and           r3, -8
+    jz            .tail
+.loop:
+    vmovups       ymm0, [gq + rax*4]
+    vpermps       zmm0, zmm3, zmm0
+    vmulps        zmm0, zmm0, [dstq + rax*8]
+    vmovups       [dstq + rax*8], zmm0
+    add           rax, 8
+    cmp           rax, r3
+    jb            .loop
+.tail:
+    cmp           rax, nq
+    jae           .done
+.tloop:
+    vbroadcastss  xmm0, [gq + rax*4]
+    vmovsd        xmm1, [dstq + rax*8]
+    vmulps        xmm1, xmm1, xmm0
+    vmovsd        [dstq + rax*8], xmm1
+    inc           rax
+    cmp           rax, nq
+    jb            .tloop
+.done:
+    RET
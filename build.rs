@@ -275,7 +275,7 @@ fn build_asm() -> Result<(), Box<dyn Error + Send + Sync>> {
             for k in ["atou", "atof", "atof2", "scan"] {
                 b.file(format!("asm/{set}/atofu/{k}.asm"));
             }
-            for k in ["mix", "loud"] {
+            for k in ["mix", "loud", "tp"] {
                 b.file(format!("asm/{set}/norm/{k}.asm"));
             }
             for k in ["pchip", "fc_spline", "lerp", "bs"] {
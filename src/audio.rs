@@ -1,7 +1,8 @@
 use alloc::borrow::Cow;
 #[cfg(target_os = "linux")]
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use core::{
+    fmt::Write as _,
     hint::cold_path,
     iter::repeat_with,
     mem::take,
@@ -13,7 +14,7 @@ use core::{
 };
 
 #[cfg(all(target_os = "linux", not(test)))]
-use crate::fmath::Powf as _;
+use crate::fmath::{Log10 as _, Powf as _};
 use crate::{
     audio::{
         AuBrate::{Auto, Fixed, Norm},
@@ -23,12 +24,14 @@ use crate::{
     ffms::get_au_streams,
     fs::{File, write},
     io::{BufWriter, Write as _},
+    lang::{lang_matches, to_bcp47},
     lavf::AuDecoder,
-    norm::{downmix, measure},
-    opus::{Encoder, FRAME},
+    norm::{compress, downmix, limit, measure, peaks, timeline},
+    opus::{App, Encoder, MAX_PKT, Mapping, OpusOpts},
     path::{Path, PathBuf},
     progs::{ProgsBar, monitor_au},
     thread::{ScopedJoinHandle, available_parallelism, scope, sleep},
+    util::json_str,
 };
 
 #[derive(Clone, Copy)]
@@ -37,6 +40,7 @@ pub struct NormParams {
     pub tp: f32,
     pub lra: f32,
     pub brate: u16,
+    pub drc: bool,
 }
 
 impl NormParams {
@@ -46,6 +50,7 @@ impl NormParams {
             tp: -1.5,
             lra: 16.0,
             brate: 128,
+            drc: false,
         }
     }
 }
@@ -63,12 +68,33 @@ pub enum AuBrate {
 pub enum AuStreams {
     All,
     Specific(Vec<u8>),
+    Select(AuSel),
+}
+
+#[derive(Clone)]
+pub struct AuSel {
+    ids: Vec<u8>,
+    langs: Vec<String>,
+    exclude: Vec<String>,
+    max_ch: u8,
+    first_per_lang: bool,
 }
 
 #[derive(Clone)]
 pub struct AuSpec {
     pub brate: AuBrate,
     pub streams: AuStreams,
+    pub opus: OpusOpts,
+    pub opus_per: Vec<(u8, OpusOpts)>,
+}
+
+impl AuSpec {
+    fn opus_for(&self, index: u8) -> OpusOpts {
+        self.opus_per
+            .iter()
+            .find(|&&(i, _)| i == index)
+            .map_or(self.opus, |&(_, o)| o)
+    }
 }
 
 #[derive(Clone)]
@@ -77,16 +103,27 @@ pub struct AuStream {
     pub channels: u8,
     pub lang: Option<Cow<'static, str>>,
     pub bitrate: u16,
+    pub opus: OpusOpts,
+    pub title: Option<String>,
+    pub comment: bool,
 }
 
 fn parse_norm(s: &str) -> Result<NormParams, Xerr> {
-    if s == "norm" {
-        return Ok(NormParams::default());
+    let (rest, drc) = match (s.strip_prefix("norm"), s.strip_prefix("drc")) {
+        (Some(r), _) => (r, false),
+        (_, Some(r)) => (r, true),
+        _ => return Err("norm format: norm|drc or norm|drc(I,TP,LRA[,BITRATE])".into()),
+    };
+    if rest.is_empty() {
+        return Ok(NormParams {
+            drc,
+            ..NormParams::default()
+        });
     }
-    let inner = s
-        .strip_prefix("norm(")
+    let inner = rest
+        .strip_prefix('(')
         .and_then(|r| r.strip_suffix(')'))
-        .ok_or("norm format: norm or norm(I,TP,LRA[,BITRATE])")?;
+        .ok_or("norm format: norm|drc or norm|drc(I,TP,LRA[,BITRATE])")?;
     let (i, tp, lra, brate) = match *inner.split(',').collect::<Vec<_>>() {
         [i, tp, lra] => (i, tp, lra, "128"),
         [i, tp, lra, b] => (i, tp, lra, b),
@@ -97,44 +134,160 @@ fn parse_norm(s: &str) -> Result<NormParams, Xerr> {
         tp: tp.parse()?,
         lra: lra.parse()?,
         brate: brate.parse()?,
+        drc,
+    })
+}
+
+fn parse_opus_kv(o: &mut OpusOpts, kv: &str) -> Result<(), Xerr> {
+    let (k, v) = kv.split_once('=').unwrap_or((kv, ""));
+    match k {
+        "app" => {
+            o.app = match v {
+                "voip" => App::Voip,
+                "audio" => App::Audio,
+                "lowdelay" => App::LowDelay,
+                _ => return Err("app: voip|audio|lowdelay".into()),
+            };
+        }
+        "frame" => {
+            o.frame = match v {
+                "2.5" => 120,
+                "5" => 240,
+                "10" => 480,
+                "20" => 960,
+                "40" => 1920,
+                "60" => 2880,
+                _ => return Err("frame: 2.5|5|10|20|40|60 (ms)".into()),
+            };
+        }
+        "cx" => {
+            o.complexity = v.parse()?;
+            if o.complexity > 10 {
+                return Err("cx: 0-10".into());
+            }
+        }
+        "cvbr" => o.cvbr = true,
+        "loss" => {
+            o.loss = v.parse()?;
+            if o.loss > 100 {
+                return Err("loss: 0-100 (%)".into());
+            }
+        }
+        "map" => {
+            o.mapping = match v {
+                "auto" => Mapping::Auto,
+                "discrete" => Mapping::Discrete,
+                "amb" => Mapping::Ambisonic,
+                "proj" => Mapping::Projection,
+                _ => return Err("map: auto|discrete|amb|proj".into()),
+            };
+        }
+        _ => return Err(format!("Unknown Opus option: {k}").into()),
+    }
+    Ok(())
+}
+
+fn parse_opus(s: &str) -> Result<(OpusOpts, Vec<(u8, OpusOpts)>), Xerr> {
+    let mut base = OpusOpts::default();
+    let mut per = Vec::new();
+    let groups: Vec<_> = s
+        .split(';')
+        .map(|g| {
+            g.split_once(':')
+                .map_or((None, g), |(id, rest)| (Some(id), rest))
+        })
+        .collect();
+    for &(_, g) in groups.iter().filter(|g| g.0.is_none()) {
+        g.split(',')
+            .try_for_each(|kv| parse_opus_kv(&mut base, kv))?;
+    }
+    for &(id, g) in &groups {
+        if let Some(id) = id {
+            let mut o = base;
+            g.split(',').try_for_each(|kv| parse_opus_kv(&mut o, kv))?;
+            per.push((id.parse()?, o));
+        }
+    }
+    Ok((base, per))
+}
+
+fn parse_streams(s: &str) -> Result<AuStreams, Xerr> {
+    if s == "all" {
+        return Ok(All);
+    }
+    let mut sel = AuSel {
+        ids: Vec::new(),
+        langs: Vec::new(),
+        exclude: Vec::new(),
+        max_ch: 0,
+        first_per_lang: false,
+    };
+    let mut only_ids = true;
+    for tok in s.split('+') {
+        if let Some(v) = tok.strip_prefix("lang:") {
+            sel.langs.extend(v.split(',').map(Into::into));
+        } else if let Some(v) = tok.strip_prefix("exclude:") {
+            sel.exclude
+                .extend(v.split(',').map(str::to_ascii_lowercase));
+        } else if let Some(v) = tok.strip_prefix("max-channels:") {
+            sel.max_ch = v.parse()?;
+        } else if tok == "first-per-lang" {
+            sel.first_per_lang = true;
+        } else if tok != "all" {
+            sel.ids.extend(
+                tok.split(',')
+                    .map(str::parse)
+                    .collect::<Result<Vec<u8>, _>>()?,
+            );
+            continue;
+        }
+        only_ids = false;
+    }
+    Ok(if only_ids {
+        Specific(sel.ids)
+    } else {
+        AuStreams::Select(sel)
     })
 }
 
 pub fn parse_au_arg(arg: &str) -> Result<AuSpec, Xerr> {
     let parts: Vec<&str> = arg.split_whitespace().collect();
-    if parts.len() != 2 {
-        return Err("Audio format: -a <auto|norm|norm(I,TP,LRA)|brate> <all|stream_ids>".into());
+    if !(2..=3).contains(&parts.len()) {
+        return Err(
+            "Audio format: -a <auto|norm|norm(I,TP,LRA)|drc|drc(I,TP,LRA)|brate> \
+             <all|ids|selectors> [opus_opts]"
+                .into(),
+        );
     }
+    let (opus, opus_per) = parts
+        .get(2)
+        .map_or_else(|| Ok((OpusOpts::default(), Vec::new())), |o| parse_opus(o))?;
 
     Ok(AuSpec {
         brate: if parts[0] == "auto" {
             Auto
-        } else if parts[0].starts_with("norm") {
+        } else if parts[0].starts_with("norm") || parts[0].starts_with("drc") {
             Norm(parse_norm(parts[0])?)
         } else {
             Fixed(parts[0].parse()?)
         },
-        streams: if parts[1] == "all" {
-            All
-        } else {
-            Specific(
-                parts[1]
-                    .split(',')
-                    .map(str::parse)
-                    .collect::<Result<_, _>>()?,
-            )
-        },
+        streams: parse_streams(parts[1])?,
+        opus,
+        opus_per,
     })
 }
 
 fn get_streams(inp: &Path) -> Result<Vec<AuStream>, Xerr> {
     get_au_streams(inp).map(|v| {
         v.into_iter()
-            .map(|(index, channels, lang)| AuStream {
+            .map(|(index, channels, lang, title, comment)| AuStream {
                 index,
                 channels,
                 lang,
                 bitrate: 0,
+                opus: OpusOpts::default(),
+                title,
+                comment,
             })
             .collect()
     })
@@ -187,21 +340,37 @@ fn enc_stream(
     };
     if let Some(np) = np {
         let mut pcm = par_decode(inp, stream, ranges, progs_line)?;
-        let (lufs, lra) = measure(&pcm);
-        let mut gain = 10f32.powf((np.i - lufs) / 20.0);
+        let (mut lufs, lra) = measure(&pcm);
+        let mut gain = 1f32;
         if lra > np.lra {
-            gain *= np.lra / lra;
+            if np.drc {
+                compress(&mut pcm, lra, np.lra);
+                lufs = measure(&pcm).0;
+            } else {
+                gain = np.lra / lra;
+            }
         }
-        let tp = 10f32.powf(np.tp / 20.0);
+        gain *= 10f32.powf((np.i - lufs) / 20.0);
         for s in &mut pcm {
-            *s = (*s * gain).clamp(-tp, tp);
+            *s *= gain;
         }
-        chunk_encode(&mut pcm, 2, brate, out, stream.index, progs_line)
+        limit(&mut pcm, 10f32.powf(np.tp / 20.0));
+        chunk_encode(
+            &mut pcm,
+            2,
+            brate,
+            &stream.opus,
+            out,
+            stream.index,
+            progs_line,
+        )
     } else {
         fused_encode(inp, stream, brate, out, ranges, progs_line)
     }
 }
 
+// Interleaved stereo PCM of `ranges`. Mono goes left with a silent right: BS.1770 counts a mono
+// channel once. Only `--au-report` sees mono here; `norm` takes surround alone
 fn par_decode(
     inp: &Path,
     stream: &AuStream,
@@ -255,10 +424,16 @@ fn par_decode(
                         let (s0, s1, isf, isl) = regions[u];
                         let mut local: Vec<f32> = Vec::new();
                         let bpos = dec.decode_range(s0, s1, isf, isl, |chnk: &mut [f32]| {
-                            let n = chnk.len() / ch;
-                            let off = local.len();
-                            local.resize(off + n * out_ch, 0.0);
-                            downmix(chnk, &mut local[off..], ch, n);
+                            match ch {
+                                1 => local.extend(chnk.iter().flat_map(|&x| [x, 0.0])),
+                                2 => local.extend_from_slice(chnk),
+                                _ => {
+                                    let n = chnk.len() / ch;
+                                    let off = local.len();
+                                    local.resize(off + n * out_ch, 0.0);
+                                    downmix(chnk, &mut local[off..], ch, n);
+                                }
+                            }
                             Ok(())
                         })?;
                         done.fetch_add(local.len() / out_ch, Relaxed);
@@ -315,6 +490,8 @@ fn fused_encode(
     let tid = stream.index;
     let total: usize = ranges.iter().map(|&(s, e)| (e - s) as usize).sum();
     let nproc = available_parallelism();
+    let surround = stream.opus.surround(stream.channels);
+    let fr = Framing::new(stream.opus.frame);
 
     let mut units: Vec<(i64, i64, bool, usize)> = Vec::new();
     for &(rs, re) in ranges {
@@ -366,7 +543,7 @@ fn fused_encode(
                         let bpos =
                             dec.decode_range(ts, te, rl > 0, last, |chnk: &mut [f32]| {
                                 let n = chnk.len() / ch;
-                                if ch > 2 {
+                                if surround {
                                     reord_surround(chnk, ch, n);
                                 }
                                 pcm.extend_from_slice(chnk);
@@ -395,7 +572,7 @@ fn fused_encode(
         let mut done = 0usize;
         let mut first = true;
         let mut progs = ProgsBar::new();
-        let seg = nproc * CHUNK_FRAMES;
+        let seg = nproc * fr.chunk;
         let enc_res = (|| -> Result<(), Xerr> {
             for u in 0..nunits {
                 while !ready[u].load(Acquire) {
@@ -414,23 +591,32 @@ fn fused_encode(
                 remaining -= emit;
                 done += emit;
                 consumed.store(u + 1, Relaxed);
-                while buf.len() / (ch * FRAME) >= enc_off + seg + POSTROLL {
-                    w.write_all(&par_encode_seg(&buf, enc_off, seg, ch, brate, first)?)?;
+                while buf.len() / (ch * fr.len) >= enc_off + seg + fr.post {
+                    w.write_all(&par_encode_seg(
+                        &buf,
+                        enc_off,
+                        seg,
+                        ch,
+                        brate,
+                        &stream.opus,
+                        first,
+                    )?)?;
                     first = false;
-                    buf.drain(..(enc_off + seg - PREROLL) * FRAME * ch);
-                    enc_off = PREROLL;
+                    buf.drain(..(enc_off + seg - fr.pre) * fr.len * ch);
+                    enc_off = fr.pre;
                     progs.up_au(done.min(total), total, progs_line, 2, tid);
                 }
             }
-            let bframes = buf.len().div_ceil(ch * FRAME);
+            let bframes = buf.len().div_ceil(ch * fr.len);
             if bframes > enc_off {
-                buf.resize(bframes * FRAME * ch, 0.0);
+                buf.resize(bframes * fr.len * ch, 0.0);
                 w.write_all(&par_encode_seg(
                     &buf,
                     enc_off,
                     bframes - enc_off,
                     ch,
                     brate,
+                    &stream.opus,
                     first,
                 )?)?;
             }
@@ -446,36 +632,59 @@ fn fused_encode(
     })
 }
 
-const CHUNK_FRAMES: usize = 500;
-const PREROLL: usize = 25;
-const POSTROLL: usize = 2;
+const CHUNK_SAMP: usize = 480_000;
+const PREROLL_SAMP: usize = 24_000;
+const POSTROLL_SAMP: usize = 1920;
+
+#[derive(Clone, Copy)]
+struct Framing {
+    len: usize,
+    chunk: usize,
+    pre: usize,
+    post: usize,
+}
+
+impl Framing {
+    const fn new(len: usize) -> Self {
+        Self {
+            len,
+            chunk: CHUNK_SAMP / len,
+            pre: PREROLL_SAMP.div_ceil(len),
+            post: POSTROLL_SAMP.div_ceil(len),
+        }
+    }
+}
 
 fn chunk_encode(
     pcm: &mut Vec<f32>,
     out_ch: usize,
     brate: u16,
+    opts: &OpusOpts,
     out: &Path,
     tid: u8,
     progs_line: usize,
 ) -> Result<(), Xerr> {
-    let nframes = (pcm.len() / out_ch).div_ceil(FRAME);
-    let stride = FRAME * out_ch;
+    let fr = Framing::new(opts.frame);
+    let nframes = (pcm.len() / out_ch).div_ceil(fr.len);
+    let stride = fr.len * out_ch;
     pcm.resize(nframes * stride, 0.0);
     let pcm = &*pcm;
-    let k = nframes.div_ceil(CHUNK_FRAMES).max(1);
+    let k = nframes.div_ceil(fr.chunk).max(1);
     let mut results: Vec<Vec<u8>> = vec![Vec::new(); k];
     let base = results.as_mut_ptr() as usize;
     let counter = AtomicUsize::new(0);
     let done = AtomicUsize::new(0);
     let failed = AtomicBool::new(false);
     let fin = AtomicBool::new(false);
-    let samples = nframes * FRAME;
+    let samples = nframes * fr.len;
     let nthreads = available_parallelism().min(k);
     let seg = Seg {
         keep_off: 0,
         nkeep: nframes,
         out_ch,
         brate,
+        opts,
+        fr,
         head: true,
     };
 
@@ -483,7 +692,7 @@ fn chunk_encode(
         let mut handles = Vec::with_capacity(nthreads);
         for _ in 0..nthreads {
             handles.push(s.spawn(|| {
-                let mut pkt = vec![0u8; out_ch * 1276 + 256];
+                let mut pkt = vec![0u8; out_ch * MAX_PKT + 256];
                 loop {
                     let c = counter.fetch_add(1, Relaxed);
                     if c >= k || failed.load(Relaxed) {
@@ -496,7 +705,7 @@ fn chunk_encode(
                             return Err(e);
                         }
                     }
-                    done.fetch_add(CHUNK_FRAMES * FRAME, Relaxed);
+                    done.fetch_add(fr.chunk * fr.len, Relaxed);
                 }
                 Ok::<(), Xerr>(())
             }));
@@ -516,22 +725,25 @@ fn chunk_encode(
     Ok(())
 }
 
-struct Seg {
+struct Seg<'a> {
     keep_off: usize,
     nkeep: usize,
     out_ch: usize,
     brate: u16,
+    opts: &'a OpusOpts,
+    fr: Framing,
     head: bool,
 }
 
 fn encode_chunk(pcm: &[f32], c: usize, seg: &Seg, pkt: &mut [u8]) -> Result<Vec<u8>, Xerr> {
-    let stride = FRAME * seg.out_ch;
+    let fr = seg.fr;
+    let stride = fr.len * seg.out_ch;
     let total = pcm.len() / stride;
-    let keep_start = seg.keep_off + c * CHUNK_FRAMES;
-    let keep_end = (seg.keep_off + (c + 1) * CHUNK_FRAMES).min(seg.keep_off + seg.nkeep);
-    let fed_start = keep_start.saturating_sub(PREROLL);
-    let fed_end = (keep_end + POSTROLL).min(total);
-    let mut enc = Encoder::new(seg.out_ch as u8, seg.brate)?;
+    let keep_start = seg.keep_off + c * fr.chunk;
+    let keep_end = (seg.keep_off + (c + 1) * fr.chunk).min(seg.keep_off + seg.nkeep);
+    let fed_start = keep_start.saturating_sub(fr.pre);
+    let fed_end = (keep_end + fr.post).min(total);
+    let mut enc = Encoder::new(seg.out_ch as u8, seg.brate, seg.opts)?;
     let mut out = Vec::with_capacity((keep_end - keep_start) * (seg.brate as usize * 3));
     if seg.head && c == 0 {
         let h = enc.head();
@@ -567,9 +779,11 @@ fn par_encode_seg(
     nkeep: usize,
     out_ch: usize,
     brate: u16,
+    opts: &OpusOpts,
     head: bool,
 ) -> Result<Vec<u8>, Xerr> {
-    let k = nkeep.div_ceil(CHUNK_FRAMES).max(1);
+    let fr = Framing::new(opts.frame);
+    let k = nkeep.div_ceil(fr.chunk).max(1);
     let mut results: Vec<Vec<u8>> = vec![Vec::new(); k];
     let base = results.as_mut_ptr() as usize;
     let counter = AtomicUsize::new(0);
@@ -580,6 +794,8 @@ fn par_encode_seg(
         nkeep,
         out_ch,
         brate,
+        opts,
+        fr,
         head,
     };
 
@@ -587,7 +803,7 @@ fn par_encode_seg(
         let mut handles = Vec::with_capacity(nthreads);
         for _ in 0..nthreads {
             handles.push(s.spawn(|| {
-                let mut pkt = vec![0u8; out_ch * 1276 + 256];
+                let mut pkt = vec![0u8; out_ch * MAX_PKT + 256];
                 loop {
                     let c = counter.fetch_add(1, Relaxed);
                     if c >= k || failed.load(Relaxed) {
@@ -614,6 +830,63 @@ fn par_encode_seg(
     Ok(out)
 }
 
+impl AuSel {
+    fn keeps(&self, s: &AuStream) -> bool {
+        let lang = s.lang.as_deref().unwrap_or("und");
+        (self.ids.is_empty() || self.ids.contains(&s.index))
+            && (self.langs.is_empty() || self.langs.iter().any(|w| lang_matches(lang, w)))
+            && (self.max_ch == 0 || s.channels <= self.max_ch)
+            && !self.exclude.iter().any(|kw| {
+                (kw == "commentary" && s.comment)
+                    || s.title
+                        .as_ref()
+                        .is_some_and(|t| t.to_ascii_lowercase().contains(kw.as_str()))
+            })
+    }
+
+    // Listed languages keep their order of preference; ties stay in source order
+    fn resolve<'a>(&self, all: &'a [AuStream]) -> Vec<&'a AuStream> {
+        fn lang(s: &AuStream) -> &str {
+            s.lang.as_deref().unwrap_or("und")
+        }
+        let mut out: Vec<_> = all.iter().filter(|s| self.keeps(s)).collect();
+        if !self.langs.is_empty() {
+            out.sort_by_key(|s| {
+                self.langs
+                    .iter()
+                    .position(|w| lang_matches(lang(s), w))
+                    .unwrap_or(usize::MAX)
+            });
+        }
+        if self.first_per_lang {
+            let mut seen: Vec<String> = Vec::new();
+            out.retain(|s| {
+                let l = to_bcp47(lang(s).split('-').next().unwrap_or_default()).into_owned();
+                let new = !seen.contains(&l);
+                if new {
+                    seen.push(l);
+                }
+                new
+            });
+        }
+        out
+    }
+}
+
+fn select<'a>(all: &'a [AuStream], streams: &AuStreams) -> Result<Vec<&'a AuStream>, Xerr> {
+    match *streams {
+        AuStreams::All => Ok(all.iter().collect()),
+        AuStreams::Specific(ref ids) => Ok(all.iter().filter(|s| ids.contains(&s.index)).collect()),
+        AuStreams::Select(ref sel) => {
+            let out = sel.resolve(all);
+            if out.is_empty() {
+                return Err("No audio stream matches the -a selector".into());
+            }
+            Ok(out)
+        }
+    }
+}
+
 struct TrackJob {
     stream: AuStream,
     do_norm: bool,
@@ -630,10 +903,7 @@ pub fn enc_au_streams(
     progs_line: usize,
 ) -> Result<Vec<(AuStream, PathBuf)>, Xerr> {
     let all = get_streams(inp)?;
-    let sel: Vec<_> = match spec.streams {
-        AuStreams::All => all.iter().collect(),
-        AuStreams::Specific(ref ids) => all.iter().filter(|s| ids.contains(&s.index)).collect(),
-    };
+    let sel = select(&all, &spec.streams)?;
 
     let norm_params = match spec.brate {
         AuBrate::Norm(p) => Some(p),
@@ -644,7 +914,8 @@ pub fn enc_au_streams(
         .iter()
         .enumerate()
         .map(|(i, s)| {
-            let np = norm_params.filter(|_| s.channels > 2);
+            let opus = spec.opus_for(s.index);
+            let np = norm_params.filter(|_| s.channels > 2 && opus.surround(s.channels));
             let do_norm = np.is_some();
             let brate = np.map_or_else(
                 || match spec.brate {
@@ -668,6 +939,7 @@ pub fn enc_au_streams(
             );
             let mut stream = (*s).clone();
             stream.bitrate = brate;
+            stream.opus = opus;
             TrackJob {
                 stream,
                 do_norm,
@@ -697,3 +969,112 @@ pub fn enc_au_streams(
         })
         .collect()
 }
+
+const TL_STEP: usize = 10;
+const TL_WIN: usize = 30;
+
+pub struct AuLoud {
+    pub stream: AuStream,
+    pub lufs: f32,
+    pub lra: f32,
+    pub true_pk: f32,
+    pub samp_pk: f32,
+}
+
+pub const fn layout(ch: u8) -> &'static str {
+    match ch {
+        1 => "mono",
+        2 => "stereo",
+        3 => "2.1",
+        4 => "3.1",
+        5 => "4.1",
+        6 => "5.1",
+        7 => "6.1",
+        8 => "7.1",
+        _ => "discrete",
+    }
+}
+
+pub fn to_db(v: f32) -> Option<f32> {
+    (v > 0.0).then(|| (20.0 * f64::from(v).log10()) as f32)
+}
+
+fn fmt_db(v: Option<f32>) -> String {
+    v.map_or_else(|| "null".into(), |d| format!("{d:.2}"))
+}
+
+pub fn au_report(
+    streams: &AuStreams,
+    inp: &Path,
+    out: &Path,
+    progs_line: usize,
+) -> Result<Vec<AuLoud>, Xerr> {
+    let all = get_streams(inp)?;
+    let sel = select(&all, streams)?;
+    let mut res = Vec::with_capacity(sel.len());
+    let mut js = String::new();
+    _ = writeln!(js, "{{");
+    _ = writeln!(js, "  \"input\": {},", json_str(&inp.to_string_lossy()));
+    _ = writeln!(js, "  \"streams\": [");
+    for (i, s) in sel.iter().enumerate() {
+        let whole = [(0, AuDecoder::new(inp, i32::from(s.index))?.tot_samples())];
+        let pcm = par_decode(inp, s, &whole, progs_line + i)?;
+        let (lufs, lra) = measure(&pcm);
+        let (true_pk, samp_pk) = peaks(&pcm);
+        let tl = timeline(&pcm, TL_STEP, TL_WIN)
+            .into_iter()
+            .map(|v| v.map_or_else(|| "null".into(), |l| format!("{l:.2}")))
+            .collect::<Vec<_>>()
+            .join(",");
+        _ = writeln!(js, "    {{");
+        _ = writeln!(js, "      \"index\": {},", s.index);
+        _ = writeln!(
+            js,
+            "      \"lang\": {},",
+            json_str(s.lang.as_deref().unwrap_or("und"))
+        );
+        _ = writeln!(js, "      \"channels\": {},", s.channels);
+        _ = writeln!(js, "      \"layout\": \"{}\",", layout(s.channels));
+        _ = writeln!(
+            js,
+            "      \"duration\": {:.3},",
+            (pcm.len() / 2) as f64 / 48000.0
+        );
+        _ = writeln!(js, "      \"integrated_lufs\": {lufs:.2},");
+        _ = writeln!(js, "      \"lra_lu\": {lra:.2},");
+        _ = writeln!(js, "      \"true_peak_dbtp\": {},", fmt_db(to_db(true_pk)));
+        _ = writeln!(
+            js,
+            "      \"sample_peak_dbfs\": {},",
+            fmt_db(to_db(samp_pk))
+        );
+        _ = writeln!(
+            js,
+            "      \"timeline\": {{ \"step_s\": {:.1}, \"window_s\": {:.1}, \"lufs\": [{tl}] }}",
+            TL_STEP as f32 / 10.0,
+            TL_WIN as f32 / 10.0
+        );
+        let comma = if i + 1 < sel.len() { "," } else { "" };
+        _ = writeln!(js, "    }}{comma}");
+        res.push(AuLoud {
+            stream: (*s).clone(),
+            lufs,
+            lra,
+            true_pk,
+            samp_pk,
+        });
+    }
+    _ = writeln!(js, "  ]");
+    _ = write!(js, "}}");
+    write(out, js)?;
+    Ok(res)
+}
+
+#[cfg(test)]
+pub mod test_access {
+    use super::*;
+
+    pub fn select<'a>(all: &'a [AuStream], streams: &AuStreams) -> Result<Vec<&'a AuStream>, Xerr> {
+        super::select(all, streams)
+    }
+}
@@ -0,0 +1,395 @@
+#[cfg(target_os = "linux")]
+use alloc::{boxed::Box, collections::BTreeMap, string::String, vec::Vec};
+use alloc::{collections::BTreeSet, sync::Arc};
+use core::{
+    cell::RefCell,
+    fmt::Write as _,
+    sync::atomic::{AtomicU64, AtomicUsize, Ordering::Relaxed},
+};
+
+use crate::{
+    Args,
+    chan::{Semaphore, SeqRing, mpmc_close, mpmc_recv, mpmc_send, sem_release},
+    chunk::Chunk,
+    ctl,
+    dec::dec_chnks,
+    enc::unpack_exact,
+    error::Xerr,
+    events::emit,
+    ffms::{VidInf, get_dec_strat},
+    fs::write,
+    path::{Path, PathBuf},
+    pipeline::{MetricProgs, Pipeline},
+    progs::ProgsTrack,
+    sync::Mutex,
+    thread::{available_parallelism, scope},
+    tq::{
+        Agg, Also, Metric, ProbeDec, ScoreSpec, calc_butter_8b_ff, calc_butter_10b_ff,
+        calc_butter_rem_ff, calc_ssimu2_8b_ff, calc_ssimu2_10b_ff, calc_ssimu2_rem_ff, make_ff,
+        prep_out, reduce,
+    },
+    util::json_str,
+    vship::{PinnedBuf, VshipProcessor, init_device},
+    worker::WorkPkg,
+};
+
+type CalcFn = fn(
+    &WorkPkg,
+    &mut ProbeDec,
+    &Pipeline,
+    &VshipProcessor,
+    &ScoreSpec,
+    &mut [u8],
+    &MetricProgs,
+) -> (f32, Vec<f32>);
+
+// Per-frame scores of each chunk, one list per `--audit` metric
+type Scores = BTreeMap<u16, Vec<Vec<f32>>>;
+
+// How many of the lowest-quality frames the report lists per metric
+const WORST_CNT: usize = 10;
+
+// `--audit ssimu2,butter`; CVVDP pools over time, so it has no per-frame scores to report
+pub fn parse_audit(s: &str) -> Result<Vec<Metric>, Xerr> {
+    let mut out = Vec::new();
+    for m in s.split(',').map(str::trim) {
+        let m = match m {
+            "ssimu2" => Metric::Ssimu2,
+            "butter" => Metric::Butter,
+            _ => return Err(format!("Invalid --audit metric: {m} (ssimu2 or butter)").into()),
+        };
+        if !out.contains(&m) {
+            out.push(m);
+        }
+    }
+    Ok(out)
+}
+
+// The output is a container, so libavcodec decodes it whatever the codec (dav1d inside for AV1)
+#[cold]
+fn calc_fn(m: Metric, inf: &VidInf, pipe: &Pipeline) -> CalcFn {
+    let shape = if !inf.is_10b {
+        0
+    } else if unpack_exact(pipe) {
+        1
+    } else {
+        2
+    };
+    match (m == Metric::Butter, shape) {
+        (true, 0) => calc_butter_8b_ff,
+        (true, 1) => calc_butter_10b_ff,
+        (true, _) => calc_butter_rem_ff,
+        (false, 0) => calc_ssimu2_8b_ff,
+        (false, 1) => calc_ssimu2_10b_ff,
+        (false, _) => calc_ssimu2_rem_ff,
+    }
+}
+
+struct AuditCtx<'a> {
+    inf: &'a VidInf,
+    pipe: &'a Pipeline,
+    metrics: &'a [Metric],
+    calc: CalcFn,
+    // the muxed output & each chunk's first frame in it
+    out: &'a Path,
+    offs: &'a BTreeMap<u16, usize>,
+    prog: &'a ProgsTrack,
+    done: (&'a AtomicUsize, &'a AtomicUsize),
+    permits: &'a Semaphore,
+    scores: &'a Mutex<Scores>,
+    err: &'a Mutex<Option<Xerr>>,
+}
+
+// Scores each chunk the decoder hands over against its frames in the output, the first metric
+// through `calc` & the rest as `--tq-and`-style extras of the same pass
+fn audit_loop(rx: &SeqRing, ctx: &AuditCtx, slot: usize) {
+    if let Err(e) = score_loop(rx, ctx, slot) {
+        ctx.err.lock().get_or_insert(e);
+        // drained so the decoder never waits on a permit
+        loop {
+            let m = unsafe { mpmc_recv(rx) };
+            if m == 0 {
+                break;
+            }
+            drop(unsafe { Box::from_raw(m as *mut WorkPkg) });
+            sem_release(ctx.permits);
+        }
+    }
+}
+
+fn vship_for<'v>(
+    v: &'v mut Option<VshipProcessor>,
+    pkg: &WorkPkg,
+    ctx: &AuditCtx,
+) -> Result<&'v VshipProcessor, Xerr> {
+    if v.is_none() {
+        let has = |m| ctx.metrics.contains(&m);
+        *v = Some(VshipProcessor::new(
+            pkg.width,
+            pkg.height,
+            ctx.inf,
+            [has(Metric::Ssimu2), false, has(Metric::Butter)],
+            None,
+        )?);
+    }
+    Ok(unsafe { v.as_ref().unwrap_unchecked() })
+}
+
+fn score_loop(rx: &SeqRing, ctx: &AuditCtx, slot: usize) -> Result<(), Xerr> {
+    let mut dec = make_ff(available_parallelism() as i32);
+    let mut vship: Option<VshipProcessor> = None;
+    let mut unpacked_buf = PinnedBuf::new(ctx.pipe.unpack_buf_sz)?;
+    let also: Vec<Also> = ctx
+        .metrics
+        .iter()
+        .skip(1)
+        .map(|&metric| Also {
+            metric,
+            agg: Agg::Mean,
+            ge: true,
+            val: 0.0,
+        })
+        .collect();
+    let keep = RefCell::new(Vec::new());
+    let spec = ScoreSpec {
+        mode: Agg::Mean,
+        also: &also,
+        fps: ctx.inf.fps_num as f32 / ctx.inf.fps_den as f32,
+        stride: 1,
+        pick: None,
+        keep: Some(&keep),
+    };
+
+    loop {
+        let m = unsafe { mpmc_recv(rx) };
+        if m == 0 {
+            break;
+        }
+        let pkg = unsafe { Box::from_raw(m as *mut WorkPkg) };
+        if !ctl::stopping() {
+            let at = ctx.offs.get(&pkg.chnk.idx).copied().unwrap_or(0);
+            let v = match prep_out(&mut dec, ctx.out, at)
+                .and_then(|()| vship_for(&mut vship, &pkg, ctx))
+            {
+                Ok(v) => v,
+                Err(e) => {
+                    sem_release(ctx.permits);
+                    return Err(e);
+                }
+            };
+            let mp = MetricProgs {
+                prog: ctx.prog,
+                slot,
+                crf: None,
+                last_score: None,
+            };
+            (ctx.calc)(&pkg, &mut dec, ctx.pipe, v, &spec, &mut unpacked_buf, &mp);
+            ctx.scores.lock().insert(pkg.chnk.idx, keep.take());
+            ctx.done.0.fetch_add(1, Relaxed);
+            ctx.done.1.fetch_add(pkg.frame_cnt, Relaxed);
+        }
+        sem_release(ctx.permits);
+    }
+    Ok(())
+}
+
+// `--audit`: decodes the muxed output, scores it frame by frame against the cropped source with
+// `-v` metric workers & writes `<output>_audit.json`. None if stopped
+pub fn run(
+    chnks: &[Chunk],
+    inf: &VidInf,
+    args: &Args,
+    crop: (u32, u32),
+) -> Result<Option<PathBuf>, Xerr> {
+    // the same decode & pixel layout TQ scores probes with
+    let strat = get_dec_strat(inf, crop, args.hwdec, true);
+    let pipe = Pipeline::new(inf, strat, None);
+    init_device()?;
+
+    let permits = Arc::new(Semaphore::new(args.chnk_buff));
+    let ring = SeqRing::new();
+    let done = Arc::new(AtomicUsize::new(0));
+    let done_frames = Arc::new(AtomicUsize::new(0));
+    let (prog, display_handle) = ProgsTrack::new(
+        chnks,
+        inf,
+        args.metric_worker,
+        0,
+        Arc::clone(&done),
+        Arc::clone(&done_frames),
+        Arc::new(AtomicU64::new(0)),
+    );
+    let scores = Mutex::new(BTreeMap::new());
+    let err = Mutex::new(None);
+    // chunks follow each other in the output, trims & splices already applied
+    let mut at = 0;
+    let offs: BTreeMap<u16, usize> = chnks
+        .iter()
+        .map(|c| {
+            at += c.end - c.start;
+            (c.idx, at - (c.end - c.start))
+        })
+        .collect();
+    let ctx = AuditCtx {
+        inf,
+        pipe: &pipe,
+        metrics: &args.audit,
+        calc: calc_fn(
+            unsafe { *args.audit.first().unwrap_unchecked() },
+            inf,
+            &pipe,
+        ),
+        out: &args.out,
+        offs: &offs,
+        prog: &prog,
+        done: (&done, &done_frames),
+        permits: &permits,
+        scores: &scores,
+        err: &err,
+    };
+
+    let dec = scope(|s| {
+        let (ring, ctx) = (&ring, &ctx);
+        for slot in 0..args.metric_worker {
+            s.spawn(move || audit_loop(ring, ctx, slot));
+        }
+        let send = |p: WorkPkg| unsafe { mpmc_send(ring, Box::into_raw(Box::new(p)) as u64) };
+        let dec = dec_chnks(
+            chnks,
+            &args.inp,
+            inf,
+            &send,
+            &BTreeSet::new(),
+            strat,
+            &permits,
+        );
+        unsafe { mpmc_close(ring) };
+        dec
+    });
+    drop(prog);
+    display_handle.join();
+
+    dec?;
+    if let Some(e) = err.lock().take() {
+        return Err(e);
+    }
+    if ctl::stopping() {
+        return Ok(None);
+    }
+    let fps = inf.fps_num as f32 / inf.fps_den as f32;
+    let report = form_report(chnks, &args.audit, &scores.lock(), fps);
+    let stem = unsafe { args.out.file_stem().unwrap_unchecked() }.to_string_lossy();
+    let path = args.out.with_file_name(format!("{stem}_audit.json"));
+    write(&path, report)?;
+    emit(
+        "audit",
+        format_args!(",\"out\":{}", json_str(&path.to_string_lossy())),
+    );
+    Ok(Some(path))
+}
+
+// Mean, mean of the worst 5% & the worst frame, as a JSON object; null for a chunk never scored
+fn stats_json(scores: &[f32], m: Metric, fps: f32) -> String {
+    if scores.is_empty() {
+        return "{ \"mean\": null, \"p5\": null, \"worst\": null }".into();
+    }
+    let hi = m == Metric::Butter;
+    let mut v = scores.to_vec();
+    let worst = if hi { Agg::Max } else { Agg::Min };
+    format!(
+        "{{ \"mean\": {:.3}, \"p5\": {:.3}, \"worst\": {:.3} }}",
+        reduce(&mut v, Agg::Mean, hi, fps),
+        reduce(&mut v, Agg::Pct(5.0), hi, fps),
+        reduce(&mut v, worst, hi, fps)
+    )
+}
+
+fn form_report(chnks: &[Chunk], metrics: &[Metric], scores: &Scores, fps: f32) -> String {
+    let per = |c: &Chunk, i: usize| {
+        scores
+            .get(&c.idx)
+            .and_then(|s| s.get(i))
+            .map_or(&[][..], Vec::as_slice)
+    };
+    let mut out = String::new();
+    _ = writeln!(out, "{{");
+    let names: Vec<_> = metrics
+        .iter()
+        .map(|m| format!("\"{}\"", m.name()))
+        .collect();
+    _ = writeln!(out, "  \"metrics\": [{}],", names.join(", "));
+    _ = writeln!(out);
+
+    _ = writeln!(out, "  \"summary\": {{");
+    for (i, &m) in metrics.iter().enumerate() {
+        let all: Vec<f32> = chnks.iter().flat_map(|c| per(c, i)).copied().collect();
+        let comma = if i + 1 < metrics.len() { "," } else { "" };
+        _ = writeln!(
+            out,
+            "    \"{}\": {}{comma}",
+            m.name(),
+            stats_json(&all, m, fps)
+        );
+    }
+    _ = writeln!(out, "  }},");
+    _ = writeln!(out);
+
+    _ = writeln!(out, "  \"worst_frames\": {{");
+    for (i, &m) in metrics.iter().enumerate() {
+        // (score, source frame, scene)
+        let mut all: Vec<(f32, usize, u16)> = chnks
+            .iter()
+            .flat_map(|c| {
+                per(c, i)
+                    .iter()
+                    .enumerate()
+                    .map(move |(f, &s)| (s, c.start + f, c.idx))
+            })
+            .collect();
+        if m == Metric::Butter {
+            all.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+        } else {
+            all.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+        }
+        all.truncate(WORST_CNT);
+        _ = writeln!(out, "    \"{}\": [", m.name());
+        for (j, &(s, f, id)) in all.iter().enumerate() {
+            let comma = if j + 1 < all.len() { "," } else { "" };
+            _ = writeln!(
+                out,
+                "      {{ \"frame\": {f}, \"scene\": {id}, \"score\": {s:.3} }}{comma}"
+            );
+        }
+        let comma = if i + 1 < metrics.len() { "," } else { "" };
+        _ = writeln!(out, "    ]{comma}");
+    }
+    _ = writeln!(out, "  }},");
+    _ = writeln!(out);
+
+    _ = writeln!(out, "  \"scenes\": [");
+    for (i, c) in chnks.iter().enumerate() {
+        _ = writeln!(out, "    {{");
+        _ = writeln!(
+            out,
+            "      \"id\": {}, \"start\": {}, \"end\": {},",
+            c.idx, c.start, c.end
+        );
+        for (j, &m) in metrics.iter().enumerate() {
+            let s = per(c, j);
+            let frames: Vec<_> = s.iter().map(|v| format!("{v:.3}")).collect();
+            let comma = if j + 1 < metrics.len() { "," } else { "" };
+            _ = writeln!(out, "      \"{}\": {},", m.name(), stats_json(s, m, fps));
+            _ = writeln!(
+                out,
+                "      \"{}_frames\": [{}]{comma}",
+                m.name(),
+                frames.join(", ")
+            );
+        }
+        let comma = if i + 1 < chnks.len() { "," } else { "" };
+        _ = writeln!(out, "    }}{comma}");
+    }
+    _ = writeln!(out, "  ]");
+    _ = writeln!(out, "}}");
+    out
+}
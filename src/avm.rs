@@ -1,5 +1,5 @@
 #[cfg(target_os = "linux")]
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use core::{
     ffi::{c_char, c_void},
     hint::cold_path,
@@ -381,6 +381,36 @@ pub fn set_avm_base(c: &mut AvmCodecEncCfg, inf: &VidInf, w: u32, h: u32) -> [i3
     ]
 }
 
+// `avmenc` options for what `set_avm_base` sets in-process, so a `--dump-failed` repro
+// runs the same encode outside xav; size, frame rate & range come from the Y4M header
+pub fn avm_app_args(inf: &VidInf, frames: usize) -> Vec<String> {
+    vec![
+        "--obu".into(),
+        "--threads=1".into(),
+        "--bit-depth=10".into(),
+        "--input-bit-depth=10".into(),
+        "--end-usage=q".into(),
+        "--disable-kf".into(),
+        format!("--limit={frames}"),
+        format!(
+            "--color-primaries={}",
+            cicp(inf.color_primaries, 0x0040_1FF2)
+        ),
+        format!(
+            "--transfer-characteristics={}",
+            cicp(inf.transfer_characteristics, 0x0007_FFF2)
+        ),
+        format!(
+            "--matrix-coefficients={}",
+            cicp(inf.matrix_coefficients, 0x0000_7FF3)
+        ),
+        format!(
+            "--chroma-sample-position={}",
+            csp(inf.chroma_sample_position)
+        ),
+    ]
+}
+
 // `known`: bitmask of the CICP codepoints; else UNSPECIFIED (2).
 // fold the negative and the oor test into one compare
 const fn cicp(v: i8, known: u32) -> i32 {
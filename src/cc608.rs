@@ -0,0 +1,350 @@
+#[cfg(target_os = "linux")]
+use alloc::{string::String, vec::Vec};
+use core::mem::swap;
+
+const ROWS: usize = 15;
+const COLS: usize = 32;
+const NO_CC: u32 = 600;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    ch: char,
+    it: bool,
+}
+
+const EMPTY: Cell = Cell {
+    ch: '\0',
+    it: false,
+};
+
+type Screen = [[Cell; COLS]; ROWS];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    PopOn,
+    RollUp(usize),
+    PaintOn,
+    Text,
+}
+
+const SPECIAL: [char; 16] = [
+    '\u{ae}', '\u{b0}', '\u{bd}', '\u{bf}', '\u{2122}', '\u{a2}', '\u{a3}', '\u{266a}', '\u{e0}',
+    ' ', '\u{e8}', '\u{e2}', '\u{ea}', '\u{ee}', '\u{f4}', '\u{fb}',
+];
+
+const EXT_12: [char; 32] = [
+    '\u{c1}', '\u{c9}', '\u{d3}', '\u{da}', '\u{dc}', '\u{fc}', '\u{2018}', '\u{a1}', '*', '\'',
+    '\u{2014}', '\u{a9}', '\u{2120}', '\u{2022}', '\u{201c}', '\u{201d}', '\u{c0}', '\u{c2}',
+    '\u{c7}', '\u{c8}', '\u{ca}', '\u{cb}', '\u{eb}', '\u{ce}', '\u{cf}', '\u{ef}', '\u{d4}',
+    '\u{d9}', '\u{f9}', '\u{db}', '\u{ab}', '\u{bb}',
+];
+
+const EXT_13: [char; 32] = [
+    '\u{c3}', '\u{e3}', '\u{cd}', '\u{cc}', '\u{ec}', '\u{d2}', '\u{f2}', '\u{d5}', '\u{f5}', '{',
+    '}', '\\', '^', '_', '|', '~', '\u{c4}', '\u{e4}', '\u{d6}', '\u{f6}', '\u{df}', '\u{a5}',
+    '\u{a4}', '\u{a6}', '\u{c5}', '\u{e5}', '\u{d8}', '\u{f8}', '\u{250c}', '\u{2510}', '\u{2514}',
+    '\u{2518}',
+];
+
+// PAC row (1-based) by (first byte & 7, second byte bit 5)
+const PAC_ROW: [usize; 16] = [11, 11, 1, 2, 3, 4, 12, 13, 14, 15, 5, 6, 7, 8, 9, 10];
+
+const fn basic(c: u8) -> char {
+    match c {
+        0x2a => '\u{e1}',
+        0x5c => '\u{e9}',
+        0x5e => '\u{ed}',
+        0x5f => '\u{f3}',
+        0x60 => '\u{fa}',
+        0x7b => '\u{e7}',
+        0x7c => '\u{f7}',
+        0x7d => '\u{d1}',
+        0x7e => '\u{f1}',
+        0x7f => '\u{2588}',
+        _ => c as char,
+    }
+}
+
+// Collects `GA94` cc_data (ATSC A/53) from H.264/HEVC SEI or MPEG-2 user data
+pub struct CcScan {
+    chunks: Vec<(i64, Vec<[u8; 2]>)>,
+    seen: u32,
+}
+
+impl CcScan {
+    pub const fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            seen: 0,
+        }
+    }
+
+    // false once enough packets went by without a single caption byte
+    pub const fn alive(&self) -> bool {
+        !self.chunks.is_empty() || self.seen < NO_CC
+    }
+
+    pub fn feed_es(&mut self, pts: i64, pkt: &[u8]) {
+        self.seen += 1;
+        let mut pairs = Vec::new();
+        let mut p = pkt;
+        while let Some(at) = p.windows(5).position(|w| w == b"GA94\x03") {
+            let body = unescape(&p[at + 5..p.len().min(at + 5 + 2 + 31 * 3 + 8)]);
+            if let [flags, _em, ref cc @ ..] = *body.as_slice()
+                && flags & 0x40 != 0
+            {
+                let n = usize::from(flags & 0x1f).min(cc.len() / 3);
+                triplets(&cc[..n * 3], &mut pairs);
+            }
+            p = &p[at + 5..];
+        }
+        if !pairs.is_empty() {
+            self.chunks.push((pts, pairs));
+        }
+    }
+
+    // Packets of an `eia_608` track are already cc_data triplets
+    pub fn feed_cc(&mut self, pts: i64, pkt: &[u8]) {
+        let mut pairs = Vec::new();
+        triplets(pkt, &mut pairs);
+        if !pairs.is_empty() {
+            self.chunks.push((pts, pairs));
+        }
+    }
+
+    pub fn decode(mut self) -> Vec<(i64, i64, String)> {
+        self.chunks.sort_by_key(|c| c.0);
+        let mut d = Dec::new();
+        let (mut prev, mut last) = (0, 0);
+        for &(pts, ref pairs) in &self.chunks {
+            for &pair in pairs {
+                d.feed(pts, pair);
+            }
+            (prev, last) = (last, pts);
+        }
+        d.sync(last);
+        // still on screen at EOF: it stays up for one more packet
+        if let Some((t0, s)) = d.shown.take()
+            && !s.is_empty()
+        {
+            d.cues.push((t0, last.max(t0) + (last - prev).max(1), s));
+        }
+        d.cues
+    }
+}
+
+fn triplets(cc: &[u8], out: &mut Vec<[u8; 2]>) {
+    for t in cc.chunks_exact(3) {
+        // cc_valid with cc_type 0: NTSC line 21 field 1 (CC1/CC2)
+        if let [k, b1, b2] = *t
+            && k & 0x07 == 0x04
+        {
+            let pair = [b1 & 0x7f, b2 & 0x7f];
+            if pair != [0, 0] {
+                out.push(pair);
+            }
+        }
+    }
+}
+
+fn unescape(b: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(b.len());
+    for &x in b {
+        if x == 3 && out.ends_with(&[0, 0]) {
+            continue;
+        }
+        out.push(x);
+    }
+    out
+}
+
+struct Dec {
+    disp: Screen,
+    back: Screen,
+    mode: Mode,
+    row: usize,
+    col: usize,
+    it: bool,
+    chan1: bool,
+    last: [u8; 2],
+    shown: Option<(i64, String)>,
+    cues: Vec<(i64, i64, String)>,
+}
+
+impl Dec {
+    const fn new() -> Self {
+        Self {
+            disp: [[EMPTY; COLS]; ROWS],
+            back: [[EMPTY; COLS]; ROWS],
+            mode: Mode::PopOn,
+            row: ROWS - 1,
+            col: 0,
+            it: false,
+            chan1: true,
+            last: [0, 0],
+            shown: None,
+            cues: Vec::new(),
+        }
+    }
+
+    const fn buf(&mut self) -> &mut Screen {
+        if matches!(self.mode, Mode::PopOn) {
+            &mut self.back
+        } else {
+            &mut self.disp
+        }
+    }
+
+    const fn put(&mut self, ch: char) {
+        if self.col < COLS {
+            let (row, col, it) = (self.row, self.col, self.it);
+            self.buf()[row][col] = Cell { ch, it };
+            self.col += 1;
+        }
+    }
+
+    fn feed(&mut self, pts: i64, [a, b]: [u8; 2]) {
+        if (0x10..=0x1f).contains(&a) {
+            // control codes are sent twice for redundancy
+            if self.last == [a, b] {
+                self.last = [0, 0];
+                return;
+            }
+            self.last = [a, b];
+            self.chan1 = a < 0x18;
+            if self.chan1 {
+                self.control(a & 0x17, b);
+                self.sync(pts);
+            }
+            return;
+        }
+        self.last = [0, 0];
+        if !self.chan1 || self.mode == Mode::Text || a < 0x20 {
+            return;
+        }
+        self.put(basic(a));
+        if b >= 0x20 {
+            self.put(basic(b));
+        }
+    }
+
+    fn control(&mut self, a: u8, b: u8) {
+        match (a, b) {
+            (0x14, 0x20..=0x2f) => self.misc(b),
+            (0x17, 0x21..=0x23) => self.col = (self.col + usize::from(b - 0x20)).min(COLS - 1),
+            (0x11, 0x20..=0x2f) => {
+                self.it = b & 0x0e == 0x0e;
+                self.put(' ');
+            }
+            (0x11, 0x30..=0x3f) => self.put(SPECIAL[usize::from(b - 0x30)]),
+            (0x12 | 0x13, 0x20..=0x3f) => {
+                self.col = self.col.saturating_sub(1);
+                let t = if a == 0x12 { &EXT_12 } else { &EXT_13 };
+                self.put(t[usize::from(b - 0x20)]);
+            }
+            (_, 0x40..=0x7f) => {
+                let row = PAC_ROW[(usize::from(a & 7) << 1) | usize::from((b >> 5) & 1)];
+                self.row = row - 1;
+                let attr = b & 0x1f;
+                if attr < 0x10 {
+                    self.it = attr & 0x0e == 0x0e;
+                    self.col = 0;
+                } else {
+                    self.it = false;
+                    self.col = usize::from(attr & 0x0e) * 2;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn misc(&mut self, b: u8) {
+        match b {
+            0x20 => self.mode = Mode::PopOn,
+            0x21 => {
+                if self.col > 0 {
+                    self.col -= 1;
+                    let (row, col) = (self.row, self.col);
+                    self.buf()[row][col] = EMPTY;
+                }
+            }
+            0x24 => {
+                let (row, col) = (self.row, self.col);
+                self.buf()[row][col..].fill(EMPTY);
+            }
+            0x25..=0x27 => {
+                if !matches!(self.mode, Mode::RollUp(_)) {
+                    self.disp = [[EMPTY; COLS]; ROWS];
+                    self.back = [[EMPTY; COLS]; ROWS];
+                    self.row = ROWS - 1;
+                }
+                self.mode = Mode::RollUp(usize::from(b - 0x23));
+                self.col = 0;
+            }
+            0x29 => self.mode = Mode::PaintOn,
+            0x2a | 0x2b => self.mode = Mode::Text,
+            0x2c => self.disp = [[EMPTY; COLS]; ROWS],
+            0x2d => {
+                if let Mode::RollUp(n) = self.mode {
+                    let top = (self.row + 1).saturating_sub(n);
+                    for r in top..self.row {
+                        self.disp[r] = self.disp[r + 1];
+                    }
+                    self.disp[..top].fill([EMPTY; COLS]);
+                    self.disp[self.row] = [EMPTY; COLS];
+                    self.col = 0;
+                }
+            }
+            0x2e => self.back = [[EMPTY; COLS]; ROWS],
+            0x2f => {
+                swap(&mut self.disp, &mut self.back);
+                self.mode = Mode::PopOn;
+            }
+            _ => {}
+        }
+    }
+
+    fn sync(&mut self, pts: i64) {
+        let text = render(&self.disp);
+        match self.shown.take() {
+            Some((t0, s)) if s == text => self.shown = Some((t0, s)),
+            Some((t0, s)) => {
+                if !s.is_empty() && pts > t0 {
+                    self.cues.push((t0, pts, s));
+                }
+                self.shown = Some((pts, text));
+            }
+            None => self.shown = Some((pts, text)),
+        }
+    }
+}
+
+fn render(s: &Screen) -> String {
+    let mut out = String::new();
+    for row in s {
+        let Some(first) = row.iter().position(|c| c.ch != '\0') else {
+            continue;
+        };
+        let last = row.iter().rposition(|c| c.ch != '\0').unwrap_or(first);
+        let mut line = String::new();
+        let mut it = false;
+        for c in &row[first..=last] {
+            if c.it != it {
+                line.push_str(if c.it { "<i>" } else { "</i>" });
+                it = c.it;
+            }
+            line.push(if c.ch == '\0' { ' ' } else { c.ch });
+        }
+        if it {
+            line.push_str("</i>");
+        }
+        let line = line.trim();
+        if !line.is_empty() {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push_str(line);
+        }
+    }
+    out
+}
@@ -1,3 +1,5 @@
+use core::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
+
 unsafe extern "C" {
     #[cfg(test)]
     fn xav_spsc_send(r: *const SpscRing, x: u64);
@@ -81,11 +83,52 @@ pub unsafe fn mpsc_recv(r: *const SeqRing) -> u64 {
     unsafe { xav_mpsc_recv(r) }
 }
 
+// False once the semaphore was closed: the holder should stop taking work
 #[inline(always)]
-pub fn sem_acq(s: &Semaphore) {
+#[must_use]
+pub fn sem_acq(s: &Semaphore) -> bool {
     unsafe { xav_sem_acq(s) };
+    s.closed.load(Acquire) == 0
 }
 #[inline(always)]
 pub fn sem_release(s: &Semaphore) {
+    if s.debt.load(Relaxed) != 0 && pay_debt(s) {
+        return;
+    }
+    unsafe { xav_sem_release(s) };
+}
+
+#[cold]
+#[inline(never)]
+fn pay_debt(s: &Semaphore) -> bool {
+    s.debt
+        .fetch_update(Relaxed, Relaxed, |d| d.checked_sub(1))
+        .is_ok()
+}
+
+// Wakes the one blocked `sem_acq` (the decoder's) with a permit that reports the close
+#[cold]
+#[inline(never)]
+pub fn sem_close(s: &Semaphore) {
+    s.closed.store(1, Release);
     unsafe { xav_sem_release(s) };
 }
+
+// Grow or shrink the permit pool while holders are live
+#[cold]
+#[inline(never)]
+pub fn sem_resize(s: &Semaphore, delta: isize) {
+    for _ in 0..delta.unsigned_abs() {
+        if delta > 0 {
+            if !pay_debt(s) {
+                unsafe { xav_sem_release(s) };
+            }
+        } else if s
+            .count
+            .fetch_update(AcqRel, Relaxed, |c| c.checked_sub(1))
+            .is_err()
+        {
+            s.debt.fetch_add(1, Relaxed);
+        }
+    }
+}
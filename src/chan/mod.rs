@@ -89,6 +89,10 @@ impl SeqRing {
 pub struct Semaphore {
     count: AtomicU32,
     waiters: AtomicU32,
+    // permits still owed after a shrink; paid out of later releases
+    debt: AtomicU32,
+    // set by `sem_close`: every `sem_acq` from then on reports it
+    closed: AtomicU32,
 }
 
 impl Semaphore {
@@ -97,6 +101,8 @@ impl Semaphore {
         Self {
             count: AtomicU32::new(permits as u32),
             waiters: AtomicU32::new(0),
+            debt: AtomicU32::new(0),
+            closed: AtomicU32::new(0),
         }
     }
 }
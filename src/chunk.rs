@@ -1,7 +1,9 @@
+use alloc::collections::BTreeMap;
 #[cfg(target_os = "linux")]
-use alloc::{boxed::Box, string::String, vec::Vec};
+use alloc::{boxed::Box, string::String, sync::Arc, vec::Vec};
 use core::{
     fmt::Write as _,
+    mem::ManuallyDrop,
     sync::atomic::{AtomicU64, Ordering::Relaxed},
 };
 
@@ -11,20 +13,23 @@ use crate::{
     clk::Mono,
     copy::{demux, read_chapters},
     error::Xerr,
+    events::draw,
     ffms::{AVMEDIA_TYPE_AUDIO, VidInf},
     fs::{read_dir, read_to_string as read_to_str, write},
     io::{Write as _, print_fmt, stdout},
     mkv_mux::{AudioSrc, Aux, mux_mkv},
     mux_webm::mux_webm,
     path::{Path, PathBuf},
-    sync::OnceLock,
+    report::Runs,
+    sync::Mutex,
 };
 
 pub static PRIOR_SECS: AtomicU64 = AtomicU64::new(0);
-static ENC_START: OnceLock<Mono> = OnceLock::new();
+// set per run: a `--queue` encodes several inputs in one process
+static ENC_START: Mutex<Option<Mono>> = Mutex::new(None);
 pub fn init_elapsed(prior: u64) {
     PRIOR_SECS.store(prior, Relaxed);
-    _ = ENC_START.set(Mono::now());
+    *ENC_START.lock() = Some(Mono::now());
 }
 
 #[derive(Clone)]
@@ -147,6 +152,50 @@ pub fn zone_tmpls(chnks: &mut [Chunk]) -> Vec<Box<str>> {
     zones
 }
 
+// `--heavy-first` order: predicted encode cost, highest first, so the longest jobs start while
+// every worker is busy instead of leaving one running alone at the end. Cost is frames scaled
+// by SCD activity against the mean; chunks timed in `encodes.txt` by an earlier run use their
+// wall seconds & the rest are scaled to seconds by how those compared to their estimates
+pub fn by_cost(chnks: &[Chunk], wt: &[f32], runs: &Runs) -> Vec<Chunk> {
+    let act = |c: &Chunk| {
+        let (mut sum, mut n) = (0.0, 0u32);
+        for &w in wt.get(c.start + 1..c.end).unwrap_or(&[]) {
+            if w >= 0.0 {
+                sum += w;
+                n += 1;
+            }
+        }
+        if n == 0 { 0.0 } else { sum / n as f32 }
+    };
+    let acts: Vec<f32> = chnks.iter().map(act).collect();
+    let mean = acts.iter().sum::<f32>() / acts.len().max(1) as f32;
+    let est: Vec<f32> = chnks
+        .iter()
+        .zip(&acts)
+        .map(|(c, &a)| {
+            let scale = if mean > 0.0 { 1.0 + a / mean } else { 1.0 };
+            (c.end - c.start) as f32 * scale
+        })
+        .collect();
+
+    let (mut secs, mut base) = (0.0, 0.0);
+    for (c, &e) in chnks.iter().zip(&est) {
+        if let Some(r) = runs.get(&c.idx) {
+            secs += r.1;
+            base += e;
+        }
+    }
+    let rate = if base > 0.0 { secs / base } else { 1.0 };
+
+    let mut order: Vec<(f32, &Chunk)> = chnks
+        .iter()
+        .zip(&est)
+        .map(|(c, &e)| (runs.get(&c.idx).map_or(e * rate, |r| r.1), c))
+        .collect();
+    order.sort_by(|a, b| b.0.total_cmp(&a.0));
+    order.into_iter().map(|(_, c)| c.clone()).collect()
+}
+
 pub fn get_resume(work_dir: &Path) -> Option<ResumeInf> {
     let path = work_dir.join("done.txt");
     path.exists()
@@ -183,7 +232,7 @@ pub fn get_resume(work_dir: &Path) -> Option<ResumeInf> {
 pub fn save_resume(data: &ResumeInf, work_dir: &Path) -> Result<(), Xerr> {
     let path = work_dir.join("done.txt");
     let mut content = String::new();
-    let elapsed = PRIOR_SECS.load(Relaxed) + ENC_START.get().map_or(0, |s| s.elapsed().as_secs());
+    let elapsed = PRIOR_SECS.load(Relaxed) + ENC_START.lock().map_or(0, |s| s.elapsed().as_secs());
     _ = writeln!(content, "elapsed {elapsed}");
 
     for chnk in &data.chnks_done {
@@ -200,6 +249,78 @@ pub fn save_resume(data: &ResumeInf, work_dir: &Path) -> Result<(), Xerr> {
     Ok(())
 }
 
+// `--redo`: listed chunks leave `done.txt`; their `-p` lands in `redo.txt` so it
+// survives an interrupted redo & is appended to their zone params on every run
+pub fn apply_redo(
+    work_dir: &Path,
+    chnks: &mut [Chunk],
+    redo: Option<&(Vec<u16>, String)>,
+) -> Result<(), Xerr> {
+    let path = work_dir.join("redo.txt");
+    let mut over: BTreeMap<u16, String> = read_to_str(&path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|l| {
+            let (i, p) = l.split_once(' ')?;
+            Some((i.parse().ok()?, p.into()))
+        })
+        .collect();
+
+    if let Some(r) = redo {
+        let (idx, params) = (&r.0, &r.1);
+        if let Some(&bad) = idx.iter().find(|&&i| usize::from(i) >= chnks.len()) {
+            return Err(format!(
+                "--redo chunk {bad} does not exist: there are {} chunks, numbered from 0",
+                chnks.len()
+            )
+            .into());
+        }
+        if !params.is_empty() {
+            over.extend(idx.iter().map(|&i| (i, params.clone())));
+            let mut content = String::new();
+            for (i, p) in &over {
+                _ = writeln!(content, "{i} {p}");
+            }
+            write(&path, content)?;
+        }
+        if let Some(mut res) = get_resume(work_dir) {
+            res.chnks_done
+                .retain(|c| idx.binary_search(&c.idx).is_err());
+            save_resume(&res, work_dir)?;
+        }
+    }
+
+    for c in chnks {
+        if let Some(p) = over.get(&c.idx) {
+            c.params = Some(
+                c.params
+                    .as_ref()
+                    .map_or_else(|| p.as_str().into(), |z| format!("{z} {p}").into()),
+            );
+        }
+    }
+    Ok(())
+}
+
+// The resume state an interrupt has to write out before exiting
+static LIVE: Mutex<Option<(Arc<Mutex<ResumeInf>>, PathBuf)>> = Mutex::new(None);
+
+pub fn track_resume(data: &Arc<Mutex<ResumeInf>>, work_dir: &Path) {
+    *LIVE.lock() = Some((Arc::clone(data), work_dir.to_path_buf()));
+}
+
+pub fn untrack_resume() {
+    *LIVE.lock() = None;
+}
+
+// Keeps the lock so no worker rewrites `done.txt` between this and exit
+pub fn flush_resume() {
+    if let Some((ref data, ref work_dir)) = *LIVE.lock() {
+        let g = ManuallyDrop::new(data.lock());
+        _ = save_resume(&g, work_dir);
+    }
+}
+
 pub fn merge_out(
     args: &Args,
     enc_dir: &Path,
@@ -249,8 +370,10 @@ pub fn merge_out(
     };
     let copy_audio = au.is_empty() && want_extras;
     let (audio, subs) = if want_extras {
-        println!();
-        _ = stdout().flush();
+        if draw() {
+            println!();
+            _ = stdout().flush();
+        }
         let streams = demux(src, copy_audio, true)?;
         if copy_audio {
             let (au_s, sub_s): (Vec<_>, Vec<_>) = streams
@@ -263,7 +386,7 @@ pub fn merge_out(
     } else {
         (AudioSrc::Encode(au), Vec::new())
     };
-    if want_extras {
+    if want_extras && draw() {
         println!();
         println!();
         _ = stdout().flush();
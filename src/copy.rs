@@ -9,6 +9,7 @@ use core::{
 
 use crate::{
     byte_range::ByteRange,
+    cc608::CcScan,
     error::Xerr,
     ffms::{
         AV_NOPTS_VALUE, AVCodecParameters, AVFormatContext, AVMEDIA_TYPE_AUDIO,
@@ -20,6 +21,7 @@ use crate::{
     path::Path,
     platform::Mmap,
     progs::ProgsBar,
+    subconv::{cc_stream, convert},
 };
 
 const AVDISCARD_ALL: c_int = 48;
@@ -68,6 +70,7 @@ pub fn demux(inp: &Path, want_audio: bool, want_subs: bool) -> Result<Vec<Stream
         let origin_us = video_origin_us(fmt_ctx);
         let mut routes = vec![None; n];
         let mut streams = Vec::new();
+        let mut cc = None;
         let map = is_matroska(fmt_ctx).then(|| Mmap::open(inp).ok()).flatten();
         let tags = map
             .as_ref()
@@ -77,7 +80,9 @@ pub fn demux(inp: &Path, want_audio: bool, want_subs: bool) -> Result<Vec<Stream
             let par = &*(*st).codecpar;
             let want = (par.codec_type == AVMEDIA_TYPE_AUDIO && want_audio)
                 || (par.codec_type == AVMEDIA_TYPE_SUBTITLE && want_subs);
-            if want {
+            if want_subs && cc.is_none() && par.codec_type == AVMEDIA_TYPE_VIDEO && cc_video(par) {
+                cc = Some((i, describe(st, par, origin_us), CcScan::new()));
+            } else if want {
                 *route = Some(streams.len());
                 let mut s = describe(st, par, origin_us);
                 s.lang = tags
@@ -90,10 +95,21 @@ pub fn demux(inp: &Path, want_audio: bool, want_subs: bool) -> Result<Vec<Stream
                 (*st).discard = AVDISCARD_ALL;
             }
         }
-        if !streams.is_empty() {
-            read_packets(fmt_ctx, &routes, &mut streams);
+        if !streams.is_empty() || cc.is_some() {
+            read_packets(fmt_ctx, &routes, &mut streams, cc.as_mut());
         }
         avformat_close_input(&raw mut fmt_ctx);
+        streams.retain_mut(|s| {
+            s.codec_type != AVMEDIA_TYPE_SUBTITLE
+                || codec_map(s.codec_id).is_some()
+                || convert(s, codec_name(s.codec_id))
+        });
+        if let Some((_, tmpl, scan)) = cc {
+            let cues = scan.decode();
+            if !cues.is_empty() {
+                streams.push(cc_stream(&tmpl, cues));
+            }
+        }
         Ok(streams)
     }
 }
@@ -186,10 +202,16 @@ unsafe fn describe(st: *mut AVStream, par: &AVCodecParameters, origin_us: i64) -
     }
 }
 
+// Only these carry ATSC A/53 caption user data we know how to find
+fn cc_video(par: &AVCodecParameters) -> bool {
+    matches!(codec_name(par.codec_id), "h264" | "hevc" | "mpeg2video")
+}
+
 unsafe fn read_packets(
     fmt_ctx: *mut AVFormatContext,
     routes: &[Option<usize>],
     streams: &mut [Stream],
+    mut cc: Option<&mut (usize, Stream, CcScan)>,
 ) {
     unsafe {
         let dur_ms = ((*fmt_ctx).duration / 1000).max(0) as usize;
@@ -209,6 +231,17 @@ unsafe fn read_packets(
                     pts: (*pkt).pts,
                     duration: (*pkt).duration,
                 });
+            } else if let Some(&mut (vi, _, ref mut scan)) = cc.as_deref_mut()
+                && vi == si
+                && (*pkt).pts != AV_NOPTS_VALUE
+            {
+                scan.feed_es(
+                    (*pkt).pts,
+                    from_raw_parts((*pkt).data, (*pkt).size as usize),
+                );
+                if !scan.alive() {
+                    (*(*(*fmt_ctx).streams.add(si))).discard = AVDISCARD_ALL;
+                }
             }
             if dur_ms > 0 && (*pkt).pts != AV_NOPTS_VALUE {
                 let tb = (*(*(*fmt_ctx).streams.add(si))).time_base;
@@ -228,10 +261,14 @@ unsafe fn read_packets(
     }
 }
 
-pub fn codec_map(codec_id: c_int) -> Option<(&'static str, &'static str)> {
-    let name = unsafe { CStr::from_ptr(avcodec_get_name(codec_id)) }
+fn codec_name(codec_id: c_int) -> &'static str {
+    unsafe { CStr::from_ptr(avcodec_get_name(codec_id)) }
         .to_str()
-        .ok()?;
+        .unwrap_or_default()
+}
+
+pub fn codec_map(codec_id: c_int) -> Option<(&'static str, &'static str)> {
+    let name = codec_name(codec_id);
     let pair = match name {
         "ac3" => ("A_AC3", "Dolby Digital / AC-3"),
         "eac3" => ("A_EAC3", "Dolby Digital Plus / E-AC-3"),
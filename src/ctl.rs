@@ -0,0 +1,302 @@
+#[cfg(target_os = "linux")]
+use alloc::{string::String, sync::Arc};
+use core::{
+    fmt::Write as _,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering::Relaxed},
+    time::Duration as Durat,
+};
+#[cfg(target_os = "linux")]
+use core::{mem::size_of, str::from_utf8, sync::atomic::AtomicI32};
+
+use crate::{
+    chan::{Semaphore, sem_close, sem_resize},
+    error::INTERRUPTED,
+    events::{cur_phase, emit},
+    mem::headroom,
+    path::Path,
+    progs::status,
+    sync::Mutex,
+    thread::{sleep, spawn},
+};
+#[cfg(target_os = "linux")]
+use crate::{
+    events::error,
+    fs::remove_file,
+    io::{Fd, Write as _},
+    sys::{
+        AF_UNIX, SHUT_RDWR, SOCK_CLOEXEC, SOCK_STREAM, SockaddrUn, accept4, bind as sys_bind,
+        close, listen, read, shutdown, socket,
+    },
+};
+
+// `<work_dir>/ctl.sock`: one text command per connection, one line back
+const POLL_MS: u64 = 100;
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+static STOP: AtomicBool = AtomicBool::new(false);
+static ACTIVE: AtomicUsize = AtomicUsize::new(usize::MAX);
+static MAX: AtomicUsize = AtomicUsize::new(0);
+// what `-w`/`workers N` asked for; memory backoff only parks workers below it
+static WANT: AtomicUsize = AtomicUsize::new(0);
+static SEM: Mutex<Option<Arc<Semaphore>>> = Mutex::new(None);
+// bumped per run, so a `--queue` run's backoff thread ends with it
+static RUN: AtomicUsize = AtomicUsize::new(0);
+#[cfg(target_os = "linux")]
+static LISTEN: AtomicI32 = AtomicI32::new(-1);
+
+fn park_while(f: impl Fn() -> bool) {
+    while f() {
+        sleep(Durat::from_millis(POLL_MS));
+    }
+}
+
+// Lib encoders call this between frames
+#[inline]
+pub fn hold() {
+    if PAUSED.load(Relaxed) {
+        park_while(|| PAUSED.load(Relaxed));
+    }
+}
+
+// Before a worker takes its next chunk: waits out a pause or a worker cut
+#[cfg(feature = "vship")]
+#[inline]
+pub fn gate(worker_id: usize) {
+    park_while(|| PAUSED.load(Relaxed) || worker_id >= ACTIVE.load(Relaxed));
+}
+
+// `gate` that also turns the worker away once `stop` was requested
+pub fn admit(worker_id: usize) -> bool {
+    park_while(|| {
+        !STOP.load(Relaxed) && (PAUSED.load(Relaxed) || worker_id >= ACTIVE.load(Relaxed))
+    });
+    !STOP.load(Relaxed)
+}
+
+// Signal-handler side of an interrupt: only stores, no locks or allocation
+pub fn interrupt() {
+    STOP.store(true, Relaxed);
+    PAUSED.store(false, Relaxed);
+}
+
+pub fn stopping() -> bool {
+    STOP.load(Relaxed)
+}
+
+// A `stop` rather than an interrupt: TQ chunks already probing still converge
+#[cfg(feature = "vship")]
+pub fn draining() -> bool {
+    STOP.load(Relaxed) && !INTERRUPTED.load(Relaxed)
+}
+
+// The chunk-buffer semaphore follows the worker count; `stop` closes it so no new chunk
+// is decoded, while TQ chunks already cycling between encoder & metric workers converge
+pub fn bind(sem: &Arc<Semaphore>) {
+    *SEM.lock() = Some(Arc::clone(sem));
+}
+
+// `stop`, or an interrupt in a `--queue` run: wakes the decoder for good so the input can
+// wind down
+pub fn halt() {
+    if let Some(ref s) = *SEM.lock() {
+        sem_close(s);
+    }
+}
+
+pub fn unbind() {
+    *SEM.lock() = None;
+}
+
+fn set_active(n: usize) {
+    let old = ACTIVE.swap(n, Relaxed);
+    if let Some(ref s) = *SEM.lock() {
+        sem_resize(s, n as isize - old as isize);
+    }
+}
+
+fn set_workers(n: usize) {
+    WANT.store(n, Relaxed);
+    set_active(n);
+}
+
+// Worker count once `-w auto` is resolved
+fn size(workers: usize) {
+    ACTIVE.store(workers, Relaxed);
+    MAX.store(workers, Relaxed);
+    WANT.store(workers, Relaxed);
+}
+
+// Every run starts unpaused & unstopped: a `stop` only ends the `--queue` input it was sent to
+fn begin(workers: usize) {
+    RUN.fetch_add(1, Relaxed);
+    // an interrupt before the encode started still stops it
+    STOP.store(INTERRUPTED.load(Relaxed), Relaxed);
+    PAUSED.store(false, Relaxed);
+    size(workers);
+}
+
+// While chunks encode: parks one worker when headroom drops under `low` (one
+// worker's worth), lets one back after it stays above `3 x low` for a while
+pub fn backoff(low: u64) {
+    let run = RUN.load(Relaxed);
+    spawn(move || {
+        let mut calm = 0u32;
+        while !STOP.load(Relaxed) && RUN.load(Relaxed) == run {
+            sleep(Durat::from_secs(1));
+            if SEM.lock().is_none() {
+                continue;
+            }
+            let (room, cur) = (headroom(), ACTIVE.load(Relaxed));
+            if room < low && cur > 1 {
+                set_active(cur - 1);
+                calm = 0;
+            } else if room > low.saturating_mul(3) && cur < WANT.load(Relaxed) {
+                calm += 1;
+                if calm < 10 {
+                    continue;
+                }
+                set_active(cur + 1);
+                calm = 0;
+            } else {
+                calm = 0;
+                continue;
+            }
+            emit(
+                "backoff",
+                format_args!(",\"workers\":{},\"headroom\":{room}", ACTIVE.load(Relaxed)),
+            );
+        }
+    });
+}
+
+fn exec(cmd: &str) -> String {
+    let max = MAX.load(Relaxed);
+    let mut it = cmd.split_whitespace();
+    let reply = match (it.next(), it.next(), it.next()) {
+        (Some("pause"), None, _) => {
+            PAUSED.store(true, Relaxed);
+            "ok paused".into()
+        }
+        (Some("resume"), None, _) => {
+            PAUSED.store(false, Relaxed);
+            "ok resumed".into()
+        }
+        (Some("workers"), Some(n), None) => match n.parse::<usize>() {
+            Ok(n) if (1..=max).contains(&n) => {
+                set_workers(n);
+                format!("ok workers {n}")
+            }
+            _ => format!("err workers must be 1-{max}"),
+        },
+        (Some("stop"), None, _) if SEM.lock().is_some() => {
+            STOP.store(true, Relaxed);
+            PAUSED.store(false, Relaxed);
+            halt();
+            "ok stopping after in-flight chunks".into()
+        }
+        (Some("stop"), None, _) => "err stop only applies while chunks encode".into(),
+        (Some("status"), None, _) => {
+            let mut s = String::new();
+            _ = write!(
+                s,
+                "{{\"phase\":\"{}\",\"paused\":{},\"stopping\":{},\"workers\":{},\"max_workers\":\
+                 {max}",
+                cur_phase(),
+                PAUSED.load(Relaxed),
+                STOP.load(Relaxed),
+                ACTIVE.load(Relaxed).min(max)
+            );
+            status(&mut s);
+            s.push('}');
+            return s;
+        }
+        _ => format!("err unknown command; use pause, resume, workers N (1-{max}), stop or status"),
+    };
+    if reply.starts_with("ok") {
+        emit("ctl", format_args!(",\"cmd\":\"{}\"", cmd.trim()));
+    }
+    reply
+}
+
+#[cfg(target_os = "linux")]
+pub fn serve(work_dir: &Path, workers: usize) {
+    begin(workers);
+    let sock = work_dir.join("ctl.sock");
+    _ = remove_file(&sock);
+    let b = sock.as_bytes();
+    let mut addr = SockaddrUn {
+        family: AF_UNIX as u16,
+        path: [0; 108],
+    };
+    let Some(dst) = addr.path.get_mut(..b.len()).filter(|_| b.len() < 108) else {
+        error("control socket path is too long");
+        return;
+    };
+    dst.copy_from_slice(b);
+    let fd = socket(AF_UNIX, SOCK_STREAM | SOCK_CLOEXEC, 0);
+    if fd < 0
+        || sys_bind(fd, &raw const addr, size_of::<SockaddrUn>() as u32) < 0
+        || listen(fd, 4) < 0
+    {
+        if fd >= 0 {
+            unsafe { close(fd) };
+        }
+        error("control socket unavailable");
+        return;
+    }
+    // the previous `--queue` input's listener: shutting it down wakes its accept
+    let old = LISTEN.swap(fd, Relaxed);
+    if old >= 0 {
+        shutdown(old, SHUT_RDWR);
+    }
+
+    spawn(move || {
+        loop {
+            let c = accept4(fd, SOCK_CLOEXEC);
+            if c < 0 {
+                // EINTR/ECONNABORTED: keep serving
+                if c == -4 || c == -103 {
+                    continue;
+                }
+                break;
+            }
+            let mut buf = [0u8; 256];
+            let n = read(c, buf.as_mut_ptr(), buf.len()).max(0) as usize;
+            let mut reply = exec(from_utf8(&buf[..n]).unwrap_or(""));
+            reply.push('\n');
+            _ = Fd(c).write_all(reply.as_bytes());
+            unsafe { close(c) };
+        }
+        unsafe { close(fd) };
+    });
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn serve(work_dir: &Path, workers: usize) {
+    use std::{
+        io::{Read as _, Write as _},
+        os::unix::net::UnixListener,
+    };
+
+    begin(workers);
+    let sock = work_dir.join("ctl.sock");
+    _ = std::fs::remove_file(&sock);
+    let Ok(l) = UnixListener::bind(&sock) else {
+        crate::events::error("control socket unavailable");
+        return;
+    };
+    crate::thread::spawn(move || {
+        for mut c in l.incoming().flatten() {
+            let mut buf = [0u8; 256];
+            let n = c.read(&mut buf).unwrap_or(0);
+            let mut reply = exec(core::str::from_utf8(&buf[..n]).unwrap_or(""));
+            reply.push('\n');
+            _ = c.write_all(reply.as_bytes());
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn serve(_: &Path, workers: usize) {
+    begin(workers);
+}
@@ -6,7 +6,7 @@ use core::hint::cold_path;
 use crate::{
     chan::{Semaphore, sem_acq},
     chunk::Chunk,
-    error::fatal,
+    error::Xerr,
     ffms::{
         DecStrat,
         DecStrat::{
@@ -117,16 +117,12 @@ pub fn dec_chnks(
     skip: &BTreeSet<u16>,
     strat: DecStrat,
     sem: &Arc<Semaphore>,
-) {
+) -> Result<(), Xerr> {
     let thr = available_parallelism() as i32;
-    let dec = if strat.is_hw() {
-        VidDecoder::new_hw(path, thr)
+    let mut dec = if strat.is_hw() {
+        VidDecoder::new_hw(path, thr)?
     } else {
-        VidDecoder::new(path, thr)
-    };
-    let mut dec = match dec {
-        Ok(d) => d,
-        Err(e) => fatal(e),
+        VidDecoder::new(path, thr)?
     };
     let filtered: Vec<Chunk> = chnks
         .iter()
@@ -167,6 +163,7 @@ pub fn dec_chnks(
         }
         _ => disp_10b(&filtered, &mut dec, inf, tx, strat, sem),
     }
+    Ok(())
 }
 
 fn disp_10b(
@@ -185,63 +182,81 @@ fn disp_10b(
         B10Fast => {
             let f = calc_packed_sz(inf.width, inf.height);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_fast(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         B10FastRem => {
             let f = calc_packed_sz(inf.width, inf.height);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_fast_rem(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         B10StrideRem => {
             let f = calc_packed_sz(inf.width, inf.height);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_stride_rem(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         B10CropFast { cc } => {
             let f = calc_packed_sz(cc.new_w, cc.new_h);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_crop_fast(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
         B10CropFastRem { cc } => {
             let f = calc_packed_sz(cc.new_w, cc.new_h);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_crop_fast_rem(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
         B10Crop { cc } => {
             let f = calc_packed_sz(cc.new_w, cc.new_h);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_crop(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
         B10CropRem { cc } => {
             let f = calc_packed_sz(cc.new_w, cc.new_h);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_crop_rem(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
         B10CropStride { cc } => {
             let f = calc_packed_sz(cc.new_w, cc.new_h);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_crop_stride(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
         B10CropStrideRem { cc } => {
             let f = calc_packed_sz(cc.new_w, cc.new_h);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_crop_stride_rem(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
@@ -261,35 +276,45 @@ fn disp_10b_raw(
         B10Raw => {
             let f = (inf.width as usize * inf.height as usize) * 3;
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_raw(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         B10RawStride => {
             let f = (inf.width as usize * inf.height as usize) * 3;
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_raw_stride(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         B10RawCropFast { cc } => {
             let f = (cc.new_w as usize * cc.new_h as usize) * 3;
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_raw_crop_fast(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
         B10RawCrop { cc } => {
             let f = (cc.new_w as usize * cc.new_h as usize) * 3;
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_raw_crop(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
         B10RawCropStride { cc } => {
             let f = (cc.new_w as usize * cc.new_h as usize) * 3;
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_10_raw_crop_stride(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
@@ -309,28 +334,36 @@ fn disp_8b(
         B8Fast => {
             let f = calc_8b_sz(inf.width, inf.height);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_8_fast(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         B8Stride => {
             let f = calc_8b_sz(inf.width, inf.height);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_8_stride(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         B8CropFast { cc } => {
             let f = calc_8b_sz(cc.new_w, cc.new_h);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_8_crop_fast(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
         B8Crop { cc } => {
             let f = calc_8b_sz(cc.new_w, cc.new_h);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_8_crop(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
@@ -338,42 +371,54 @@ fn disp_8b(
             let f = calc_8b_sz(cc.new_w, cc.new_h);
             let mut buf = vec![0u8; calc_8b_sz(inf.width, inf.height)];
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_8_crop_stride(ch, dec, inf, &cc, f, &mut buf));
             }
         }
         HwNv12 => {
             let f = calc_8b_sz(inf.width, inf.height);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_hw_nv12(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         HwNv12Stride => {
             let f = calc_8b_sz(inf.width, inf.height);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_hw_nv12_stride(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         HwNv12Crop { cc } => {
             let f = calc_8b_sz(cc.new_w, cc.new_h);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_hw_nv12_crop(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
         HwNv12To10 => {
             let f = calc_8b_sz(inf.width, inf.height);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_hw_nv12_to10(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         HwNv12To10Stride => {
             let f = calc_8b_sz(inf.width, inf.height);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_hw_nv12_to10_stride(
                     ch, dec, inf, inf.width, inf.height, f,
                 ));
@@ -382,7 +427,9 @@ fn disp_8b(
         HwNv12CropTo10 { cc } => {
             let f = calc_8b_sz(cc.new_w, cc.new_h);
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_hw_nv12_crop_to10(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
@@ -402,21 +449,27 @@ fn disp_hw_10b_raw(
         HwP010Raw => {
             let f = (inf.width as usize * inf.height as usize) * 3;
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_hw_p010_raw(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         HwP010RawRem => {
             let f = (inf.width as usize * inf.height as usize) * 3;
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_hw_p010_raw_rem(ch, dec, inf, inf.width, inf.height, f));
             }
         }
         HwP010RawRemStride => {
             let f = (inf.width as usize * inf.height as usize) * 3;
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_hw_p010_raw_rem_stride(
                     ch, dec, inf, inf.width, inf.height, f,
                 ));
@@ -425,14 +478,18 @@ fn disp_hw_10b_raw(
         HwP010RawCrop { cc } => {
             let f = (cc.new_w as usize * cc.new_h as usize) * 3;
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_hw_p010_raw_crop(ch, dec, &cc, cc.new_w, cc.new_h, f));
             }
         }
         HwP010RawCropRem { cc } => {
             let f = (cc.new_w as usize * cc.new_h as usize) * 3;
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx(dec_hw_p010_raw_crop_rem(
                     ch, dec, &cc, cc.new_w, cc.new_h, f,
                 ));
@@ -464,7 +521,9 @@ fn disp_hw_10b_pack(
     macro_rules! run {
         ($dec_fn:ident, $ctx:expr) => {
             for ch in filtered {
-                sem_acq(sem);
+                if !sem_acq(sem) {
+                    return;
+                }
                 tx($dec_fn(ch, dec, $ctx, w, h, fsz, &mut raw_buf));
             }
         };
@@ -889,11 +948,18 @@ fn pipe_loop<F>(
         let len = ch.end - ch.start;
 
         if skip.contains(&ch.idx) {
-            reader.skip_frames(len);
+            if !reader.seeks() {
+                reader.skip_frames(len);
+            }
             continue;
         }
 
-        sem_acq(sem);
+        if !sem_acq(sem) {
+            return;
+        }
+        if !reader.seek(ch) {
+            return;
+        }
 
         let mut raw = vec![0u8; len * raw_fsz];
         for i in 0..len {
@@ -1,20 +1,25 @@
 #[cfg(feature = "vship")]
 use alloc::collections::BTreeMap;
 #[cfg(all(target_os = "linux", feature = "vship"))]
-use alloc::string::String;
+use alloc::string::{String, ToString as _};
 #[cfg(target_os = "linux")]
 use alloc::{boxed::Box, vec::Vec};
 use alloc::{collections::BTreeSet, sync::Arc};
 #[cfg(feature = "avm")]
 use core::{ffi::c_void, ptr::null};
-#[cfg(feature = "vship")]
-use core::{fmt::Write as _, mem::swap};
 use core::{
+    fmt::Arguments,
     hint::cold_path,
-    mem::{MaybeUninit, size_of, transmute, zeroed},
+    mem::{MaybeUninit, size_of, take, transmute, zeroed},
     ptr::{copy_nonoverlapping, null_mut},
     slice::from_raw_parts,
     sync::atomic::{AtomicU64, AtomicUsize, Ordering::Relaxed},
+    time::Duration as Durat,
+};
+#[cfg(feature = "vship")]
+use core::{
+    fmt::Write as _,
+    mem::{replace, swap},
 };
 
 #[cfg(feature = "avm")]
@@ -26,31 +31,39 @@ use crate::avm::{
 };
 #[cfg(feature = "vship")]
 use crate::chan::{mpmc_close, mpmc_recv, mpmc_send, mpsc_recv, mpsc_send};
+#[cfg(feature = "avm")]
+use crate::encoder::make_avm_app_cmd;
 #[cfg(all(target_os = "linux", not(test), feature = "vship"))]
 use crate::fmath::FloatExt as _;
-#[cfg(not(target_os = "linux"))]
-use crate::path::PathBuf;
 use crate::{
     Args,
-    chan::{Semaphore, SeqRing, sem_release, spmc_close, spmc_recv, spmc_send},
-    chunk::{Chunk, ChunkComp, ResumeInf, get_resume, save_resume, zone_tmpls},
+    chan::{Semaphore, SeqRing, sem_close, sem_release, spmc_close, spmc_recv, spmc_send},
+    chunk::{
+        Chunk, ChunkComp, ResumeInf, by_cost, get_resume, save_resume, track_resume,
+        untrack_resume, zone_tmpls,
+    },
+    clk::Mono,
+    ctl,
     dec::{dec_chnks, dec_pipe},
     encoder::{
         EncConfig, Encoder,
         Encoder::{Avm, SvtAv1, Vvenc, X264, X265},
-        SVT_CONF_SIZE, make_enc_cmd, parse_svt_params, set_svt_base,
+        SVT_CONF_SIZE, make_enc_cmd, make_svt_app_cmd, parse_svt_params, set_svt_base,
     },
-    error::fatal,
+    error::{Xerr, fatal},
+    events::{chunk_done, emit},
     ffms::{DecStrat, VidInf, nv12_10b, nv12_10b_rem},
-    fs::{File, metadata},
-    io::{BufWriter, Write},
+    fs::{File, OpenOptions, create_dir_all, metadata, write},
+    io::{BufWriter, Read, Result as IoResult, Write},
     pack::{
         PACK_CHUNK, SHIFT_CHUNK, UNPACK_CHUNK, conv_10b, conv_10b_rem, unpack_10b, unpack_10b_rem,
     },
-    path::Path,
+    path::{Path, PathBuf},
     pipeline::Pipeline,
-    process::Child,
+    process::{Child, cmd_line},
     progs::{ProgsTrack, Tracker, Watch},
+    report::{log_chunk, read_runs},
+    scd::load_wt,
     svt::{
         EB_BUFFERFLAG_EOS, EB_ERROR_NONE, EbBufferHeaderType, EbComponentType,
         EbSvtAv1EncConfiguration, EbSvtIOFormat, svt_av1_enc_deinit, svt_av1_enc_deinit_handle,
@@ -58,25 +71,27 @@ use crate::{
         svt_av1_enc_set_parameter,
     },
     sync::{Mutex, OnceLock},
-    thread::{JoinHandle, spawn},
-    util::assume_unreachable,
+    thread::{JoinHandle, sleep, spawn},
+    util::{assume_unreachable, json_str},
     worker::WorkPkg,
-    y4m::PipeReader,
+    y4m::{PipeReader, y4m_header},
 };
 #[cfg(feature = "vship")]
 use crate::{
     atofu::{TqChunkLine, parse_chunks},
-    fs::{OpenOptions, copy, read, write},
+    fs::{copy, read, read_to_string as read_to_str},
     pipeline::MetricProgs,
     thread::{PHandle, available_parallelism, pspawn},
     tq::{
-        Probe, ProbeDec, ProbeLog, calc_butter_8b_dav1d, calc_butter_8b_ff, calc_butter_10b_dav1d,
-        calc_butter_10b_ff, calc_butter_rem_dav1d, calc_butter_rem_ff, calc_cvvdp_8b_dav1d,
-        calc_cvvdp_8b_ff, calc_cvvdp_10b_dav1d, calc_cvvdp_10b_ff, calc_cvvdp_rem_dav1d,
-        calc_cvvdp_rem_ff, calc_ssimu2_8b_dav1d, calc_ssimu2_8b_ff, calc_ssimu2_10b_dav1d,
-        calc_ssimu2_10b_ff, calc_ssimu2_rem_dav1d, calc_ssimu2_rem_ff, interpolate_crf, make_dav1d,
-        make_ff, prep_dav1d, prep_ff,
+        Agg, Also, CrfSeeds, Metric, Probe, ProbeDec, ProbeLog, Sample, ScoreSpec,
+        calc_butter_8b_dav1d, calc_butter_8b_ff, calc_butter_10b_dav1d, calc_butter_10b_ff,
+        calc_butter_rem_dav1d, calc_butter_rem_ff, calc_cvvdp_8b_dav1d, calc_cvvdp_8b_ff,
+        calc_cvvdp_10b_dav1d, calc_cvvdp_10b_ff, calc_cvvdp_rem_dav1d, calc_cvvdp_rem_ff,
+        calc_ssimu2_8b_dav1d, calc_ssimu2_8b_ff, calc_ssimu2_10b_dav1d, calc_ssimu2_10b_ff,
+        calc_ssimu2_rem_dav1d, calc_ssimu2_rem_ff, interpolate_crf, make_dav1d, make_ff,
+        prep_dav1d, prep_ff, scd_pick, smooth_crfs,
     },
+    tqcache::ProbeCache,
     vship::{Disp, PinnedBuf, VshipProcessor, init_device},
     worker::TQState,
 };
@@ -294,6 +309,7 @@ struct WorkerStats {
     completed_frames: Arc<AtomicUsize>,
     tot_sz: Arc<AtomicU64>,
     completions: Arc<Mutex<ResumeInf>>,
+    failed: Mutex<Vec<u16>>,
 }
 
 impl WorkerStats {
@@ -305,10 +321,12 @@ impl WorkerStats {
             completed_frames: Arc::new(AtomicUsize::new(init_frames)),
             tot_sz: Arc::new(AtomicU64::new(init_sz)),
             completions: Arc::new(Mutex::new(resume_data.clone())),
+            failed: Mutex::new(Vec::new()),
         }
     }
 
     fn add_completion(&self, completion: ChunkComp, work_dir: &Path) {
+        chunk_done(completion.idx, completion.frames, completion.sz, None);
         self.completed_frames.fetch_add(completion.frames, Relaxed);
         self.tot_sz.fetch_add(completion.sz, Relaxed);
         let mut data = self.completions.lock();
@@ -342,12 +360,18 @@ struct EncTrack {
     crf_score: Option<(f32, Option<f32>)>,
 }
 
-type LibEncFn =
-    fn(&mut Vec<u8>, &mut dyn Write, &EncConfig, &EncWorkerCtx, &mut [u8], &EncTrack) -> u64;
+type LibEncFn = fn(
+    &mut Vec<u8>,
+    &mut dyn Write,
+    &EncConfig,
+    &EncWorkerCtx,
+    &mut [u8],
+    &EncTrack,
+) -> Result<u64, Xerr>;
 
-type WatchEncFn = fn(&Arc<ProgsTrack>, &mut Child, Watch, Encoder);
+type WatchEncFn = fn(&Arc<ProgsTrack>, &mut Child, Watch, Encoder, Option<&Path>);
 
-type ChnkFn = fn(&mut WorkPkg, &str, &EncWorkerCtx, &Path, &mut [u8], usize) -> u64;
+type ChnkFn = fn(&mut WorkPkg, &str, &EncWorkerCtx, &Path, &mut [u8], usize) -> Result<u64, Xerr>;
 
 #[cfg(feature = "vship")]
 struct EncRecipe<'a> {
@@ -356,25 +380,87 @@ struct EncRecipe<'a> {
 }
 
 #[cfg(feature = "vship")]
-type ProbeFn = fn(&mut WorkPkg, f32, &EncRecipe, &EncWorkerCtx, &mut [u8], usize, Option<&Path>);
+type ProbeFn = fn(
+    &mut WorkPkg,
+    f32,
+    &EncRecipe,
+    &EncWorkerCtx,
+    &mut [u8],
+    usize,
+    Option<&Path>,
+) -> Result<u64, Xerr>;
+
+// Copies what the progress watcher reads into the chunk's log
+struct Tee<R> {
+    rd: R,
+    log: Option<File>,
+}
 
-fn watch_enc_stderr(prog: &Arc<ProgsTrack>, child: &mut Child, w: Watch, encoder: Encoder) {
+impl<R: Read> Read for Tee<R> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        let n = self.rd.read(buf)?;
+        if let Some(ref mut f) = self.log {
+            _ = f.write_all(&buf[..n]);
+        }
+        Ok(n)
+    }
+}
+
+fn open_log(log: Option<&Path>) -> Option<File> {
+    OpenOptions::new().create(true).append(true).open(log?).ok()
+}
+
+fn watch_enc_stderr(
+    prog: &Arc<ProgsTrack>,
+    child: &mut Child,
+    w: Watch,
+    encoder: Encoder,
+    log: Option<&Path>,
+) {
     prog.watch_enc(
-        unsafe { child.stderr.take().unwrap_unchecked() },
+        Tee {
+            rd: unsafe { child.stderr.take().unwrap_unchecked() },
+            log: open_log(log),
+        },
         w,
         encoder,
     );
 }
 
-fn watch_enc_stdout(prog: &Arc<ProgsTrack>, child: &mut Child, w: Watch, encoder: Encoder) {
+fn watch_enc_stdout(
+    prog: &Arc<ProgsTrack>,
+    child: &mut Child,
+    w: Watch,
+    encoder: Encoder,
+    log: Option<&Path>,
+) {
+    if let Some(mut f) = open_log(log)
+        && let Some(mut err) = child.stderr.take()
+    {
+        spawn(move || {
+            let mut buf = [0u8; 4096];
+            while let Ok(n @ 1..) = err.read(&mut buf) {
+                _ = f.write_all(&buf[..n]);
+            }
+        });
+    }
     prog.watch_enc(
-        unsafe { child.stdout.take().unwrap_unchecked() },
+        Tee {
+            rd: unsafe { child.stdout.take().unwrap_unchecked() },
+            log: open_log(log),
+        },
         w,
         encoder,
     );
 }
 
-const fn watch_enc_unreachable(_: &Arc<ProgsTrack>, _: &mut Child, _: Watch, _: Encoder) {
+const fn watch_enc_unreachable(
+    _: &Arc<ProgsTrack>,
+    _: &mut Child,
+    _: Watch,
+    _: Encoder,
+    _: Option<&Path>,
+) {
     assume_unreachable();
 }
 
@@ -423,6 +509,12 @@ struct EncWorkerCtx<'a> {
     chnk_fn: ChnkFn,
     tmpl: Option<&'a [u8]>,
     tmpls: &'a [Arc<[u8]>],
+    // `--retries` & `--fallback-param`; `fb_tmpls` are the lib templates with the fallback applied
+    retries: usize,
+    fallback: Option<&'a str>,
+    fb_tmpls: &'a [Arc<[u8]>],
+    // `--dump-failed`
+    dump: bool,
     #[cfg(feature = "vship")]
     probe_fn: ProbeFn,
 }
@@ -432,7 +524,7 @@ struct TQWorkerCtx<'a> {
     inf: &'a VidInf,
     pipe: &'a Pipeline,
     work_dir: &'a Path,
-    metric_mode: &'a str,
+    metric_mode: Agg,
     prog: &'a Arc<ProgsTrack>,
     done_tx: &'a SeqRing,
     resume_state: &'a Arc<Mutex<ResumeInf>>,
@@ -443,6 +535,8 @@ struct TQWorkerCtx<'a> {
     worker_cnt: usize,
     threads: i32,
     ext: &'static str,
+    cache: Option<&'a ProbeCache>,
+    seeds: &'a CrfSeeds,
 }
 
 #[cold]
@@ -491,7 +585,7 @@ const fn nv12_exact(pipe: &Pipeline) -> bool {
         && (pipe.final_w / 2 * (pipe.final_h / 2)).is_multiple_of(SHIFT_CHUNK * 2)
 }
 
-const fn unpack_exact(pipe: &Pipeline) -> bool {
+pub const fn unpack_exact(pipe: &Pipeline) -> bool {
     pipe.final_w.is_multiple_of(PACK_CHUNK) && pipe.frame_sz.is_multiple_of(UNPACK_CHUNK)
 }
 
@@ -535,6 +629,20 @@ fn resolve_lib_enc(
     }
 }
 
+// The order chunks are decoded & so encoded in: the timeline's unless `--heavy-first`
+fn dec_order(chnks: &[Chunk], args: &Args, inf: &VidInf, work_dir: &Path) -> Vec<Chunk> {
+    if args.heavy_first {
+        by_cost(
+            chnks,
+            &load_wt(&args.sc_file, inf.frames),
+            &read_runs(work_dir),
+        )
+    } else {
+        chnks.to_vec()
+    }
+}
+
+// Returns the chunks that still failed after every retry
 pub fn enc_all(
     chnks: &[Chunk],
     inf: &VidInf,
@@ -542,20 +650,21 @@ pub fn enc_all(
     path: &Path,
     work_dir: &Path,
     pipe_reader: Option<PipeReader>,
-) {
+) -> Vec<u16> {
     let resume_data = load_resume_data(work_dir);
+    _ = create_dir_all(work_dir.join("logs"));
 
     #[cfg(feature = "vship")]
     {
         let is_tq = args.tq.is_some() && args.qp_range.is_some();
         if is_tq {
-            enc_tq(chnks, inf, args, path, work_dir, pipe_reader);
-            return;
+            return enc_tq(chnks, inf, args, path, work_dir, pipe_reader);
         }
     }
 
     let (skip_indices, completed_cnt, completed_frames) = build_skip_set(&resume_data);
     let stats = create_stats(completed_cnt, &resume_data);
+    track_resume(&stats.completions, work_dir);
     let (prog, display_handle) = ProgsTrack::new(
         chnks,
         inf,
@@ -589,10 +698,12 @@ pub fn enc_all(
 
     let ring = Arc::new(SeqRing::new());
     let sem = Arc::new(Semaphore::new(args.chnk_buff));
+    ctl::bind(&sem);
 
     let build = resolve_build_tmpl(args.encoder);
     let mut chnks = chnks.to_vec();
     let zones = build.map_or_else(Vec::new, |_| zone_tmpls(&mut chnks));
+    let chnks = dec_order(&chnks, args, inf, work_dir);
 
     let decoder = {
         let path = path.to_path_buf();
@@ -607,13 +718,18 @@ pub fn enc_all(
             if let Some(mut reader) = pipe_reader {
                 dec_pipe(&chnks, &mut reader, &inf, &send, &skip_indices, strat, &sem);
             } else {
-                dec_chnks(&chnks, &path, &inf, &send, &skip_indices, strat, &sem);
+                dec_chnks(&chnks, &path, &inf, &send, &skip_indices, strat, &sem)
+                    .unwrap_or_else(|e| fatal(e));
             }
             unsafe { spmc_close(rp) };
         })
     };
 
     let tmpls = build.map(|b| build_zoned(b, inf, &args.params, &pipe, &zones));
+    let fb_tmpls = build.zip(args.fallback.as_deref()).map(|(b, fb)| {
+        let zones: Vec<Box<str>> = zones.iter().map(|z| format!("{z} {fb}").into()).collect();
+        build_zoned(b, inf, &format!("{} {fb}", args.params), &pipe, &zones)
+    });
     let chnk_fn = resolve_chnk_fn(args.encoder, !zones.is_empty());
     let watch_enc = resolve_watch_enc(args.encoder);
 
@@ -629,6 +745,9 @@ pub fn enc_all(
         let sem_clone = Arc::clone(&sem);
         let encoder = args.encoder;
         let tmpls = tmpls.clone();
+        let fb_tmpls = fb_tmpls.clone();
+        let (retries, fallback) = (args.retries, args.fallback.clone());
+        let dump = args.dump_failed;
 
         let handle = spawn(move || {
             let tset: &[Arc<[u8]>] = tmpls.as_deref().unwrap_or(&[]);
@@ -643,6 +762,10 @@ pub fn enc_all(
                 chnk_fn,
                 tmpl: tset.first().map(|t| &**t),
                 tmpls: tset,
+                retries,
+                fallback: fallback.as_deref(),
+                fb_tmpls: fb_tmpls.as_deref().unwrap_or(&[]),
+                dump,
                 #[cfg(feature = "vship")]
                 probe_fn,
             };
@@ -658,13 +781,29 @@ pub fn enc_all(
         workers.push(handle);
     }
 
-    join_one(decoder);
     join_all(workers);
+    // after a stop nobody returns permits: wake the decoder, then free what it had queued
+    if ctl::stopping() {
+        sem_close(&sem);
+    }
+    join_one(decoder);
+    loop {
+        let m = unsafe { spmc_recv(Arc::as_ptr(&ring)) };
+        if m == 0 {
+            break;
+        }
+        drop(unsafe { Box::from_raw(m as *mut WorkPkg) });
+    }
+    ctl::unbind();
+    untrack_resume();
     drop(prog);
     join_one(display_handle);
+    let mut failed = take(&mut *stats.failed.lock());
+    failed.sort_unstable();
+    failed
 }
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 #[cfg(feature = "vship")]
 struct TQCtx {
     target: f32,
@@ -673,41 +812,93 @@ struct TQCtx {
     qp_max: f32,
     use_butter: bool,
     use_cvvdp: bool,
+    also: Arc<[Also]>,
+    sample: usize,
+    // `--tq-sample encN`: probes are encoded from the sampled frames alone
+    thin: bool,
+    // SCD weights of every frame once `--tq-sample scdN` picks frames by them; else empty
+    wt: Arc<[f32]>,
 }
 
 #[cfg(feature = "vship")]
 impl TQCtx {
     #[inline(always)]
-    fn converged(&self, score: f32) -> bool {
-        (score - self.target).abs() <= self.tolerance
+    fn passes(&self, p: &Probe) -> bool {
+        self.also.iter().zip(&p.also).all(|(a, &s)| a.ok(s))
     }
 
     #[inline(always)]
-    fn up_bounds(&self, state: &mut TQState, score: f32) -> bool {
-        if self.use_butter {
-            if score > self.target + self.tolerance {
-                state.search_max = state.last_crf - 0.25;
-            } else if score < self.target - self.tolerance {
-                state.search_min = state.last_crf + 0.25;
-            }
-        } else if score < self.target - self.tolerance {
+    fn converged(&self, p: &Probe) -> bool {
+        (p.score - self.target).abs() <= self.tolerance && self.passes(p)
+    }
+
+    // Distance from target; failing a `--tq-and` term ranks last, lowest CRF first
+    #[inline(always)]
+    fn cost(&self, p: &Probe) -> f32 {
+        if self.passes(p) {
+            (p.score - self.target).abs()
+        } else {
+            1e4 + p.crf
+        }
+    }
+
+    #[inline(always)]
+    fn up_bounds(&self, state: &mut TQState, p: &Probe) -> bool {
+        let (worse, better) = (
+            p.score < self.target - self.tolerance,
+            p.score > self.target + self.tolerance,
+        );
+        let (lower, raise) = if !self.passes(p) {
+            (true, false)
+        } else if self.use_butter {
+            (better, worse)
+        } else {
+            (worse, better)
+        };
+        if lower {
             state.search_max = state.last_crf - 0.25;
-        } else if score > self.target + self.tolerance {
+        } else if raise {
             state.search_min = state.last_crf + 0.25;
         }
+        if state.search_min > state.search_max && state.narrowed {
+            // the prediction was off: search on past the edge it ran into
+            state.narrowed = false;
+            if raise {
+                state.search_max = self.qp_max;
+            } else {
+                state.search_min = self.qp_min;
+            }
+        }
         state.search_min > state.search_max
     }
 
+    // In tolerance & passing, non-monotonic or out of range; failing all terms, only at `qp_min`
+    #[inline(always)]
+    fn settled(&self, state: &mut TQState, p: &Probe) -> bool {
+        if self.converged(p) {
+            true
+        } else if self.passes(p) || state.probes.iter().any(|q| self.passes(q)) {
+            state
+                .probes
+                .iter()
+                .any(|q| (q.crf - p.crf) * (q.score - p.score) >= 0.0)
+                || self.up_bounds(state, p)
+        } else if !self.up_bounds(state, p) {
+            false
+        } else if state.probes.iter().chain([p]).any(|q| q.crf <= self.qp_min) {
+            true
+        } else {
+            (state.search_min, state.search_max) = (self.qp_min, self.qp_min);
+            false
+        }
+    }
+
     #[inline(always)]
     fn best_probe<'a>(&self, probes: &'a [Probe]) -> &'a Probe {
         unsafe {
             probes
                 .iter()
-                .min_by(|a, b| {
-                    (a.score - self.target)
-                        .abs()
-                        .total_cmp(&(b.score - self.target).abs())
-                })
+                .min_by(|a, b| self.cost(a).total_cmp(&self.cost(b)))
                 .unwrap_unchecked()
         }
     }
@@ -728,7 +919,7 @@ impl TQCtx {
 #[inline(never)]
 #[cfg(feature = "vship")]
 fn complete_chnk(
-    chnk_idx: u16,
+    chnk: &Chunk,
     chnk_frames: usize,
     file_sz: u64,
     ctx: &TQWorkerCtx,
@@ -736,6 +927,15 @@ fn complete_chnk(
     best: &Probe,
 ) {
     unsafe { mpsc_send(ctx.done_tx, 1) };
+    ctx.seeds.add(chnk, best.crf);
+    let chnk_idx = chnk.idx;
+    log_chunk(
+        ctx.work_dir,
+        chnk_idx,
+        tq_state.worker,
+        tq_state.started.elapsed().as_secs_f32(),
+        chnk.params.as_deref(),
+    );
 
     let comp = ChunkComp {
         idx: chnk_idx,
@@ -753,6 +953,15 @@ fn complete_chnk(
         s.completed_frames.fetch_add(comp.frames, Relaxed);
         s.tot_sz.fetch_add(comp.sz, Relaxed);
     }
+    if !ctx.tq_ctx.passes(best) {
+        unmet(chnk_idx, &ctx.tq_ctx.also, best);
+    }
+    chunk_done(
+        chnk_idx,
+        chnk_frames,
+        file_sz,
+        Some((best.crf, Some(best.score))),
+    );
 
     let probes_with_sz: Vec<(f32, f32, u64)> = tq_state
         .probes
@@ -770,6 +979,7 @@ fn complete_chnk(
     let log_entry = ProbeLog {
         chnk_idx,
         probes: probes_with_sz,
+        also: tq_state.probes.iter().map(|p| p.also.clone()).collect(),
         final_crf: best.crf,
         final_score: best.score,
         final_sz: file_sz,
@@ -780,17 +990,37 @@ fn complete_chnk(
     ctx.tq_logger.lock().push(log_entry);
 }
 
+// No probe down to `qp_min` met every `--tq-and` term: `best` ships, named in a `constraint` event
+#[cold]
+#[inline(never)]
+#[cfg(feature = "vship")]
+fn unmet(idx: u16, also: &[Also], best: &Probe) {
+    let terms: Vec<_> = also
+        .iter()
+        .zip(&best.also)
+        .filter(|&(a, &s)| !a.ok(s))
+        .map(|(a, _)| json_str(&a.key()))
+        .collect();
+    emit(
+        "constraint",
+        format_args!(
+            ",\"chunk\":{idx},\"crf\":{:.2},\"unmet\":[{}]",
+            best.crf,
+            terms.join(",")
+        ),
+    );
+}
+
 #[cfg(feature = "vship")]
-fn retain_swap(pkg: &mut WorkPkg, score: f32) {
+fn retain_swap(pkg: &mut WorkPkg, cost: f32) {
     let WorkPkg {
         ref mut probe,
         ref mut tq_state,
         ..
     } = *pkg;
     let tq = unsafe { tq_state.as_mut().unwrap_unchecked() };
-    let diff = (score - tq.target).abs();
-    if diff < tq.best_diff {
-        tq.best_diff = diff;
+    if cost < tq.best_diff {
+        tq.best_diff = cost;
         swap(probe, &mut tq.best_probe);
     }
 }
@@ -837,6 +1067,7 @@ macro_rules! make_metric_loop {
         $prep:expr,
         $retain:expr,
         $output:expr,
+        $fin_out:expr,
         $mk_split:expr,
         $calc:expr
     ) => {
@@ -864,7 +1095,7 @@ macro_rules! make_metric_loop {
                 let tq_st = unsafe { pkg.tq_state.as_ref().unwrap_unchecked() };
                 if tq_st.final_enc {
                     let best = ctx.tq_ctx.best_probe(&tq_st.probes);
-                    let sz = ($output)(
+                    let sz = ($fin_out)(
                         enc_path.set(pkg.chnk.idx),
                         tq_st,
                         &mut split_path,
@@ -872,17 +1103,22 @@ macro_rules! make_metric_loop {
                         best.crf,
                         pkg.probe.len(),
                     );
-                    complete_chnk(pkg.chnk.idx, pkg.frame_cnt, sz, ctx, tq_st, best);
+                    complete_chnk(&pkg.chnk, pkg.frame_cnt, sz, ctx, tq_st, best);
                     continue;
                 }
 
                 if vship.is_none() {
+                    let t = ctx.tq_ctx;
+                    let need = |m| t.also.iter().any(|a| a.metric == m);
                     let v = VshipProcessor::new(
                         pkg.width,
                         pkg.height,
                         ctx.inf,
-                        ctx.tq_ctx.use_cvvdp,
-                        ctx.tq_ctx.use_butter,
+                        [
+                            (!t.use_cvvdp && !t.use_butter) || need(Metric::Ssimu2),
+                            t.use_cvvdp || need(Metric::Cvvdp),
+                            t.use_butter || need(Metric::Butter),
+                        ],
                         disp,
                     )
                     .unwrap_or_else(|e| fatal(e));
@@ -890,48 +1126,75 @@ macro_rules! make_metric_loop {
                 }
 
                 let tq_st = unsafe { pkg.tq_state.as_ref().unwrap_unchecked() };
-                let crf = tq_st.last_crf;
+                let (crf, sample) = (tq_st.last_crf, tq_st.sample);
                 let last_score = tq_st.probes.last().map(|probe| probe.score);
                 let metric_slot = ctx.worker_cnt + worker_id;
+                // a thinned probe holds only the sampled frames: each is scored
+                let (step, stride) = if ctx.tq_ctx.thin {
+                    (sample, 1)
+                } else {
+                    (1, sample)
+                };
 
                 let d = dec.get_or_insert_with(|| ($mk_dec)(ctx.threads));
-                let probe_sz = ($prep)(d, &pkg, &mut split_path, pkg.chnk.idx, crf);
-                unsafe { pkg.tq_state.as_mut().unwrap_unchecked() }
-                    .probe_szs
-                    .push((crf, probe_sz));
-
                 let mp = MetricProgs {
                     prog: ctx.prog,
                     slot: metric_slot,
-                    crf,
+                    crf: Some(crf),
                     last_score,
                 };
-                let score = ($calc)(
-                    &pkg,
-                    d,
-                    ctx.pipe,
-                    unsafe { vship.as_ref().unwrap_unchecked() },
-                    ctx.metric_mode,
-                    &mut unpacked_buf,
-                    &mp,
-                );
-
-                ($retain)(&mut pkg, score);
+                let pick = (stride > 1)
+                    .then(|| ctx.tq_ctx.wt.get(pkg.chnk.start..pkg.chnk.end))
+                    .flatten()
+                    .map(|w| scd_pick(w, stride));
+                let (probe_sz, (score, also)) = with_thin(&mut pkg, ctx.pipe.frame_sz, step, |p| {
+                    let sz = ($prep)(d, p, &mut split_path, p.chnk.idx, crf);
+                    let res = ($calc)(
+                        p,
+                        d,
+                        ctx.pipe,
+                        unsafe { vship.as_ref().unwrap_unchecked() },
+                        &ScoreSpec {
+                            mode: ctx.metric_mode,
+                            also: &ctx.tq_ctx.also,
+                            fps: ctx.inf.fps_num as f32 / ctx.inf.fps_den as f32 / step as f32,
+                            stride,
+                            pick: pick.as_deref(),
+                            keep: None,
+                        },
+                        &mut unpacked_buf,
+                        &mp,
+                    );
+                    (sz, res)
+                });
+                unsafe { pkg.tq_state.as_mut().unwrap_unchecked() }
+                    .probe_szs
+                    .push((crf, probe_sz));
+                let probe = Probe { crf, score, also };
+
+                ($retain)(&mut pkg, ctx.tq_ctx.cost(&probe));
+                // sampled scores would pass for full ones in a later run
+                if let Some(c) = ctx.cache
+                    && sample == 1
+                {
+                    c.put(&pkg.chnk, &probe, probe_sz);
+                }
 
                 let tq_state = unsafe { pkg.tq_state.as_mut().unwrap_unchecked() };
 
-                let should_complete = ctx.tq_ctx.converged(score)
-                    || tq_state
-                        .probes
-                        .iter()
-                        .any(|p| (p.crf - crf) * (p.score - score) >= 0.0)
-                    || ctx.tq_ctx.up_bounds(tq_state, score);
+                let should_complete = ctx.tq_ctx.settled(tq_state, &probe);
 
-                tq_state.probes.push(Probe { crf, score });
+                tq_state.probes.push(probe);
 
                 if should_complete {
+                    let pick = ctx.tq_ctx.best_probe(&tq_state.probes).crf;
+                    if start_verify(tq_state, pick) {
+                        unsafe { mpsc_send(work_tx, Box::into_raw(pkg) as u64) };
+                        continue;
+                    }
                     let best = ctx.tq_ctx.best_probe(&tq_state.probes);
-                    if ctx.use_alt_param {
+                    // a cached pick has no bitstream from this run
+                    if ctx.use_alt_param || tq_state.reused.contains(&best.crf) {
                         tq_state.final_enc = true;
                         tq_state.last_crf = best.crf;
                         unsafe { mpsc_send(work_tx, Box::into_raw(pkg) as u64) };
@@ -944,7 +1207,7 @@ macro_rules! make_metric_loop {
                             best.crf,
                             pkg.probe.len(),
                         );
-                        complete_chnk(pkg.chnk.idx, pkg.frame_cnt, sz, ctx, tq_state, best);
+                        complete_chnk(&pkg.chnk, pkg.frame_cnt, sz, ctx, tq_state, best);
                     }
                 } else {
                     unsafe { mpsc_send(work_tx, Box::into_raw(pkg) as u64) };
@@ -961,6 +1224,7 @@ macro_rules! make_metric_group {
         $prep:expr,
         $retain:expr,
         $output:expr,
+        $fin_out:expr,
         $mk_split:expr,
         $ss8:ident,
         $c_ss8:expr,
@@ -981,15 +1245,33 @@ macro_rules! make_metric_group {
         $cvr:ident,
         $c_cvr:expr
     ) => {
-        make_metric_loop!($ss8, $mk_dec, $prep, $retain, $output, $mk_split, $c_ss8);
-        make_metric_loop!($ss10, $mk_dec, $prep, $retain, $output, $mk_split, $c_ss10);
-        make_metric_loop!($ssr, $mk_dec, $prep, $retain, $output, $mk_split, $c_ssr);
-        make_metric_loop!($bu8, $mk_dec, $prep, $retain, $output, $mk_split, $c_bu8);
-        make_metric_loop!($bu10, $mk_dec, $prep, $retain, $output, $mk_split, $c_bu10);
-        make_metric_loop!($bur, $mk_dec, $prep, $retain, $output, $mk_split, $c_bur);
-        make_metric_loop!($cv8, $mk_dec, $prep, $retain, $output, $mk_split, $c_cv8);
-        make_metric_loop!($cv10, $mk_dec, $prep, $retain, $output, $mk_split, $c_cv10);
-        make_metric_loop!($cvr, $mk_dec, $prep, $retain, $output, $mk_split, $c_cvr);
+        make_metric_loop!(
+            $ss8, $mk_dec, $prep, $retain, $output, $fin_out, $mk_split, $c_ss8
+        );
+        make_metric_loop!(
+            $ss10, $mk_dec, $prep, $retain, $output, $fin_out, $mk_split, $c_ss10
+        );
+        make_metric_loop!(
+            $ssr, $mk_dec, $prep, $retain, $output, $fin_out, $mk_split, $c_ssr
+        );
+        make_metric_loop!(
+            $bu8, $mk_dec, $prep, $retain, $output, $fin_out, $mk_split, $c_bu8
+        );
+        make_metric_loop!(
+            $bu10, $mk_dec, $prep, $retain, $output, $fin_out, $mk_split, $c_bu10
+        );
+        make_metric_loop!(
+            $bur, $mk_dec, $prep, $retain, $output, $fin_out, $mk_split, $c_bur
+        );
+        make_metric_loop!(
+            $cv8, $mk_dec, $prep, $retain, $output, $fin_out, $mk_split, $c_cv8
+        );
+        make_metric_loop!(
+            $cv10, $mk_dec, $prep, $retain, $output, $fin_out, $mk_split, $c_cv10
+        );
+        make_metric_loop!(
+            $cvr, $mk_dec, $prep, $retain, $output, $fin_out, $mk_split, $c_cvr
+        );
     };
 }
 
@@ -999,6 +1281,7 @@ make_metric_group!(
     prep_dav1d,
     retain_swap,
     output_bytes,
+    output_probe,
     split_unused,
     met_d_ss_8b,
     calc_ssimu2_8b_dav1d,
@@ -1025,6 +1308,7 @@ make_metric_group!(
     prep_dav1d,
     retain_noop,
     output_probe,
+    output_probe,
     split_unused,
     met_da_ss_8b,
     calc_ssimu2_8b_dav1d,
@@ -1051,6 +1335,7 @@ make_metric_group!(
     prep_ff,
     retain_noop,
     output_copy,
+    output_stat,
     SplitPath::new,
     met_f_ss_8b,
     calc_ssimu2_8b_ff,
@@ -1077,6 +1362,7 @@ make_metric_group!(
     prep_ff,
     retain_noop,
     output_stat,
+    output_stat,
     SplitPath::new,
     met_fa_ss_8b,
     calc_ssimu2_8b_ff,
@@ -1210,17 +1496,30 @@ fn parse_tq_ctx(args: &Args) -> TQCtx {
         qp_max: qp_parts[1],
         use_butter: tq_target < 8.0,
         use_cvvdp: is_cvvdp(tq_target),
+        also: args.tq_and.clone().into(),
+        sample: args.tq_sample,
+        thin: args.tq_sample_by == Sample::Enc,
+        wt: Arc::default(),
     }
 }
 
 #[cfg(feature = "vship")]
-fn tq_coord(coord: &SeqRing, enc: &SeqRing, tot_chnks: usize, permits: &Semaphore) {
-    let mut completed = 0;
-    while completed < tot_chnks {
+fn tq_coord(
+    coord: &SeqRing,
+    enc: &SeqRing,
+    tot_chnks: usize,
+    permits: &Semaphore,
+    sent: &AtomicUsize,
+) {
+    let (mut completed, mut tot) = (0, tot_chnks);
+    while completed < tot {
         let m = unsafe { mpsc_recv(coord) };
         if m == 1 {
             sem_release(permits);
             completed += 1;
+        } else if m == 2 {
+            // the decoder is done: after a stop that can be before every chunk was sent
+            tot = sent.load(Relaxed);
         } else {
             unsafe { spmc_send(enc, m) };
         }
@@ -1230,17 +1529,102 @@ fn tq_coord(coord: &SeqRing, enc: &SeqRing, tot_chnks: usize, permits: &Semaphor
 
 #[cfg(feature = "vship")]
 #[inline]
-fn tq_search_crf(tq: &mut TQState, encoder: Encoder) -> f32 {
-    tq.round += 1;
-    let c = if tq.round <= 2 {
-        bisect(tq.search_min, tq.search_max)
-    } else {
-        interpolate_crf(&tq.probes, tq.target, tq.round)
+// Next CRF to probe, taking cached points as measured; `None` once those settle the chunk
+fn tq_search_crf(tq: &mut TQState, tq_ctx: &TQCtx, encoder: Encoder) -> Option<f32> {
+    if tq.round == 0 && seed_known(tq, tq_ctx) {
+        return None;
+    }
+    loop {
+        tq.round += 1;
+        let c = if let Some(s) = tq.seed.take() {
+            s
+        } else if tq.round <= 2 || !tq.probes.iter().any(|p| tq_ctx.passes(p)) {
+            // failing a `--tq-and` term, the `-t` target says nothing about how far down to go
+            bisect(tq.search_min, tq.search_max)
+        } else {
+            interpolate_crf(&tq.probes, tq.target, tq.round)
+        }
+        .clamp(tq.search_min, tq.search_max);
+        let c = if encoder.integer_qp() { c.round() } else { c };
+        tq.last_crf = c;
+        let Some(i) = tq.known.iter().position(|k| (k.0.crf - c).abs() < 1e-3) else {
+            return Some(c);
+        };
+        let (p, sz) = tq.known.swap_remove(i);
+        let done = tq_ctx.settled(tq, &p);
+        tq.probes.push(p);
+        tq.probe_szs.push((c, sz));
+        tq.reused.push(c);
+        if done {
+            return None;
+        }
+    }
+}
+
+// Cached probes in the window narrow it before round 1; true once they settle the chunk
+#[cfg(feature = "vship")]
+fn seed_known(tq: &mut TQState, tq_ctx: &TQCtx) -> bool {
+    while let Some(i) = tq
+        .known
+        .iter()
+        .position(|k| (tq.search_min..=tq.search_max).contains(&k.0.crf))
+    {
+        let (p, sz) = tq.known.swap_remove(i);
+        // the prediction would only land on the narrowed window's edge
+        tq.seed = None;
+        tq.round += 1;
+        tq.last_crf = p.crf;
+        let done = tq_ctx.settled(tq, &p);
+        tq.probe_szs.push((p.crf, sz));
+        tq.reused.push(p.crf);
+        tq.probes.push(p);
+        if done {
+            return true;
+        }
+    }
+    false
+}
+
+// Re-scores a `--tq-sample` pick on all frames; false for a full search or a cached pick
+#[cfg(feature = "vship")]
+fn start_verify(tq: &mut TQState, crf: f32) -> bool {
+    if tq.sample <= 1 || tq.reused.contains(&crf) {
+        return false;
     }
-    .clamp(tq.search_min, tq.search_max);
-    let c = if encoder.integer_qp() { c.round() } else { c };
-    tq.last_crf = c;
-    c
+    tq.sample = 1;
+    tq.probes.clear();
+    tq.probe_szs.clear();
+    tq.reused.clear();
+    tq.best_probe.clear();
+    tq.best_diff = f32::INFINITY;
+    (tq.search_min, tq.search_max) = (crf, crf);
+    tq.narrowed = true;
+    tq.round = 0;
+    true
+}
+
+// `--tq-sample encN`: `f` sees only frames 0, N, 2N.. of the chunk, the whole one is put back after
+#[cfg(feature = "vship")]
+fn with_thin<R>(
+    pkg: &mut WorkPkg,
+    frame_sz: usize,
+    step: usize,
+    f: impl FnOnce(&mut WorkPkg) -> R,
+) -> R {
+    if step <= 1 {
+        return f(pkg);
+    }
+    let cnt = pkg.frame_cnt.div_ceil(step);
+    let mut thin = Vec::with_capacity(cnt * frame_sz);
+    for fr in pkg.yuv.chunks_exact(frame_sz).step_by(step) {
+        thin.extend_from_slice(fr);
+    }
+    let full = replace(&mut pkg.yuv, thin);
+    let full_cnt = replace(&mut pkg.frame_cnt, cnt);
+    let r = f(pkg);
+    pkg.yuv = full;
+    pkg.frame_cnt = full_cnt;
+    r
 }
 
 #[cfg(feature = "vship")]
@@ -1248,6 +1632,12 @@ struct TqEncParams<'a> {
     tmpls: Option<&'a TqTmpls>,
     params: &'a str,
     alt_param: Option<&'a str>,
+    cache: Option<&'a ProbeCache>,
+    seeds: &'a CrfSeeds,
+    pins: &'a BTreeMap<u16, f32>,
+    // a chunk that fails every retry is counted done here & listed in `failed`
+    done: &'a SeqRing,
+    failed: &'a Mutex<Vec<u16>>,
 }
 
 #[cfg(feature = "vship")]
@@ -1271,6 +1661,11 @@ macro_rules! make_tq_loop {
                 tmpls,
                 params,
                 alt_param,
+                cache,
+                seeds,
+                pins,
+                done,
+                failed,
             } = enc;
             let mut conv_buf = vec![0u8; ctx.pipe.conv_buf_sz];
             let ext = ctx.encoder.extension();
@@ -1281,49 +1676,96 @@ macro_rules! make_tq_loop {
                 (t.base.as_slice(), t.alt.as_deref().unwrap_or(&t.base))
             });
             loop {
+                ctl::gate(worker_id);
                 let m = unsafe { spmc_recv(rx) };
                 if m == 0 {
                     cold_path();
                     break;
                 }
                 let mut $pkg = unsafe { Box::from_raw(m as *mut WorkPkg) };
-                let tq = $pkg.tq_state.get_or_insert_with(|| TQState {
-                    probes: Vec::new(),
-                    probe_szs: Vec::new(),
-                    search_min: tq_ctx.qp_min,
-                    search_max: tq_ctx.qp_max,
-                    round: 0,
-                    target: tq_ctx.target,
-                    last_crf: 0.0,
-                    final_enc: false,
-                    best_probe: Vec::new(),
-                    best_diff: f32::INFINITY,
+                // a `stop` drops chunks no worker has started; an interrupt drops all in flight
+                if ctl::stopping() && (!ctl::draining() || $pkg.tq_state.is_none()) {
+                    drop($pkg);
+                    unsafe { mpsc_send(done, 1) };
+                    continue;
+                }
+                // the chunk's first probe: its log starts over
+                let fresh = $pkg.tq_state.is_none();
+                let tq = $pkg.tq_state.get_or_insert_with(|| {
+                    // `--crf-smooth` pins a chunk's window to its smoothed CRF
+                    let pin = pins.get(&$pkg.chnk.idx).copied();
+                    let pred = pin.map_or_else(
+                        || seeds.predict(&$pkg.chnk, tq_ctx.qp_min, tq_ctx.qp_max),
+                        |c| Some((c, c, c)),
+                    );
+                    TQState {
+                        probes: Vec::new(),
+                        probe_szs: Vec::new(),
+                        search_min: pred.map_or(tq_ctx.qp_min, |p| p.1),
+                        search_max: pred.map_or(tq_ctx.qp_max, |p| p.2),
+                        round: 0,
+                        target: tq_ctx.target,
+                        last_crf: 0.0,
+                        final_enc: false,
+                        best_probe: Vec::new(),
+                        best_diff: f32::INFINITY,
+                        known: cache.map_or_else(Vec::new, |c| c.get(&$pkg.chnk)),
+                        reused: Vec::new(),
+                        seed: pred.map(|p| p.0),
+                        // a pin missing tolerance reopens like a missed prediction
+                        narrowed: pred.is_some(),
+                        // a pinned CRF is re-measured once: no search to sample for
+                        sample: if pin.is_some() { 1 } else { tq_ctx.sample },
+                        worker: worker_id,
+                        started: Mono::now(),
+                    }
                 });
-                let $is_final = tq.final_enc;
-                let $crf = if $is_final {
+                let $crf = if tq.final_enc {
                     tq.last_crf
+                } else if let Some(c) = tq_search_crf(tq, tq_ctx, ctx.encoder) {
+                    c
                 } else {
-                    tq_search_crf(tq, ctx.encoder)
+                    // settled on cached points: encode the pick for its bitstream
+                    let c = tq_ctx.best_probe(&tq.probes).crf;
+                    if start_verify(tq, c) {
+                        tq.round = 1;
+                    } else {
+                        tq.final_enc = true;
+                    }
+                    tq.last_crf = c;
+                    c
                 };
+                let $is_final = tq.final_enc;
+                // a verified or final encode has `sample` back at 1, so it is never thinned
+                let step = if tq_ctx.thin { tq.sample } else { 1 };
                 let (p, dst) = if $is_final {
                     (params, Some(enc_path.set($pkg.chnk.idx)))
                 } else {
                     (probe_params, $probe_dst)
                 };
                 let svt_t = $sel;
-                (ctx.probe_fn)(
-                    &mut $pkg,
-                    $crf,
-                    &EncRecipe {
-                        params: p,
-                        template: svt_t,
-                    },
-                    ctx,
-                    &mut conv_buf,
-                    worker_id,
-                    dst,
-                );
-                unsafe { mpmc_send(tx, Box::into_raw($pkg) as u64) };
+                let zone = $pkg.chnk.params.clone();
+                // the fallback try swaps in `fb_tmpls`, indexed like the zones
+                let try_enc = |w: &mut WorkPkg, c: &EncWorkerCtx, buf: &mut [u8]| {
+                    let template = c.tmpls.get(w.chnk.tmpl as usize).map_or(svt_t, |t| Some(&**t));
+                    let recipe = EncRecipe { params: p, template };
+                    with_thin(w, c.pipe.frame_sz, step, |w| {
+                        (c.probe_fn)(w, $crf, &recipe, c, buf, worker_id, dst)
+                    })
+                };
+                let ok =
+                    enc_retry(&mut $pkg, p, Some($crf), fresh, ctx, &mut conv_buf, try_enc).is_some();
+                // each probe starts again from the chunk's own zone params
+                $pkg.chnk.params = zone;
+                if ok {
+                    unsafe { mpmc_send(tx, Box::into_raw($pkg) as u64) };
+                } else {
+                    if !ctl::stopping() {
+                        failed.lock().push($pkg.chnk.idx);
+                    }
+                    drop($pkg);
+                    unsafe { mpsc_send(done, 1) };
+                }
             }
         }
     };
@@ -1416,20 +1858,25 @@ fn spawn_tq_dec(
     let coord_dec = Arc::clone(&coord);
     let permits_dec = Arc::clone(permits);
     let permits_done = Arc::clone(permits);
+    let sent = Arc::new(AtomicUsize::new(0));
+    let sent_dec = Arc::clone(&sent);
     let handle = spawn(move || {
         let inf2 = inf.clone();
         let dec = pspawn(move || {
             let rp = Arc::as_ptr(&coord_dec);
-            let send = move |p: WorkPkg| unsafe {
+            let send = |p: WorkPkg| unsafe {
+                sent_dec.fetch_add(1, Relaxed);
                 mpsc_send(rp, Box::into_raw(Box::new(p)) as u64);
             };
             if let Some(mut r) = pipe_reader {
                 dec_pipe(&chnks, &mut r, &inf2, &send, &skip, strat, &permits_dec);
             } else {
-                dec_chnks(&chnks, &path, &inf2, &send, &skip, strat, &permits_dec);
+                dec_chnks(&chnks, &path, &inf2, &send, &skip, strat, &permits_dec)
+                    .unwrap_or_else(|e| fatal(e));
             }
+            unsafe { mpsc_send(rp, 2) };
         });
-        tq_coord(&coord2, &enc2, tot, &permits_done);
+        tq_coord(&coord2, &enc2, tot, &permits_done, &sent);
         dec.join();
     });
     TQDecodeResult { enc, coord, handle }
@@ -1443,23 +1890,115 @@ fn enc_tq(
     path: &Path,
     work_dir: &Path,
     pipe_reader: Option<PipeReader>,
-) {
+) -> Vec<u16> {
+    let failed = tq_pass(
+        chnks,
+        inf,
+        args,
+        path,
+        work_dir,
+        pipe_reader,
+        BTreeMap::new(),
+    );
+    // smoothing needs every chunk's CRF: a failed one is encoded again first
+    let Some(delta) = args.crf_smooth.filter(|_| failed.is_empty()) else {
+        return failed;
+    };
+    if ctl::stopping() {
+        return failed;
+    }
+    let pins = smooth_pins(work_dir, &parse_tq_ctx(args), args.encoder, delta);
+    if pins.is_empty() {
+        return failed;
+    }
+    emit("smooth", format_args!(",\"chunks\":{}", pins.len()));
+    if let Some(mut res) = get_resume(work_dir) {
+        res.chnks_done.retain(|c| !pins.contains_key(&c.idx));
+        _ = save_resume(&res, work_dir);
+    }
+    tq_pass(chnks, inf, args, path, work_dir, None, pins)
+}
+
+// `--crf-smooth`: chunks to encode again, with their new CRF
+#[cfg(feature = "vship")]
+fn smooth_pins(work_dir: &Path, tq: &TQCtx, encoder: Encoder, delta: f32) -> BTreeMap<u16, f32> {
+    let Ok(mut buf) = read(work_dir.join("chunks.json")) else {
+        return BTreeMap::new();
+    };
+    buf.extend_from_slice(&[0u8; 16]);
+    let (logs, tri) = parse_chunks(&buf);
+    // a chunk encoded again logs a new line: the last one counts
+    let last: BTreeMap<usize, &TqChunkLine> = logs.iter().map(|l| (l.id, l)).collect();
+    let mut inp: Vec<(Vec<(f32, f32)>, f32)> = last
+        .values()
+        .map(|l| {
+            let probes = tri.get(l.po..l.po + l.pn).unwrap_or(&[]);
+            (probes.iter().map(|&(c, s, _)| (c, s)).collect(), l.fc)
+        })
+        .collect();
+    let step = if encoder.integer_qp() { 1.0 } else { 0.25 };
+    let crfs = smooth_crfs(
+        &mut inp,
+        (tq.target, tq.tolerance),
+        step,
+        !tq.also.is_empty(),
+        delta,
+    );
+    last.keys()
+        .zip(crfs.iter().zip(&inp))
+        .filter(|&(_, (c, l))| (c - l.1).abs() >= step / 2.0)
+        .map(|(&id, (&c, _))| (id as u16, c))
+        .collect()
+}
+
+#[cfg(feature = "vship")]
+fn tq_pass(
+    chnks: &[Chunk],
+    inf: &VidInf,
+    args: &Args,
+    path: &Path,
+    work_dir: &Path,
+    pipe_reader: Option<PipeReader>,
+    pins: BTreeMap<u16, f32>,
+) -> Vec<u16> {
     let resume_data = load_resume_data(work_dir);
     let (skip_indices, completed_cnt, completed_frames) = build_skip_set(&resume_data);
-    let tq_ctx = parse_tq_ctx(args);
+    let mut tq_ctx = parse_tq_ctx(args);
+    if args.tq_sample_by == Sample::Scd {
+        // without weights (an `--sc` file from elsewhere), probes fall back to every Nth frame
+        tq_ctx.wt = load_wt(&args.sc_file, inf.frames).into();
+    }
+    // a pipe has no stable identity to key cached probes on
+    let cache = pipe_reader
+        .is_none()
+        .then(|| ProbeCache::open(path, args, tq_ctx.metric_name()))
+        .flatten()
+        .map(Arc::new);
     let strat = unsafe { args.dec_strat.unwrap_unchecked() };
     let pipe = Pipeline::new(inf, strat, args.tq.as_deref());
     let permits = Arc::new(Semaphore::new(args.chnk_buff));
+    ctl::bind(&permits);
     let build = resolve_build_tmpl(args.encoder);
     let mut chnks = chnks.to_vec();
     let zones = build.map_or_else(Vec::new, |_| zone_tmpls(&mut chnks));
     let chnks = &chnks;
 
-    let dec = spawn_tq_dec(chnks, path, inf, skip_indices, strat, &permits, pipe_reader);
+    let order = dec_order(chnks, args, inf, work_dir);
+    let dec = spawn_tq_dec(
+        &order,
+        path,
+        inf,
+        skip_indices,
+        strat,
+        &permits,
+        pipe_reader,
+    );
     let met = Arc::new(SeqRing::new());
 
     let resume_state = Arc::new(Mutex::new(resume_data.clone()));
+    track_resume(&resume_state, work_dir);
     let tq_logger = Arc::new(Mutex::new(Vec::new()));
+    let failed = Arc::new(Mutex::new(Vec::new()));
     let stats = create_stats(completed_cnt, &resume_data);
     let (prog, display_handle) = ProgsTrack::new(
         chnks,
@@ -1481,28 +2020,43 @@ fn enc_tq(
         stats,
         resume_state: &resume_state,
         tq_logger: &tq_logger,
+        failed: &failed,
         tq_ctx,
         zones: &zones,
         build,
         encoder: args.encoder,
         use_alt_param: args.alt_param.is_some(),
         worker_cnt: args.worker,
+        cache,
+        seeds: Arc::new(load_seeds(chnks, args, inf, work_dir)),
+        pins: Arc::new(pins),
     };
 
     init_device().unwrap_or_else(|e| fatal(e));
 
     let metric_workers = spawn_tq_metric(args.metric_worker, &met, &dec.coord, &sc);
 
-    let workers = spawn_tq_encoders(&dec.enc, &met, &sc);
+    let workers = spawn_tq_encoders(&dec.enc, &met, &dec.coord, &sc);
 
     join_one(dec.handle);
     join_all(workers);
     unsafe { mpmc_close(Arc::as_ptr(&met)) };
     metric_workers.into_iter().for_each(PHandle::join);
 
-    write_tq_log(&args.inp, work_dir, inf, sc.tq_ctx.metric_name());
+    write_tq_log(
+        &args.inp,
+        work_dir,
+        inf,
+        sc.tq_ctx.metric_name(),
+        &args.tq_and,
+    );
+    ctl::unbind();
+    untrack_resume();
     drop(prog);
     join_one(display_handle);
+    let mut failed = take(&mut *failed.lock());
+    failed.sort_unstable();
+    failed
 }
 
 #[cfg(feature = "vship")]
@@ -1515,12 +2069,16 @@ struct TQSpawnCtx<'a> {
     stats: Option<Arc<WorkerStats>>,
     resume_state: &'a Arc<Mutex<ResumeInf>>,
     tq_logger: &'a Arc<Mutex<Vec<ProbeLog>>>,
+    failed: &'a Arc<Mutex<Vec<u16>>>,
     tq_ctx: TQCtx,
     zones: &'a [Box<str>],
     build: Option<BuildTmpl>,
     encoder: Encoder,
     use_alt_param: bool,
     worker_cnt: usize,
+    cache: Option<Arc<ProbeCache>>,
+    seeds: Arc<CrfSeeds>,
+    pins: Arc<BTreeMap<u16, f32>>,
 }
 
 #[cfg(feature = "vship")]
@@ -1545,19 +2103,21 @@ fn spawn_tq_metric(
         let rx = Arc::clone(met);
         let coord = Arc::clone(coord);
         let (inf, pipe, wd) = (sc.inf.clone(), sc.pipe.clone(), sc.work_dir.to_path_buf());
-        let (metric_mode, st) = (sc.args.metric_mode.clone(), sc.stats.clone());
+        let (metric_mode, st) = (sc.args.metric_mode, sc.stats.clone());
         let (resume_state, tq_logger, prog_clone) = (
             Arc::clone(sc.resume_state),
             Arc::clone(sc.tq_logger),
             Arc::clone(sc.prog),
         );
-        let (tq_ctx, use_alt_param, worker_cnt) = (sc.tq_ctx, sc.use_alt_param, sc.worker_cnt);
+        let (tq_ctx, use_alt_param, worker_cnt) =
+            (sc.tq_ctx.clone(), sc.use_alt_param, sc.worker_cnt);
+        let (cache, seeds) = (sc.cache.clone(), Arc::clone(&sc.seeds));
         metric_workers.push(pspawn(move || {
             let ctx = TQWorkerCtx {
                 inf: &inf,
                 pipe: &pipe,
                 work_dir: &wd,
-                metric_mode: &metric_mode,
+                metric_mode,
                 prog: &prog_clone,
                 done_tx: &coord,
                 resume_state: &resume_state,
@@ -1568,6 +2128,8 @@ fn spawn_tq_metric(
                 worker_cnt,
                 threads,
                 ext,
+                cache: cache.as_deref(),
+                seeds: &seeds,
             };
             metric_loop(&rx, &coord, &ctx, worker_id, disp);
         }));
@@ -1586,8 +2148,23 @@ struct TqTmpls {
 fn spawn_tq_encoders(
     enc: &Arc<SeqRing>,
     met: &Arc<SeqRing>,
+    coord: &Arc<SeqRing>,
     sc: &TQSpawnCtx,
 ) -> Vec<JoinHandle<()>> {
+    let fb_tmpls = sc.build.zip(sc.args.fallback.as_deref()).map(|(b, fb)| {
+        let zones: Vec<Box<str>> = sc
+            .zones
+            .iter()
+            .map(|z| format!("{z} {fb}").into())
+            .collect();
+        build_zoned(
+            b,
+            sc.inf,
+            &format!("{} {fb}", sc.args.params),
+            sc.pipe,
+            &zones,
+        )
+    });
     let tmpls = sc.build.map(|build| TqTmpls {
         base: build_zoned(build, sc.inf, &sc.args.params, sc.pipe, sc.zones),
         alt: sc
@@ -1610,8 +2187,15 @@ fn spawn_tq_encoders(
         let (inf, pipe, wd) = (sc.inf.clone(), sc.pipe.clone(), sc.work_dir.to_path_buf());
         let (params, alt_param) = (sc.args.params.clone(), sc.args.alt_param.clone());
         let prog_clone = Arc::clone(sc.prog);
-        let (tq_ctx, encoder) = (sc.tq_ctx, sc.encoder);
-        let tmpls = tmpls.clone();
+        let (tq_ctx, encoder) = (sc.tq_ctx.clone(), sc.encoder);
+        let (tmpls, cache, seeds) = (tmpls.clone(), sc.cache.clone(), Arc::clone(&sc.seeds));
+        let (pins, done, failed) = (
+            Arc::clone(&sc.pins),
+            Arc::clone(coord),
+            Arc::clone(sc.failed),
+        );
+        let (fb_tmpls, fallback) = (fb_tmpls.clone(), sc.args.fallback.clone());
+        let (retries, dump) = (sc.args.retries, sc.args.dump_failed);
         workers.push(spawn(move || {
             let ctx = EncWorkerCtx {
                 inf: &inf,
@@ -1624,6 +2208,10 @@ fn spawn_tq_encoders(
                 chnk_fn,
                 tmpl: None,
                 tmpls: &[],
+                retries,
+                fallback: fallback.as_deref(),
+                fb_tmpls: fb_tmpls.as_deref().unwrap_or(&[]),
+                dump,
                 probe_fn,
             };
             tq_loop(
@@ -1634,6 +2222,11 @@ fn spawn_tq_encoders(
                     tmpls: tmpls.as_ref(),
                     params: &params,
                     alt_param: alt_param.as_deref(),
+                    cache: cache.as_deref(),
+                    seeds: &seeds,
+                    pins: &pins,
+                    done: &done,
+                    failed: &failed,
                 },
                 &tq_ctx,
                 worker_id,
@@ -1652,7 +2245,7 @@ fn enc_tq_probe_lib(
     conv_buf: &mut [u8],
     worker_id: usize,
     dst: Option<&Path>,
-) {
+) -> Result<u64, Xerr> {
     let &EncRecipe { params, template } = recipe;
     let last_score = pkg
         .tq_state
@@ -1670,7 +2263,7 @@ fn enc_tq_probe_lib(
         frames: pkg.frame_cnt,
     };
     pkg.probe.clear();
-    (ctx.lib_enc)(
+    let sz = (ctx.lib_enc)(
         &mut pkg.yuv,
         &mut pkg.probe,
         &cfg,
@@ -1681,10 +2274,11 @@ fn enc_tq_probe_lib(
             track_frames: false,
             crf_score: Some((crf, last_score)),
         },
-    );
+    )?;
     if let Some(fin) = dst {
-        _ = write(fin, &pkg.probe);
+        write(fin, &pkg.probe)?;
     }
+    Ok(sz)
 }
 
 #[cfg(feature = "vship")]
@@ -1696,7 +2290,7 @@ fn enc_tq_probe_sub(
     conv_buf: &mut [u8],
     worker_id: usize,
     dst: Option<&Path>,
-) {
+) -> Result<u64, Xerr> {
     let &EncRecipe { params, template } = recipe;
     let out = unsafe { dst.unwrap_unchecked() };
     let cfg = EncConfig {
@@ -1712,7 +2306,7 @@ fn enc_tq_probe_sub(
     };
 
     let cmd = make_enc_cmd(ctx.encoder, &cfg, pkg.chnk.params.as_deref());
-    let mut child = cmd.spawn().unwrap_or_else(|e| fatal(e));
+    let mut child = cmd.spawn()?;
 
     let last_score = pkg
         .tq_state
@@ -1729,6 +2323,7 @@ fn enc_tq_probe_sub(
             crf_score: Some((crf, last_score)),
         },
         ctx.encoder,
+        Some(&log_path(ctx.work_dir, pkg.chnk.idx)),
     );
     (ctx.pipe.write_frames)(
         unsafe { child.stdin.as_mut().unwrap_unchecked() },
@@ -1737,11 +2332,20 @@ fn enc_tq_probe_sub(
         conv_buf,
         ctx.pipe,
     );
+    drop(child.stdin.take());
 
-    let status = child.wait().unwrap_or_else(|e| fatal(e));
+    let status = child.wait()?;
     if !status.success() {
-        fatal(format_args!("probe encode failed: {}", out.display()));
+        cold_path();
+        return Err(status
+            .code()
+            .map_or_else(
+                || "encoder was killed by a signal".into(),
+                |c| format!("encoder exited with code {c}"),
+            )
+            .into());
     }
+    Ok(metadata(out).unwrap_or(0))
 }
 
 fn run_enc_worker(
@@ -1756,6 +2360,9 @@ fn run_enc_worker(
     let mut enc_path = OutPath::new(ctx.work_dir, ctx.encoder.extension());
 
     loop {
+        if !ctl::admit(worker_id) {
+            break;
+        }
         let m = unsafe { spmc_recv(rx) };
         if m == 0 {
             cold_path();
@@ -1763,22 +2370,186 @@ fn run_enc_worker(
         }
         let mut pkg = unsafe { Box::from_raw(m as *mut WorkPkg) };
         let out = enc_path.set(pkg.chnk.idx);
-        let sz = (ctx.chnk_fn)(&mut pkg, params, ctx, out, &mut conv_buf, worker_id);
-
-        stats.completed.fetch_add(1, Relaxed);
-        stats.add_completion(
-            ChunkComp {
-                idx: pkg.chnk.idx,
-                frames: pkg.frame_cnt,
-                sz,
-            },
-            ctx.work_dir,
-        );
+        let start = Mono::now();
+        let enc = |p: &mut WorkPkg, c: &EncWorkerCtx, buf: &mut [u8]| {
+            (c.chnk_fn)(p, params, c, out, buf, worker_id)
+        };
+        if let Some(sz) = enc_retry(&mut pkg, params, None, true, ctx, &mut conv_buf, enc) {
+            log_chunk(
+                ctx.work_dir,
+                pkg.chnk.idx,
+                worker_id,
+                start.elapsed().as_secs_f32(),
+                pkg.chnk.params.as_deref(),
+            );
+            stats.completed.fetch_add(1, Relaxed);
+            stats.add_completion(
+                ChunkComp {
+                    idx: pkg.chnk.idx,
+                    frames: pkg.frame_cnt,
+                    sz,
+                },
+                ctx.work_dir,
+            );
+        } else if !ctl::stopping() {
+            stats.failed.lock().push(pkg.chnk.idx);
+        }
 
         sem_release(sem);
     }
 }
 
+fn log_path(work_dir: &Path, idx: u16) -> PathBuf {
+    work_dir.join("logs").join(format!("{idx:04}.log"))
+}
+
+fn log_note(log: &Path, fresh: bool, msg: Arguments<'_>) {
+    let f = if fresh {
+        File::create(log).ok()
+    } else {
+        open_log(Some(log))
+    };
+    if let Some(mut f) = f {
+        _ = f.write_all(format!("[xav] {msg}\n").as_bytes());
+    }
+}
+
+// One `enc` try per attempt, 1, 2, 4.. s apart, `--fallback-param` last; `None` gives up
+fn enc_retry(
+    pkg: &mut WorkPkg,
+    params: &str,
+    crf: Option<f32>,
+    fresh: bool,
+    ctx: &EncWorkerCtx,
+    conv_buf: &mut [u8],
+    enc: impl Fn(&mut WorkPkg, &EncWorkerCtx, &mut [u8]) -> Result<u64, Xerr>,
+) -> Option<u64> {
+    let idx = pkg.chnk.idx;
+    let log = log_path(ctx.work_dir, idx);
+    let tries = ctx.retries + 1 + usize::from(ctx.fallback.is_some());
+    for n in 1..=tries {
+        let fb = ctx.fallback.filter(|_| n == tries);
+        log_note(
+            &log,
+            fresh && n == 1,
+            format_args!(
+                "attempt {n}/{tries}{}{}",
+                crf.map(|c| format!(" at CRF {c:.2}")).unwrap_or_default(),
+                if fb.is_some() {
+                    " (fallback params)"
+                } else {
+                    ""
+                }
+            ),
+        );
+        let res = if let Some(fb) = fb {
+            let zone = pkg
+                .chnk
+                .params
+                .as_deref()
+                .map_or_else(|| fb.into(), |z| format!("{z} {fb}"));
+            pkg.chnk.params = Some(zone.into());
+            let fctx = EncWorkerCtx {
+                tmpl: ctx.fb_tmpls.first().map(|t| &**t),
+                tmpls: ctx.fb_tmpls,
+                ..*ctx
+            };
+            enc(pkg, &fctx, conv_buf)
+        } else {
+            enc(pkg, ctx, conv_buf)
+        };
+        let e = match res {
+            Ok(sz) => return Some(sz),
+            Err(e) => e,
+        };
+        log_note(&log, false, format_args!("failed: {e}"));
+        let last = n == tries || ctl::stopping();
+        emit(
+            if last { "chunk_failed" } else { "chunk_retry" },
+            format_args!(
+                ",\"chunk\":{idx},\"attempt\":{n},\"msg\":{}",
+                json_str(&format!("{e}"))
+            ),
+        );
+        if last {
+            if ctx.dump && !ctl::stopping() {
+                match dump_chunk(pkg, params, crf, ctx) {
+                    Ok(sh) => log_note(&log, false, format_args!("repro: {}", sh.display())),
+                    Err(e) => log_note(&log, false, format_args!("repro not saved: {e}")),
+                }
+            }
+            break;
+        }
+        sleep(Durat::from_secs(1 << (n - 1).min(5)));
+    }
+    None
+}
+
+// `--dump-failed`: the encoder's input as `repro/NNNN.y4m` & a `NNNN.sh` re-running it
+fn dump_chunk(
+    pkg: &WorkPkg,
+    params: &str,
+    crf: Option<f32>,
+    ctx: &EncWorkerCtx,
+) -> Result<PathBuf, Xerr> {
+    let dir = ctx.work_dir.join("repro");
+    create_dir_all(&dir)?;
+    let (idx, pipe, inf) = (pkg.chnk.idx, ctx.pipe, ctx.inf);
+    let y4m = format!("{idx:04}.y4m");
+    let out = format!("{idx:04}.{}", ctx.encoder.extension());
+
+    let mut w = BufWriter::new(File::create(dir.join(&y4m))?);
+    w.write_all(
+        y4m_header(
+            pipe.final_w,
+            pipe.final_h,
+            (inf.fps_num, inf.fps_den),
+            inf.color_range == 1,
+        )
+        .as_bytes(),
+    )?;
+    let mut frame = vec![0u8; pipe.final_w * pipe.final_h * 3];
+    for f in pkg.yuv.chunks_exact(pipe.frame_sz).take(pkg.frame_cnt) {
+        (pipe.conv)(f, &mut frame, pipe.final_w, pipe.final_h);
+        w.write_all(b"FRAME\n")?;
+        w.write_all(&frame)?;
+    }
+    w.flush()?;
+
+    let cfg = EncConfig {
+        inf,
+        template: None,
+        params,
+        crf,
+        out: Path::new(&out),
+        chnk_idx: idx,
+        width: pkg.width,
+        height: pkg.height,
+        frames: pkg.frame_cnt,
+    };
+    let zone = pkg.chnk.params.as_deref();
+    let run = match ctx.encoder {
+        SvtAv1 => cmd_line(&make_svt_app_cmd(&cfg, &y4m, zone)),
+        #[cfg(feature = "avm")]
+        Avm => cmd_line(&make_avm_app_cmd(&cfg, &y4m, zone)),
+        #[cfg(not(feature = "avm"))]
+        Avm => assume_unreachable(),
+        Vvenc | X265 | X264 => format!(
+            "ffmpeg -v error -i {y4m} -f rawvideo -pix_fmt yuv420p10le - | {}",
+            cmd_line(&make_enc_cmd(ctx.encoder, &cfg, zone))
+        ),
+    };
+    let sh = dir.join(format!("{idx:04}.sh"));
+    write(
+        &sh,
+        format!(
+            "#!/bin/sh\n# chunk {idx}: frames {}..{}\ncd \"$(dirname \"$0\")\" || exit 1\n{run}\n",
+            pkg.chnk.start, pkg.chnk.end
+        ),
+    )?;
+    Ok(sh)
+}
+
 macro_rules! make_chnk_lib {
     ($name:ident, $ctx:ident, $pkg:ident, $tmpl:expr) => {
         fn $name(
@@ -1788,7 +2559,7 @@ macro_rules! make_chnk_lib {
             out: &Path,
             conv_buf: &mut [u8],
             worker_id: usize,
-        ) -> u64 {
+        ) -> Result<u64, Xerr> {
             let cfg = EncConfig {
                 inf: $ctx.inf,
                 template: $tmpl,
@@ -1800,7 +2571,7 @@ macro_rules! make_chnk_lib {
                 height: $pkg.height,
                 frames: $pkg.frame_cnt,
             };
-            let mut sink = BufWriter::new(File::create(out).unwrap_or_else(|e| fatal(e)));
+            let mut sink = BufWriter::new(File::create(out)?);
             ($ctx.lib_enc)(
                 &mut $pkg.yuv,
                 &mut sink,
@@ -1832,7 +2603,7 @@ fn enc_chnk_sub(
     out: &Path,
     conv_buf: &mut [u8],
     worker_id: usize,
-) -> u64 {
+) -> Result<u64, Xerr> {
     let cfg = EncConfig {
         inf: ctx.inf,
         template: None,
@@ -1846,7 +2617,7 @@ fn enc_chnk_sub(
     };
 
     let cmd = make_enc_cmd(ctx.encoder, &cfg, pkg.chnk.params.as_deref());
-    let mut child = cmd.spawn().unwrap_or_else(|e| fatal(e));
+    let mut child = cmd.spawn()?;
 
     (ctx.watch_enc)(
         ctx.prog,
@@ -1859,6 +2630,7 @@ fn enc_chnk_sub(
             crf_score: None,
         },
         ctx.encoder,
+        Some(&log_path(ctx.work_dir, pkg.chnk.idx)),
     );
 
     (ctx.pipe.write_frames)(
@@ -1868,14 +2640,23 @@ fn enc_chnk_sub(
         conv_buf,
         ctx.pipe,
     );
-    pkg.yuv = Vec::new();
+    // closes stdin so the encoder sees EOF even if it stopped reading early
+    drop(child.stdin.take());
 
-    let status = child.wait().unwrap_or_else(|e| fatal(e));
+    let status = child.wait()?;
     if !status.success() {
         cold_path();
-        fatal(format_args!("encode failed: chunk {:04}", pkg.chnk.idx));
+        return Err(status
+            .code()
+            .map_or_else(
+                || "encoder was killed by a signal".into(),
+                |c| format!("encoder exited with code {c}"),
+            )
+            .into());
     }
-    metadata(out).unwrap_or(0)
+    // kept until here for a retry
+    pkg.yuv = Vec::new();
+    Ok(metadata(out).unwrap_or(0))
 }
 
 #[cfg(feature = "vship")]
@@ -1906,6 +2687,90 @@ pub fn write_chnk_log(chnk_log: &ProbeLog, work_dir: &Path) {
     {
         _ = file.write_all(line.as_bytes());
     }
+    write_also_log(chnk_log, work_dir);
+}
+
+// `also.txt`: `<id> <crf> <score>...` per probe, as `chunks.json` lines keep a fixed shape
+#[cfg(feature = "vship")]
+fn write_also_log(chnk_log: &ProbeLog, work_dir: &Path) {
+    if chnk_log.also.iter().all(Vec::is_empty) {
+        return;
+    }
+    let mut out = String::new();
+    for (&(c, ..), also) in chnk_log.probes.iter().zip(&chnk_log.also) {
+        _ = write!(out, "{} {c:.2}", chnk_log.chnk_idx);
+        for s in also {
+            _ = write!(out, " {s:.4}");
+        }
+        out.push('\n');
+    }
+    if let Ok(mut file) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(work_dir.join("also.txt"))
+    {
+        _ = file.write_all(out.as_bytes());
+    }
+}
+
+// `(chunk, CRF×100)` to its `--tq-and` scores
+#[cfg(feature = "vship")]
+type AlsoScores = BTreeMap<(usize, u64), Vec<f32>>;
+
+#[cfg(feature = "vship")]
+fn read_also_log(work_dir: &Path) -> AlsoScores {
+    let mut out = BTreeMap::new();
+    for line in read_to_str(work_dir.join("also.txt"))
+        .unwrap_or_default()
+        .lines()
+    {
+        let mut it = line.split_whitespace();
+        let (Some(id), Some(crf)) = (
+            it.next().and_then(|v| v.parse().ok()),
+            it.next().and_then(|v| v.parse::<f32>().ok()),
+        ) else {
+            continue;
+        };
+        let scores = it.filter_map(|v| v.parse().ok()).collect();
+        out.insert((id, (crf * 100.0).round() as u64), scores);
+    }
+    out
+}
+
+// Chunks whose last pick fails a `--tq-and` term, by their `also.txt` scores at its CRF
+#[cfg(feature = "vship")]
+pub fn unmet_chunks(work_dir: &Path, also: &[Also]) -> Vec<usize> {
+    if also.is_empty() {
+        return Vec::new();
+    }
+    let Ok(mut buf) = read(work_dir.join("chunks.json")) else {
+        return Vec::new();
+    };
+    buf.extend_from_slice(&[0u8; 16]);
+    let scores = read_also_log(work_dir);
+    let picks: BTreeMap<usize, f32> = parse_chunks(&buf)
+        .0
+        .into_iter()
+        .map(|l| (l.id, l.fc))
+        .collect();
+    picks
+        .into_iter()
+        .filter(|&(id, fc)| {
+            scores
+                .get(&(id, (fc * 100.0).round() as u64))
+                .is_some_and(|s| also.iter().zip(s).any(|(a, &v)| !a.ok(v)))
+        })
+        .map(|(id, _)| id)
+        .collect()
+}
+
+#[cfg(feature = "vship")]
+fn also_json(keys: &[String], scores: Option<&Vec<f32>>) -> String {
+    let mut out = String::new();
+    for (k, s) in keys.iter().zip(scores.into_iter().flatten()) {
+        _ = write!(out, ", \"{k}\": {s:.3}");
+    }
+    out
 }
 
 #[cfg(feature = "vship")]
@@ -1916,7 +2781,11 @@ fn form_tq_json(
     fps: f32,
     round_cnts: &BTreeMap<usize, usize>,
     crf_cnts: &BTreeMap<u64, usize>,
+    also: &(Vec<String>, AlsoScores, Vec<usize>),
 ) -> String {
+    let (ref keys, ref scores, ref unmet) = *also;
+    let also_of =
+        |id: usize, crf: f32| also_json(keys, scores.get(&(id, (crf * 100.0).round() as u64)));
     let tot = all_logs.len();
     let avg_probes = all_logs.iter().map(|l| l.pn).sum::<usize>() as f32 / tot as f32;
     let in_range = all_logs.iter().filter(|l| l.r <= 6).count();
@@ -1944,17 +2813,19 @@ fn form_tq_json(
             let comma = if j + 1 < sp.len() { "," } else { "" };
             _ = writeln!(
                 out,
-                "        {{ \"crf\": {c:.2}, \"score\": {s:.3}, \"kbs\": {:.0} }}{comma}",
-                calc_kbs(sz, l.f)
+                "        {{ \"crf\": {c:.2}, \"score\": {s:.3}, \"kbs\": {:.0}{} }}{comma}",
+                calc_kbs(sz, l.f),
+                also_of(l.id, c)
             );
         }
         _ = writeln!(out, "      ],");
         _ = writeln!(
             out,
-            "      \"final\": {{ \"crf\": {:.2}, \"score\": {:.3}, \"kbs\": {:.0} }}",
+            "      \"final\": {{ \"crf\": {:.2}, \"score\": {:.3}, \"kbs\": {:.0}{} }}",
             l.fc,
             l.fs,
-            calc_kbs(l.fz, l.f)
+            calc_kbs(l.fz, l.f),
+            also_of(l.id, l.fc)
         );
         let comma = if i + 1 < all_logs.len() { "," } else { "" };
         _ = writeln!(out, "    }}{comma}");
@@ -1972,6 +2843,10 @@ fn form_tq_json(
     );
     _ = writeln!(out, "  \"in_range\": {in_range},");
     _ = writeln!(out, "  \"out_range\": {},", tot - in_range);
+    if !keys.is_empty() {
+        let ids: Vec<_> = unmet.iter().map(usize::to_string).collect();
+        _ = writeln!(out, "  \"unmet\": [{}],", ids.join(", "));
+    }
     _ = writeln!(out);
     _ = writeln!(out, "  \"rounds\": {{");
     let rv: Vec<_> = round_cnts.iter().collect();
@@ -2003,8 +2878,23 @@ fn form_tq_json(
     out
 }
 
+// Chunks finished by an earlier run of this tmp dir predict from the start
+#[cfg(feature = "vship")]
+fn load_seeds(chnks: &[Chunk], args: &Args, inf: &VidInf, work_dir: &Path) -> CrfSeeds {
+    let seeds = CrfSeeds::new(&args.sc_file, inf.frames);
+    if let Ok(mut buf) = read(work_dir.join("chunks.json")) {
+        buf.extend_from_slice(&[0u8; 16]);
+        for l in parse_chunks(&buf).0 {
+            if let Some(c) = chnks.iter().find(|c| usize::from(c.idx) == l.id) {
+                seeds.add(c, l.fc);
+            }
+        }
+    }
+    seeds
+}
+
 #[cfg(feature = "vship")]
-fn write_tq_log(inp: &Path, work_dir: &Path, inf: &VidInf, metric_name: &str) {
+fn write_tq_log(inp: &Path, work_dir: &Path, inf: &VidInf, metric_name: &str, also: &[Also]) {
     let log_path = inp.with_extension("json");
     let chnks_path = work_dir.join("chunks.json");
     let fps = inf.fps_num as f32 / inf.fps_den as f32;
@@ -2013,7 +2903,14 @@ fn write_tq_log(inp: &Path, work_dir: &Path, inf: &VidInf, metric_name: &str) {
         return;
     };
     buf.extend_from_slice(&[0u8; 16]);
-    let (mut all_logs, tri) = parse_chunks(&buf);
+    let (logs, tri) = parse_chunks(&buf);
+    // `--crf-smooth` & `--redo` log a chunk again: the last line counts
+    let all_logs: Vec<TqChunkLine> = logs
+        .into_iter()
+        .map(|l| (l.id, l))
+        .collect::<BTreeMap<_, _>>()
+        .into_values()
+        .collect();
     if all_logs.is_empty() {
         return;
     }
@@ -2024,9 +2921,20 @@ fn write_tq_log(inp: &Path, work_dir: &Path, inf: &VidInf, metric_name: &str) {
         *round_cnts.entry(l.pn).or_insert(0) += 1;
         *crf_cnts.entry((l.fc * 100.0).round() as u64).or_insert(0) += 1;
     }
-    all_logs.sort_by_key(|l| l.id);
-
-    let out = form_tq_json(&all_logs, &tri, metric_name, fps, &round_cnts, &crf_cnts);
+    let also = (
+        also.iter().map(Also::key).collect(),
+        read_also_log(work_dir),
+        unmet_chunks(work_dir, also),
+    );
+    let out = form_tq_json(
+        &all_logs,
+        &tri,
+        metric_name,
+        fps,
+        &round_cnts,
+        &crf_cnts,
+        &also,
+    );
     if let Ok(mut file) = OpenOptions::new()
         .create(true)
         .write(true)
@@ -2069,21 +2977,40 @@ fn drain_go(
     unsafe { xav_svt_drain_go(worker_id, handle, d, v, tr.enced(), wr_dyn) }
 }
 
-fn svt_handle(conf: *mut EbSvtAv1EncConfiguration) -> *mut EbComponentType {
+fn svt_handle(conf: *mut EbSvtAv1EncConfiguration) -> Result<*mut EbComponentType, Xerr> {
     let mut handle: *mut EbComponentType = null_mut();
     let ret = unsafe { svt_av1_enc_init_handle(&raw mut handle, conf) };
     if ret != EB_ERROR_NONE {
         cold_path();
-        fatal(format_args!("svt_av1_enc_init_handle failed: {ret}"));
+        return Err(format!("svt_av1_enc_init_handle failed: {ret}").into());
+    }
+    Ok(handle)
+}
+
+// Applies the filled-in config; the handle is released again on failure
+fn svt_start(
+    handle: *mut EbComponentType,
+    conf: *mut EbSvtAv1EncConfiguration,
+) -> Result<*mut EbComponentType, Xerr> {
+    let ret = unsafe { svt_av1_enc_set_parameter(handle, conf) };
+    let (ret, what) = if ret == EB_ERROR_NONE {
+        (unsafe { svt_av1_enc_init(handle) }, "svt_av1_enc_init")
+    } else {
+        (ret, "svt_av1_enc_set_parameter")
+    };
+    if ret != EB_ERROR_NONE {
+        cold_path();
+        unsafe { svt_av1_enc_deinit_handle(handle) };
+        return Err(format!("{what} failed: {ret}").into());
     }
-    handle
+    Ok(handle)
 }
 
 fn svt_defaults() -> &'static [u8; SVT_CONF_SIZE] {
     static DEFAULTS: OnceLock<[u8; SVT_CONF_SIZE]> = OnceLock::new();
     DEFAULTS.get_or_init(|| {
         let mut conf = unsafe { zeroed::<EbSvtAv1EncConfiguration>() };
-        let handle = svt_handle(&raw mut conf);
+        let handle = svt_handle(&raw mut conf).unwrap_or_else(|e| fatal(e));
         unsafe { svt_av1_enc_deinit_handle(handle) };
         unsafe { (&raw const conf).cast::<[u8; SVT_CONF_SIZE]>().read() }
     })
@@ -2149,46 +3076,26 @@ fn build_svt_templates(
     v
 }
 
-fn init_svt(cfg: &EncConfig) -> *mut EbComponentType {
+fn init_svt(cfg: &EncConfig) -> Result<*mut EbComponentType, Xerr> {
     let mut conf = MaybeUninit::<EbSvtAv1EncConfiguration>::uninit();
-    let handle = svt_handle(conf.as_mut_ptr());
+    let handle = svt_handle(conf.as_mut_ptr())?;
     unsafe {
         let t = cfg.template.unwrap_unchecked();
         copy_nonoverlapping(t.as_ptr(), conf.as_mut_ptr().cast::<u8>(), SVT_CONF_SIZE);
     }
-    let ret = unsafe { svt_av1_enc_set_parameter(handle, conf.as_mut_ptr()) };
-    if ret != EB_ERROR_NONE {
-        cold_path();
-        fatal(format_args!("svt_av1_enc_set_parameter failed: {ret}"));
-    }
-    let ret = unsafe { svt_av1_enc_init(handle) };
-    if ret != EB_ERROR_NONE {
-        cold_path();
-        fatal(format_args!("svt_av1_enc_init failed: {ret}"));
-    }
-    handle
+    svt_start(handle, conf.as_mut_ptr())
 }
 
 #[cfg(feature = "vship")]
-fn init_svt_crf(cfg: &EncConfig) -> *mut EbComponentType {
+fn init_svt_crf(cfg: &EncConfig) -> Result<*mut EbComponentType, Xerr> {
     let mut conf = MaybeUninit::<EbSvtAv1EncConfiguration>::uninit();
-    let handle = svt_handle(conf.as_mut_ptr());
+    let handle = svt_handle(conf.as_mut_ptr())?;
     unsafe {
         let t = cfg.template.unwrap_unchecked();
         copy_nonoverlapping(t.as_ptr(), conf.as_mut_ptr().cast::<u8>(), SVT_CONF_SIZE);
         set_svt_crf(conf.as_mut_ptr(), cfg.crf.unwrap_unchecked());
     }
-    let ret = unsafe { svt_av1_enc_set_parameter(handle, conf.as_mut_ptr()) };
-    if ret != EB_ERROR_NONE {
-        cold_path();
-        fatal(format_args!("svt_av1_enc_set_parameter failed: {ret}"));
-    }
-    let ret = unsafe { svt_av1_enc_init(handle) };
-    if ret != EB_ERROR_NONE {
-        cold_path();
-        fatal(format_args!("svt_av1_enc_init failed: {ret}"));
-    }
-    handle
+    svt_start(handle, conf.as_mut_ptr())
 }
 
 macro_rules! make_send_svt {
@@ -2200,13 +3107,13 @@ macro_rules! make_send_svt {
             ctx: &EncWorkerCtx,
             conv_buf: &mut [u8],
             track: &EncTrack,
-        ) -> (*mut EbComponentType, Tracker) {
+        ) -> Result<(*mut EbComponentType, Tracker), Xerr> {
             let &EncTrack {
                 worker_id,
                 track_frames,
                 crf_score,
             } = track;
-            let handle = $init(cfg);
+            let handle = $init(cfg)?;
 
             let w = cfg.width as usize;
             let h = cfg.height as usize;
@@ -2244,6 +3151,7 @@ macro_rules! make_send_svt {
             let frame_sz = ctx.pipe.frame_sz;
             let mut src = yuv.as_ptr();
             for i in 0..cfg.frames {
+                ctl::hold();
                 ($conv)(unsafe { from_raw_parts(src, frame_sz) }, conv_buf, fw, fh);
                 src = unsafe { src.add(frame_sz) };
 
@@ -2252,14 +3160,15 @@ macro_rules! make_send_svt {
                 let ret = unsafe { svt_av1_enc_send_picture(handle, &raw mut in_hdr) };
                 if ret != EB_ERROR_NONE {
                     cold_path();
-                    fatal(format_args!(
-                        "svt_av1_enc_send_picture failed at frame {i}: {ret}"
-                    ));
+                    finish_svt(handle, worker_id, &tracker);
+                    return Err(
+                        format!("svt_av1_enc_send_picture failed at frame {i}: {ret}").into(),
+                    );
                 }
                 drain_poke(st);
             }
 
-            (handle, tracker)
+            Ok((handle, tracker))
         }
     };
 }
@@ -2334,10 +3243,10 @@ macro_rules! make_enc_svt {
             ctx: &EncWorkerCtx,
             conv_buf: &mut [u8],
             track: &EncTrack,
-        ) -> u64 {
-            let (handle, tracker) = $send(out, yuv, cfg, ctx, conv_buf, track);
+        ) -> Result<u64, Xerr> {
+            let (handle, tracker) = $send(out, yuv, cfg, ctx, conv_buf, track)?;
             *yuv = Vec::new();
-            finish_svt(handle, track.worker_id, &tracker)
+            Ok(finish_svt(handle, track.worker_id, &tracker))
         }
     };
 }
@@ -2352,9 +3261,9 @@ macro_rules! make_enc_svt_tq {
             ctx: &EncWorkerCtx,
             conv_buf: &mut [u8],
             track: &EncTrack,
-        ) -> u64 {
-            let (handle, tracker) = $send(out, yuv.as_slice(), cfg, ctx, conv_buf, track);
-            finish_svt(handle, track.worker_id, &tracker)
+        ) -> Result<u64, Xerr> {
+            let (handle, tracker) = $send(out, yuv.as_slice(), cfg, ctx, conv_buf, track)?;
+            Ok(finish_svt(handle, track.worker_id, &tracker))
         }
     };
 }
@@ -2382,13 +3291,13 @@ fn enc_svt_direct(
     ctx: &EncWorkerCtx,
     _conv_buf: &mut [u8],
     track: &EncTrack,
-) -> u64 {
+) -> Result<u64, Xerr> {
     let &EncTrack {
         worker_id,
         track_frames,
         crf_score,
     } = track;
-    let handle = init_svt(cfg);
+    let handle = init_svt(cfg)?;
 
     let w = cfg.width as usize;
     let h = cfg.height as usize;
@@ -2425,6 +3334,7 @@ fn enc_svt_direct(
     let frame_sz = ctx.pipe.frame_sz;
     let mut src = yuv.as_ptr().cast_mut();
     for i in 0..cfg.frames {
+        ctl::hold();
         unsafe {
             (*io_ptr).luma = src;
             (*io_ptr).cb = src.add(y_sz);
@@ -2437,15 +3347,14 @@ fn enc_svt_direct(
         let ret = unsafe { svt_av1_enc_send_picture(handle, &raw mut in_hdr) };
         if ret != EB_ERROR_NONE {
             cold_path();
-            fatal(format_args!(
-                "svt_av1_enc_send_picture failed at frame {i}: {ret}"
-            ));
+            finish_svt(handle, worker_id, &tracker);
+            return Err(format!("svt_av1_enc_send_picture failed at frame {i}: {ret}").into());
         }
         drain_poke(st);
     }
     *yuv = Vec::new();
 
-    finish_svt(handle, worker_id, &tracker)
+    Ok(finish_svt(handle, worker_id, &tracker))
 }
 
 fn finish_svt(handle: *mut EbComponentType, worker_id: usize, tracker: &Tracker) -> u64 {
@@ -2579,7 +3488,7 @@ macro_rules! make_send_avm {
             ctx: &EncWorkerCtx,
             conv_buf: &mut [u8],
             track: &EncTrack,
-        ) -> (Tracker, usize, u64) {
+        ) -> Result<(Tracker, usize, u64), Xerr> {
             let &EncTrack {
                 worker_id,
                 track_frames,
@@ -2615,19 +3524,21 @@ macro_rules! make_send_avm {
             let mut src = yuv.as_ptr();
 
             for i in 0..cfg.frames {
+                ctl::hold();
                 ($conv)(unsafe { from_raw_parts(src, frame_sz) }, conv_buf, fw, fh);
                 src = unsafe { src.add(frame_sz) };
 
                 let ret = unsafe { avm_codec_encode(ec, img_ptr, i as i64, 1, 0) };
                 if ret != AVM_CODEC_OK {
                     cold_path();
-                    fatal(format_args!("avm_codec_encode failed at frame {i}: {ret}"));
+                    finish_avm(ec, out, &tracker, &mut done);
+                    return Err(format!("avm_codec_encode failed at frame {i}: {ret}").into());
                 }
 
                 sz += drain_avm_packets(ec, out, &tracker, &mut done);
             }
 
-            (tracker, done, sz)
+            Ok((tracker, done, sz))
         }
     };
 }
@@ -2677,12 +3588,12 @@ macro_rules! make_enc_avm {
             ctx: &EncWorkerCtx,
             conv_buf: &mut [u8],
             track: &EncTrack,
-        ) -> u64 {
+        ) -> Result<u64, Xerr> {
             let mut ec = MaybeUninit::<AvmCodecCtx>::uninit();
             let ecp = ec.as_mut_ptr();
-            let (tracker, mut done, sz) = $send(ecp, out, yuv, cfg, ctx, conv_buf, track);
+            let (tracker, mut done, sz) = $send(ecp, out, yuv, cfg, ctx, conv_buf, track)?;
             *yuv = Vec::new();
-            sz + finish_avm(ecp, out, &tracker, &mut done)
+            Ok(sz + finish_avm(ecp, out, &tracker, &mut done))
         }
     };
 }
@@ -2708,7 +3619,7 @@ fn enc_avm_direct(
     ctx: &EncWorkerCtx,
     _conv_buf: &mut [u8],
     track: &EncTrack,
-) -> u64 {
+) -> Result<u64, Xerr> {
     let &EncTrack {
         worker_id,
         track_frames,
@@ -2740,6 +3651,7 @@ fn enc_avm_direct(
     let mut src = yuv.as_ptr().cast_mut();
 
     for i in 0..cfg.frames {
+        ctl::hold();
         unsafe {
             (*img_ptr).planes = [src, src.add(y_sz), src.add(y_sz + uv_sz)];
             src = src.add(frame_sz);
@@ -2748,14 +3660,15 @@ fn enc_avm_direct(
         let ret = unsafe { avm_codec_encode(ecp, img_ptr, i as i64, 1, 0) };
         if ret != AVM_CODEC_OK {
             cold_path();
-            fatal(format_args!("avm_codec_encode failed at frame {i}: {ret}"));
+            finish_avm(ecp, out, &tracker, &mut done);
+            return Err(format!("avm_codec_encode failed at frame {i}: {ret}").into());
         }
 
         sz += drain_avm_packets(ecp, out, &tracker, &mut done);
     }
     *yuv = Vec::new();
 
-    sz + finish_avm(ecp, out, &tracker, &mut done)
+    Ok(sz + finish_avm(ecp, out, &tracker, &mut done))
 }
 
 #[cfg(feature = "avm")]
@@ -2787,6 +3700,27 @@ fn finish_avm(
 pub mod test_access {
     use super::*;
 
+    #[cfg(feature = "vship")]
+    pub fn with_thin<R>(
+        pkg: &mut WorkPkg,
+        frame_sz: usize,
+        step: usize,
+        f: impl FnOnce(&mut WorkPkg) -> R,
+    ) -> R {
+        super::with_thin(pkg, frame_sz, step, f)
+    }
+
+    #[cfg(feature = "vship")]
+    pub fn tq_coord(
+        coord: &SeqRing,
+        enc: &SeqRing,
+        tot: usize,
+        permits: &Semaphore,
+        sent: &AtomicUsize,
+    ) {
+        super::tq_coord(coord, enc, tot, permits, sent);
+    }
+
     pub fn resolve_svt_enc_addr(
         strat: DecStrat,
         is_nv12: bool,
@@ -2818,6 +3752,64 @@ pub mod test_access {
         (enc_svt_unpack_drop_rem as LibEncFn) as usize
     }
 
+    // CRFs searched when every probe scores `100 - crf` but fails its `--tq-and` term
+    #[cfg(feature = "vship")]
+    pub fn unmet_search(qp_min: f32, qp_max: f32) -> Vec<f32> {
+        let ctx = TQCtx {
+            target: 80.0,
+            tolerance: 1.0,
+            qp_min,
+            qp_max,
+            use_butter: false,
+            use_cvvdp: false,
+            also: Arc::from([Also {
+                metric: Metric::Butter,
+                agg: Agg::Max,
+                ge: false,
+                val: 0.5,
+            }]),
+            sample: 1,
+            thin: false,
+            wt: Arc::default(),
+        };
+        let mut tq = TQState {
+            probes: Vec::new(),
+            probe_szs: Vec::new(),
+            search_min: qp_min,
+            search_max: qp_max,
+            round: 0,
+            target: ctx.target,
+            last_crf: 0.0,
+            final_enc: false,
+            best_probe: Vec::new(),
+            best_diff: f32::INFINITY,
+            known: Vec::new(),
+            reused: Vec::new(),
+            seed: None,
+            narrowed: false,
+            sample: 1,
+            worker: 0,
+            started: Mono::now(),
+        };
+        let mut crfs = Vec::new();
+        while crfs.len() < 32
+            && let Some(c) = tq_search_crf(&mut tq, &ctx, Encoder::SvtAv1)
+        {
+            crfs.push(c);
+            let p = Probe {
+                crf: c,
+                score: 100.0 - c,
+                also: vec![2.0],
+            };
+            let done = ctx.settled(&mut tq, &p);
+            tq.probes.push(p);
+            if done {
+                break;
+            }
+        }
+        crfs
+    }
+
     #[cfg(feature = "vship")]
     pub fn resolve_metric_loop_addr(
         dav1d: bool,
@@ -2833,6 +3825,10 @@ pub mod test_access {
             qp_max: 0.0,
             use_butter: false,
             use_cvvdp: cvvdp,
+            also: Arc::default(),
+            sample: 1,
+            thin: false,
+            wt: Arc::default(),
         };
         resolve_metric_loop(dav1d, use_alt, &tq, inf, pipe) as usize
     }
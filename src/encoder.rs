@@ -6,6 +6,8 @@ use alloc::{
 };
 use core::mem::size_of;
 
+#[cfg(feature = "avm")]
+use crate::avm::avm_app_args;
 #[cfg(all(target_os = "linux", not(test)))]
 use crate::fmath::FloatExt as _;
 #[cfg(any(feature = "vship", test))]
@@ -702,6 +704,66 @@ pub fn set_svt_base(
     parse_svt_params(conf, params);
 }
 
+// `SvtAv1EncApp` line matching `set_svt_base`, used to reproduce a failed chunk outside xav
+pub fn make_svt_app_cmd(cfg: &EncConfig, input: &str, zone: Option<&str>) -> Command {
+    let inf = cfg.inf;
+    let mut cmd = Command::new("SvtAv1EncApp");
+    cmd.args(["-i", input]);
+    cmd.arg("-b").arg(cfg.out);
+    cmd.args([
+        "--keyint",
+        "-1",
+        "--scd",
+        "0",
+        "--scm",
+        "0",
+        "--input-depth",
+        "10",
+        "--profile",
+        "0",
+        "--rc",
+        "0",
+        "--fps-num",
+        &inf.fps_num.to_string(),
+        "--fps-denom",
+        &inf.fps_den.to_string(),
+        "--color-primaries",
+        &inf.color_primaries.to_string(),
+        "--transfer-characteristics",
+        &inf.transfer_characteristics.to_string(),
+        "--matrix-coefficients",
+        &inf.matrix_coefficients.to_string(),
+        "--color-range",
+        &inf.color_range.to_string(),
+        "--chroma-sample-position",
+        &inf.chroma_sample_position.to_string(),
+    ]);
+    if let Some(ref md) = inf.mastering_display {
+        cmd.args(["--mastering-display", md]);
+    }
+    if let Some(ref cl) = inf.content_light {
+        cmd.args(["--content-light", cl]);
+    }
+    cmd.args(cfg.params.split_whitespace());
+    if let Some(z) = zone {
+        cmd.args(z.split_whitespace());
+    }
+    cmd
+}
+
+// The standalone AV2 encoder on a `--dump-failed` Y4M, with the params given to the library
+#[cfg(feature = "avm")]
+pub fn make_avm_app_cmd(cfg: &EncConfig, input: &str, zone: Option<&str>) -> Command {
+    let mut cmd = Command::new("avmenc");
+    cmd.args(avm_app_args(cfg.inf, cfg.frames));
+    cmd.args(cfg.params.split_whitespace());
+    if let Some(z) = zone {
+        cmd.args(z.split_whitespace());
+    }
+    cmd.arg("-o").arg(cfg.out).arg(input);
+    cmd
+}
+
 #[cfg(any(feature = "vship", test))]
 pub fn set_svt_crf(conf: *mut EbSvtAv1EncConfiguration, crf: f32) {
     let c = (f64::from(crf) * 100.0).round() / 100.0;
@@ -7,16 +7,24 @@ use core::{
     fmt::{self, Arguments, Display, Formatter},
     num::{ParseFloatError, ParseIntError},
     sync::atomic::{AtomicBool, Ordering::Relaxed},
+    time::Duration,
 };
 
 #[cfg(target_os = "linux")]
 use crate::sys::{exit_group, sigaction};
 use crate::{
     error::Xerr::Msg,
+    events::{error, on},
     io::{Error, Write as _, print_fmt, stderr, stdout},
+    thread::sleep,
 };
 
 pub static IN_ALT_SCREEN: AtomicBool = AtomicBool::new(false);
+// Set by the first SIGINT/SIGTERM/SIGHUP; the interrupt thread owns the exit from then on
+pub static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+// `--queue`: the interrupted input winds down & returns instead, so the queue can end with
+// its summary; nothing parks waiting for an exit
+pub static UNWIND: AtomicBool = AtomicBool::new(false);
 
 #[derive(Debug)]
 pub enum Xerr {
@@ -102,32 +110,46 @@ pub fn exit(code: i32) -> ! {
     unsafe { _exit(code) }
 }
 
+pub const SIGHUP: i32 = 1;
 pub const SIGINT: i32 = 2;
 pub const SIGSEGV: i32 = 11;
+pub const SIGPIPE: i32 = 13;
+pub const SIGTERM: i32 = 15;
+pub const SIG_DFL: usize = 0;
+pub const SIG_IGN: usize = 1;
+
+// Threads that fail because an interrupt killed their child wait here to be exited
+pub fn park_if_interrupted() {
+    while INTERRUPTED.load(Relaxed) && !UNWIND.load(Relaxed) {
+        sleep(Duration::from_secs(1));
+    }
+}
 
 #[cfg(target_os = "linux")]
-pub fn signal(sig: i32, handler: usize) {
-    sigaction(sig, handler);
+pub fn signal(sig: i32, handler: usize) -> usize {
+    sigaction(sig, handler)
 }
 
 #[cfg(not(target_os = "linux"))]
-pub fn signal(sig: i32, handler: usize) {
+pub fn signal(sig: i32, handler: usize) -> usize {
     unsafe extern "C" {
         #[link_name = "signal"]
         fn c_signal(sig: i32, handler: usize) -> usize;
     }
-    unsafe {
-        c_signal(sig, handler);
-    }
+    unsafe { c_signal(sig, handler) }
 }
 
 #[cold]
 #[inline(never)]
 pub fn fatal<E: Display>(e: E) -> ! {
+    park_if_interrupted();
     if IN_ALT_SCREEN.load(Relaxed) {
         print!("\x1b[?25h\x1b[?1049l");
         _ = stdout().flush();
     }
+    if on() {
+        error(&format!("{e}"));
+    }
     _ = writeln!(stderr(), "{e}");
     exit(1)
 }
@@ -139,6 +161,9 @@ pub fn eprint(args: Arguments<'_>) {
         print!("\x1b[?1049l");
         _ = stdout().flush();
     }
+    if on() {
+        error(&format!("{args}"));
+    }
     _ = writeln!(stderr(), "{args}");
 }
 
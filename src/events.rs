@@ -0,0 +1,117 @@
+#[cfg(target_os = "linux")]
+use alloc::string::String;
+use core::{
+    cell::Cell,
+    fmt::{self, Arguments, Display, Formatter, Write as _},
+    sync::atomic::{
+        AtomicBool, AtomicI32,
+        Ordering::{Acquire, Relaxed, Release},
+    },
+};
+
+use crate::{
+    clk::Mono,
+    io::{Fd, IsTerminal as _, Write as _, stdout},
+    sync::{Mutex, OnceLock},
+    util::json_str,
+};
+
+// NDJSON event stream for `--progress json` / `--progress-fd N`
+static FD: AtomicI32 = AtomicI32::new(-1);
+static DRAW: AtomicBool = AtomicBool::new(true);
+static START: OnceLock<Mono> = OnceLock::new();
+static LINE: Mutex<String> = Mutex::new(String::new());
+static PHASE: Mutex<&str> = Mutex::new("init");
+// A `--queue` input muxes behind the next one's TUI; its thread & those it spawns stay off screen
+#[thread_local]
+static MUTED: Cell<bool> = Cell::new(false);
+
+// The TUI only draws on a terminal that is not carrying the event stream
+pub fn init(fd: Option<i32>) {
+    START.get_or_init(Mono::now);
+    if let Some(fd) = fd {
+        FD.store(fd, Release);
+    }
+    DRAW.store(fd != Some(1) && stdout().is_terminal(), Release);
+}
+
+#[inline]
+pub fn on() -> bool {
+    FD.load(Relaxed) >= 0
+}
+
+#[inline]
+pub fn draw() -> bool {
+    DRAW.load(Relaxed) && !MUTED.get()
+}
+
+pub fn mute() {
+    MUTED.set(true);
+}
+
+#[inline]
+pub fn muted() -> bool {
+    MUTED.get()
+}
+
+pub fn owns_stdout() -> bool {
+    FD.load(Relaxed) == 1
+}
+
+// `body` is spliced in after `"ev"` and `"t"`, so it starts with a comma
+pub fn emit(ev: &str, body: Arguments<'_>) {
+    let fd = FD.load(Acquire);
+    if fd < 0 {
+        return;
+    }
+    let t = START.get().map_or(0.0, |s| s.elapsed().as_secs_f64());
+    let mut line = LINE.lock();
+    line.clear();
+    _ = write!(line, "{{\"ev\":\"{ev}\",\"t\":{t:.3}");
+    _ = line.write_fmt(body);
+    line.push_str("}\n");
+    _ = Fd(fd).write_all(line.as_bytes());
+    drop(line);
+}
+
+// A muted finisher leaves the phase to the input that is encoding
+pub fn phase(name: &'static str) {
+    if MUTED.get() {
+        return;
+    }
+    *PHASE.lock() = name;
+    emit("phase", format_args!(",\"phase\":\"{name}\""));
+}
+
+pub fn cur_phase() -> &'static str {
+    *PHASE.lock()
+}
+
+#[cold]
+#[inline(never)]
+pub fn error(msg: &str) {
+    emit("error", format_args!(",\"msg\":{}", json_str(msg)));
+}
+
+pub fn chunk_done(idx: u16, frames: usize, bytes: u64, cs: Option<(f32, Option<f32>)>) {
+    emit(
+        "chunk_done",
+        format_args!(
+            ",\"chunk\":{idx},\"frames\":{frames},\"bytes\":{bytes}{}",
+            CrfScore(cs)
+        ),
+    );
+}
+
+// Optional CRF/score pair as trailing JSON fields
+pub struct CrfScore(pub Option<(f32, Option<f32>)>);
+
+impl Display for CrfScore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some((c, Some(s))) => write!(f, ",\"crf\":{c:.2},\"score\":{s:.4}"),
+            Some((c, None)) => write!(f, ",\"crf\":{c:.2}"),
+            None => Ok(()),
+        }
+    }
+}
@@ -39,6 +39,7 @@ pub const AVMEDIA_TYPE_VIDEO: c_int = 0;
 pub const AVMEDIA_TYPE_SUBTITLE: c_int = 3;
 pub const AV_NOPTS_VALUE: i64 = i64::MIN;
 pub const AVSEEK_FLAG_BACKWARD: c_int = 1;
+const AV_DISPOSITION_COMMENT: c_int = 8;
 const AV_DICT_IGNORE_SUFFIX: c_int = 2;
 const AV_FRAME_DATA_MASTERING_DISPLAY_METADATA: c_int = 11;
 const AV_FRAME_DATA_CONTENT_LIGHT_LEVEL: c_int = 14;
@@ -114,7 +115,7 @@ pub struct AVStream {
     pub start_time: i64,
     pub duration: i64,
     nb_frames: i64,
-    _disposition: c_int,
+    pub disposition: c_int,
     pub discard: c_int,
     pub sample_aspect_ratio: AVRational,
     pub metadata: *mut c_void,
@@ -235,6 +236,11 @@ pub struct AVPacket {
     pub duration: i64,
 }
 
+#[repr(C)]
+struct AVCodecDescriptor {
+    id: c_int,
+}
+
 #[repr(C)]
 pub struct AVChapter {
     pub id: i64,
@@ -255,6 +261,7 @@ unsafe extern "C" {
     ) -> c_int;
     pub fn avformat_find_stream_info(ic: *mut AVFormatContext, options: *mut *mut c_void) -> c_int;
     pub fn avcodec_get_name(id: c_int) -> *const c_char;
+    fn avcodec_descriptor_get_by_name(name: *const c_char) -> *const AVCodecDescriptor;
     pub fn avformat_close_input(ps: *mut *mut AVFormatContext);
     pub fn av_opt_set_int(
         obj: *mut c_void,
@@ -664,10 +671,30 @@ impl VidDecoder {
         }
     }
 
+    // Back to the first frame without decoding it: `seek_near` always consumes the frame it
+    // lands on, so it can not position before frame 0
+    fn rewind(&mut self) {
+        unsafe {
+            av_seek_frame(
+                self.fmt_ctx,
+                self.stream_idx,
+                self.start_pts,
+                AVSEEK_FLAG_BACKWARD,
+            );
+            avcodec_flush_buffers(self.codec_ctx);
+        }
+        self.eof = false;
+        self.next_frame = 0;
+    }
+
     pub fn skip_to(&mut self, frame_idx: usize) {
         if frame_idx == self.next_frame {
             return;
         }
+        if frame_idx == 0 {
+            self.rewind();
+            return;
+        }
         if frame_idx < self.next_frame || frame_idx - self.next_frame > 150 {
             self.seek_near(frame_idx);
             if self.next_frame > frame_idx {
@@ -1052,6 +1079,12 @@ pub unsafe fn dict_get(metadata: *const c_void, key: *const c_char) -> Option<St
         .map(ToOwned::to_owned)
 }
 
+// Descriptors exist for every codec id, even ones whose decoder is not built in
+pub fn codec_id(name: &CStr) -> c_int {
+    let d = unsafe { avcodec_descriptor_get_by_name(name.as_ptr()) };
+    if d.is_null() { 0 } else { unsafe { (*d).id } }
+}
+
 pub unsafe fn stream_lang(metadata: *const c_void) -> Option<Cow<'static, str>> {
     let entry = unsafe { av_dict_get(metadata, c"language".as_ptr(), null(), 0) };
     if entry.is_null() {
@@ -1076,7 +1109,7 @@ pub unsafe fn is_matroska(fmt_ctx: *const AVFormatContext) -> bool {
     }
 }
 
-type AuStreamMeta = (u8, u8, Option<Cow<'static, str>>);
+type AuStreamMeta = (u8, u8, Option<Cow<'static, str>>, Option<String>, bool);
 
 pub fn get_au_streams(path: &Path) -> Result<Vec<AuStreamMeta>, Xerr> {
     unsafe {
@@ -1110,7 +1143,13 @@ pub fn get_au_streams(path: &Path) -> Result<Vec<AuStreamMeta>, Xerr> {
                 .find(|t| t.0 == i as u64)
                 .map(|t| Cow::Owned(t.1.to_owned()))
                 .or_else(|| stream_lang(stream.metadata));
-            result.push((stream.index as u8, channels, lang));
+            result.push((
+                stream.index as u8,
+                channels,
+                lang,
+                dict_get(stream.metadata, c"title".as_ptr()),
+                stream.disposition & AV_DISPOSITION_COMMENT != 0,
+            ));
         }
 
         avformat_close_input(addr_of_mut!(fmt_ctx));
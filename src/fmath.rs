@@ -3,7 +3,7 @@ use core::arch::x86_64::{
     _mm_fmadd_ss, _mm_round_sd, _mm_round_ss, _mm_set_sd, _mm_set_ss,
 };
 #[cfg(feature = "vship")]
-use core::arch::x86_64::{_mm_ceil_sd, _mm_ceil_ss};
+use core::arch::x86_64::{_mm_ceil_sd, _mm_ceil_ss, _mm_sqrt_sd, _mm_sqrt_ss};
 
 const F64_SIGN: u64 = 1 << 63;
 const F32_SIGN: u32 = 1 << 31;
@@ -19,6 +19,8 @@ pub trait FloatExt {
     fn mul_add(self, a: Self, b: Self) -> Self;
     #[cfg(feature = "vship")]
     fn ceil(self) -> Self;
+    #[cfg(feature = "vship")]
+    fn sqrt(self) -> Self;
 }
 
 pub trait Powf {
@@ -51,6 +53,13 @@ impl FloatExt for f64 {
         unsafe { _mm_cvtsd_f64(_mm_ceil_sd(x, x)) }
     }
 
+    #[cfg(feature = "vship")]
+    #[inline]
+    fn sqrt(self) -> Self {
+        let x = unsafe { _mm_set_sd(self) };
+        unsafe { _mm_cvtsd_f64(_mm_sqrt_sd(x, x)) }
+    }
+
     #[inline]
     fn round(self) -> Self {
         let half = Self::from_bits((0.5f64).to_bits() | (self.to_bits() & F64_SIGN));
@@ -72,6 +81,13 @@ impl FloatExt for f32 {
         unsafe { _mm_cvtss_f32(_mm_ceil_ss(x, x)) }
     }
 
+    #[cfg(feature = "vship")]
+    #[inline]
+    fn sqrt(self) -> Self {
+        let x = unsafe { _mm_set_ss(self) };
+        unsafe { _mm_cvtss_f32(_mm_sqrt_ss(x)) }
+    }
+
     #[inline]
     fn round(self) -> Self {
         let half = Self::from_bits((0.5f32).to_bits() | (self.to_bits() & F32_SIGN));
@@ -12,8 +12,8 @@ use crate::{
     path::{Path, PathBuf},
     sys::{
         AT_REMOVEDIR, O_APPEND, O_CLOEXEC, O_CREAT, O_DIRECTORY, O_RDONLY, O_RDWR, O_TRUNC,
-        O_WRONLY, Stat, close, fstat, ftruncate, getdents64, mkdirat, newfstatat, openat,
-        read as sys_read, readlinkat, unlinkat, write as sys_write,
+        O_WRONLY, Stat, close, fstat, ftruncate, getdents64, mkdirat, newfstatat, openat, pread64,
+        read as sys_read, readlinkat, renameat, unlinkat, write as sys_write,
     },
 };
 
@@ -160,7 +160,6 @@ impl OpenOptions {
         self
     }
 
-    #[cfg(feature = "vship")]
     #[inline]
     pub const fn append(&mut self, v: bool) -> &mut Self {
         if v {
@@ -353,6 +352,37 @@ pub fn metadata<P: AsRef<Path>>(path: P) -> Result<u64> {
     Ok(unsafe { (*st.as_ptr()).st_size } as u64)
 }
 
+// Modification time in whole seconds since the epoch
+#[cfg(target_os = "linux")]
+pub fn mtime<P: AsRef<Path>>(path: P) -> Result<u64> {
+    let mut st = MaybeUninit::<Stat>::uninit();
+    let r = path.as_ref().with_cstr(|p| newfstatat(p, st.as_mut_ptr()));
+    if r < 0 {
+        return err(i64::from(r));
+    }
+    Ok(unsafe { (*st.as_ptr()).st_mtime } as u64)
+}
+
+#[cfg(target_os = "linux")]
+pub fn read_at(f: &File, buf: &mut [u8], off: u64) -> Result<usize> {
+    let n = pread64(f.fd, buf.as_mut_ptr(), buf.len(), off);
+    if n < 0 {
+        return err(n as i64);
+    }
+    Ok(n as usize)
+}
+
+#[cfg(target_os = "linux")]
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
+    let r = from
+        .as_ref()
+        .with_cstr(|a| to.as_ref().with_cstr(|b| renameat(a, b)));
+    if r < 0 {
+        return err(i64::from(r));
+    }
+    Ok(())
+}
+
 #[cfg(target_os = "linux")]
 fn mkdir_one(prefix: &[u8]) -> Result<()> {
     let r = Path::from_bytes(prefix).with_cstr(|p| mkdirat(p, 0o755));
@@ -440,7 +470,7 @@ use std::fs::{
     copy as std_copy, create_dir_all as std_create_dir_all, metadata as std_metadata,
     read as std_read, read_dir as std_read_dir, read_link as std_read_link,
     read_to_string as std_read_to_string, remove_dir_all as std_remove_dir_all,
-    remove_file as std_remove_file, write as std_write,
+    remove_file as std_remove_file, rename as std_rename, write as std_write,
 };
 #[cfg(not(target_os = "linux"))]
 use std::path::{Path, PathBuf};
@@ -487,6 +517,28 @@ pub fn metadata<P: AsRef<Path>>(path: P) -> Result<u64> {
     std_metadata(path).map(|m| m.len())
 }
 
+#[cfg(not(target_os = "linux"))]
+pub fn mtime<P: AsRef<Path>>(path: P) -> Result<u64> {
+    let t = std_metadata(path)?.modified()?;
+    Ok(t.duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs()))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn read_at(f: &File, buf: &mut [u8], off: u64) -> Result<usize> {
+    std::os::unix::fs::FileExt::read_at(f, buf, off)
+}
+
+#[cfg(windows)]
+pub fn read_at(f: &File, buf: &mut [u8], off: u64) -> Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(f, buf, off)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(from: P, to: Q) -> Result<()> {
+    std_rename(from, to)
+}
+
 #[cfg(not(target_os = "linux"))]
 pub fn create_dir_all<P: AsRef<Path>>(path: P) -> Result<()> {
     std_create_dir_all(path)
@@ -389,6 +389,30 @@ impl IsTerminal for Stdin {
     }
 }
 
+#[cfg(target_os = "linux")]
+impl IsTerminal for Stdout {
+    #[inline]
+    fn is_terminal(&self) -> bool {
+        isatty(1)
+    }
+}
+
+// Raw descriptor handed over on the command line (e.g. `--progress-fd`)
+pub struct Fd(pub i32);
+
+#[cfg(target_os = "linux")]
+impl Write for Fd {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        write_fd(self.0, buf)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
 #[cfg(target_os = "linux")]
 struct Line {
     buf: [MaybeUninit<u8>; 512],
@@ -508,6 +532,29 @@ impl IsTerminal for Stdin {
     }
 }
 
+#[cfg(not(target_os = "linux"))]
+impl IsTerminal for Stdout {
+    fn is_terminal(&self) -> bool {
+        StdIsTerminal::is_terminal(&self.0)
+    }
+}
+
+// Only the standard streams are reachable by number off Linux
+#[cfg(not(target_os = "linux"))]
+impl Write for Fd {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        if self.0 == 2 {
+            StdWrite::write(&mut std_stderr(), buf)
+        } else {
+            StdWrite::write(&mut std_stdout(), buf)
+        }
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
 #[cfg(not(target_os = "linux"))]
 pub fn print_fmt(args: Arguments<'_>) {
     let mut o = std_stdout();
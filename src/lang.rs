@@ -7,6 +7,19 @@ pub fn to_bcp47(code: &str) -> Cow<'static, str> {
     canonical(code).map_or_else(|| Cow::Owned(code.to_owned()), Cow::Borrowed)
 }
 
+// A bare primary subtag in `want` matches every script/region variant of it
+#[must_use]
+pub fn lang_matches(tag: &str, want: &str) -> bool {
+    let (p, rest) = want.split_once('-').map_or((want, ""), |(p, r)| (p, r));
+    let (tp, trest) = tag.split_once('-').map_or((tag, ""), |(p, r)| (p, r));
+    to_bcp47(&p.to_ascii_lowercase()) == to_bcp47(&tp.to_ascii_lowercase())
+        && (rest.is_empty()
+            || trest
+                .get(..rest.len())
+                .is_some_and(|r| r.eq_ignore_ascii_case(rest))
+                && trest.as_bytes().get(rest.len()).is_none_or(|&b| b == b'-'))
+}
+
 fn canonical(code: &str) -> Option<&'static str> {
     Some(match code {
         "eng" => "en",
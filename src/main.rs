@@ -35,11 +35,12 @@ use crate::{
     error::Xerr::Help,
     fs::{
         create_dir_all, read_to_string as read_to_str, remove_dir_all as rm_dir_all,
-        remove_file as rm_file, write as write_to,
+        remove_file as rm_file,
     },
-    io::{Write as _, print_fmt, println_fmt, stdout},
+    io::{Write as _, print_fmt, println_fmt, stderr, stdout},
     path::{Path, PathBuf},
-    thread::available_parallelism,
+    process::{kill_all, var},
+    thread::{available_parallelism, sleep, spawn},
 };
 
 macro_rules! print {
@@ -53,22 +54,27 @@ macro_rules! println {
 #[cfg(feature = "vship")]
 mod atofu;
 mod audio;
+#[cfg(feature = "vship")]
+mod audit;
 #[cfg(feature = "avm")]
 mod av2_parse;
 #[cfg(feature = "avm")]
 mod avm;
 mod byte_range;
+mod cc608;
 mod chan;
 mod chunk;
 mod clk;
 mod copy;
 mod crop;
+mod ctl;
 #[cfg(feature = "vship")]
 mod dav1d;
 mod dec;
 mod enc;
 mod encoder;
 mod error;
+mod events;
 mod ffms;
 #[cfg(all(target_os = "linux", not(test)))]
 mod fmath;
@@ -80,6 +86,8 @@ mod interp;
 mod io;
 mod lang;
 mod lavf;
+mod manifest;
+mod mem;
 mod mkv;
 mod mkv_mux;
 mod mux_webm;
@@ -96,7 +104,10 @@ mod plat;
 mod platform;
 mod process;
 mod progs;
+mod queue;
+mod report;
 mod scd;
+mod subconv;
 mod svt;
 mod svterr;
 mod sync;
@@ -105,6 +116,8 @@ mod sys;
 mod thread;
 #[cfg(feature = "vship")]
 mod tq;
+#[cfg(feature = "vship")]
+mod tqcache;
 #[cfg(target_os = "linux")]
 mod uring;
 mod util;
@@ -113,34 +126,44 @@ mod vship;
 mod worker;
 mod y4m;
 
-use audio::{AuSpec, AuStream, enc_au_streams, frame_samp, parse_au_arg};
+use audio::{
+    AuSpec, AuStream, AuStreams, au_report, enc_au_streams, frame_samp, layout, parse_au_arg, to_db,
+};
+#[cfg(feature = "vship")]
+use audit::parse_audit;
 #[cfg(feature = "vship")]
 use chunk::has_rc;
 use chunk::{
-    Chunk, Scene, chnkify, get_resume, init_elapsed, load_scenes, merge_out, trans_scenes,
-    val_scenes,
+    Chunk, Scene, apply_redo, chnkify, flush_resume, get_resume, init_elapsed, load_scenes,
+    merge_out, trans_scenes, val_scenes,
 };
 use crop::{CropConf, detect_crop};
 use enc::enc_all;
 #[cfg(feature = "vship")]
-use enc::{is_cvvdp, tq_target};
+use enc::{is_cvvdp, tq_target, unmet_chunks};
 use encoder::Encoder;
-use error::{IN_ALT_SCREEN, SIGINT, SIGSEGV, Xerr, eprint, exit, fatal, signal};
+use error::{
+    IN_ALT_SCREEN, INTERRUPTED, SIG_IGN, SIGHUP, SIGINT, SIGPIPE, SIGSEGV, SIGTERM, UNWIND, Xerr,
+    eprint, exit, fatal, park_if_interrupted, signal,
+};
 use ffms::{DecStrat, VidDecoder, VidInf, get_dec_strat, get_vidinf, vid_bytes};
 use scd::fd_scenes;
 use svterr::val;
 #[cfg(feature = "vship")]
-use vship::{Disp, load_disp};
+use tq::{Agg, Also, Metric, Sample, parse_also, val_agg};
+#[cfg(feature = "vship")]
+use vship::{Disp, init_device, load_disp};
 #[cfg(target_os = "linux")]
-use y4m::vspipe_resume;
+use y4m::{PipeCmd, pipe_cmd_start, pipe_failure, vspipe_resume};
 use y4m::{PipeReader, init_pipe, is_pipe};
 
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
 mod tests;
 
-use util::{B, C, Fnv, G, N, P, R, W, Y};
+use util::{B, C, Fnv, G, N, P, R, W, Y, json_str};
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Clone)]
 pub struct Args {
     pub encoder: Encoder,
@@ -152,7 +175,20 @@ pub struct Args {
     pub out: PathBuf,
     pub dec_strat: Option<DecStrat>,
     pub chnk_buff: usize,
+    // raw `-b`; `-w auto` (`worker` 0 until resolved) sizes the buffer itself without it
+    pub buff: Option<usize>,
+    // `--mem-limit` bytes: caps `-w auto` sizing & arms the low-memory backoff
+    pub mem_limit: Option<u64>,
+    // extra tries for a failing chunk; `fallback` params are appended for one final try
+    pub retries: usize,
+    pub fallback: Option<String>,
+    // `--dump-failed`: save failed chunks' frames & a repro script under `repro/`
+    pub dump_failed: bool,
+    // `--heavy-first`: decode & encode the costliest chunks first
+    pub heavy_first: bool,
     pub ranges: Option<Vec<(usize, usize)>>,
+    // `--pipe-cmd`: filter pipeline xav spawns itself, with `{start}` & `{end}` frames
+    pub pipe_cmd: Option<String>,
     #[cfg(feature = "vship")]
     pub qp_range: Option<String>,
     #[cfg(feature = "vship")]
@@ -160,15 +196,39 @@ pub struct Args {
     #[cfg(feature = "vship")]
     pub tq: Option<String>,
     #[cfg(feature = "vship")]
-    pub metric_mode: String,
+    pub metric_mode: Agg,
     #[cfg(feature = "vship")]
     pub cvvdp_conf: Option<String>,
     #[cfg(feature = "vship")]
     pub disp: Option<Disp>,
     #[cfg(feature = "vship")]
     pub alt_param: Option<String>,
+    // `--tq-and` terms every TQ pick must also meet
+    #[cfg(feature = "vship")]
+    pub tq_and: Vec<Also>,
+    // `--crf-smooth`: largest CRF step between neighbouring chunks after TQ
+    #[cfg(feature = "vship")]
+    pub crf_smooth: Option<f32>,
+    // `--tq-sample`: probes score every Nth frame; 1 scores all
+    #[cfg(feature = "vship")]
+    pub tq_sample: usize,
+    // `--tq-sample scdN` picks that share by SCD weight; `encN` encodes only those frames
+    #[cfg(feature = "vship")]
+    pub tq_sample_by: Sample,
+    // `--audit` metrics: the finished encode scored frame by frame
+    #[cfg(feature = "vship")]
+    pub audit: Vec<Metric>,
     pub sc_only: bool,
     pub hwdec: bool,
+    pub au_report: bool,
+    pub progress: Option<i32>,
+    pub keep_work: bool,
+    // where `.<hash>` tmp dirs go instead of next to the input (`--work-dir`/`XAV_WORK_DIR`)
+    pub work_dir: Option<PathBuf>,
+    // chunk indices to re-encode & the `-p` given with them, applied as their zone params
+    pub redo: Option<(Vec<u16>, String)>,
+    // `--queue`: argv index of its value & the queue file or watched dir
+    pub queue: Option<(usize, PathBuf)>,
 }
 
 extern "C" fn restore() {
@@ -181,6 +241,34 @@ extern "C" fn exit_restore(_: i32) {
     restore();
     exit(130)
 }
+// First SIGINT/SIGTERM/SIGHUP stops dispatch and hands off to `watch_intr`; a second aborts
+extern "C" fn on_intr(sig: i32) {
+    if INTERRUPTED.swap(true, Relaxed) {
+        exit_restore(sig);
+    }
+    ctl::interrupt();
+}
+
+fn watch_intr() {
+    spawn(|| {
+        while !INTERRUPTED.load(Relaxed) {
+            sleep(Durat::from_millis(100));
+        }
+        kill_all();
+        flush_resume();
+        if UNWIND.load(Relaxed) {
+            ctl::halt();
+            return;
+        }
+        restore();
+        events::phase("interrupted");
+        _ = writeln!(
+            stderr(),
+            "{Y}Interrupted; finished chunks are saved, rerun the same command to resume{N}"
+        );
+        exit(130)
+    });
+}
 
 const fn wmax(a: usize, b: usize) -> usize {
     if a > b { a } else { b }
@@ -206,19 +294,35 @@ fn print_help() {
     println!("{C}-e {P}┃ {C}--encoder    {R}<{G}svt-av1{P}┃{G}avm{P}┃{G}vvenc{P}┃{G}x265{P}┃{G}x264{R}>");
     #[cfg(not(feature = "avm"))]
     println!("{C}-e {P}┃ {C}--encoder    {R}<{G}svt-av1{P}┃{G}vvenc{P}┃{G}x265{P}┃{G}x264{R}>");
-    println!("{C}-w {P}┃ {C}--worker     {W}Parallelism");
+    println!("{C}-w {P}┃ {C}--worker     {W}Parallelism: N or {G}auto");
     println!("{C}-b {P}┃ {C}--buff       {W}Chunks to buffer");
     println!("{C}-p {P}┃ {C}--param      {W}Encoder params");
     println!("{C}-s {P}┃ {C}--sc         {W}SCD file");
     println!("   {P}┃ {C}--sc-only    {W}Exit after SCD");
     println!("   {P}┃ {C}--hwdec      {W}GPU decode");
+    println!("   {P}┃ {C}--au-report  {W}Audio loudness report only; no encode");
+    println!("   {P}┃ {C}--progress   {W}NDJSON events: {G}json {W}(stdout) or {C}--progress-fd {G}N");
+    println!("   {P}┃ {C}--keep-work  {W}Keep tmp dir after mux");
+    println!("   {P}┃ {C}--work-dir   {W}Tmp dir location (env: {G}XAV_WORK_DIR{W})");
+    println!("   {P}┃ {C}--queue      {W}Queue file or watched dir of inputs");
+    println!("   {P}┃ {C}--mem-limit  {W}Memory budget, e.g. {G}24G");
+    println!("   {P}┃ {C}--retries    {W}Extra tries for a failing chunk (default {G}2{W})");
+    println!("   {P}┃ {C}--fallback-param {W}Params for a last try");
+    println!("   {P}┃ {C}--dump-failed {W}Save failed chunks as Y4M + repro script");
+    println!("   {P}┃ {C}--heavy-first {W}Encode the costliest chunks first");
+    println!("   {P}┃ {C}--pipe-cmd   {W}Filter pipe xav runs & resumes: {G}\"vspipe -s {{start}} -e {{end}} a.vpy -c y4m -\"");
+    println!("   {P}┃ {C}--redo       {W}Re-encode chunks & re-mux: {G}\"12,45-48\"");
     println!("{C}-r {P}┃ {C}--range      {W}Trim/splice: {G}\"10-20,90-100\"");
-    println!("{C}-a {P}┃ {C}--audio      {W}Opus Enc: {Y}-a {G}\"{R}<{G}auto{P}┃{G}norm{P}┃{G}bitrate{R}> {R}<{G}all{P}┃{G}stream_ids{R}>{G}\"");
+    println!("{C}-a {P}┃ {C}--audio      {W}Opus Enc: {Y}-a {G}\"{R}<{G}auto{P}┃{G}norm{P}┃{G}drc{P}┃{G}bitrate{R}> {R}<{G}all{P}┃{G}ids{P}┃{G}selectors{R}> {R}[{G}opus_opts{R}]{G}\"");
     #[cfg(feature = "vship")]
     {
         println!("{C}-t {P}┃ {C}--tq         {W}TQ Range: {R}<8{B}={W}Butter, {R}8-10{B}={W}CVVDP, {R}>10{B}={W}SSIMU2");
-        println!("{C}-m {P}┃ {C}--mode       {W}TQ stat: {G}mean {W}or pN%");
+        println!("{C}-m {P}┃ {C}--mode       {W}TQ stat: {G}mean{W}, {G}pN{W}, {G}median{W}, {G}min{W}, {G}max{W}, {G}hmean{W}, {G}mean-Ksd{W}, {G}winN");
+        println!("   {P}┃ {C}--tq-sample  {W}Probes score every Nth frame, by SCD weight with {G}scdN{W}, encode only those with {G}encN");
         println!("{C}-f {P}┃ {C}--qp         {W}CRF range: {G}crf-crf{W}");
+        println!("   {P}┃ {C}--tq-and     {W}Also require: {G}\"ssimu2:p5>=78,butter:max<=2.5\"");
+        println!("   {P}┃ {C}--crf-smooth {W}Max CRF step between neighbouring chunks, e.g. {G}4");
+        println!("   {P}┃ {C}--audit      {W}Score the output per frame: {G}ssimu2{W}, {G}butter{W}, e.g. {G}ssimu2,butter");
         println!("{C}-v {P}┃ {C}--vship      {W}Metric parallelism");
         println!("{C}-d {P}┃ {C}--display    {W}CVVDP display file");
         println!("{C}-P {P}┃ {C}--alt-param  {W}Alt params for probes ({R}NOT RECOMMENDED{W}; expert-only)");
@@ -300,6 +404,21 @@ fn parse_ranges(s: &str) -> Result<Vec<(usize, usize)>, Xerr> {
     Ok(r)
 }
 
+fn parse_redo(s: &str) -> Result<Vec<u16>, Xerr> {
+    let mut v = Vec::new();
+    for p in s.split(',') {
+        let (a, b) = p.split_once('-').unwrap_or((p, p));
+        let (a, b): (u16, u16) = (a.trim().parse()?, b.trim().parse()?);
+        if a > b {
+            return Err(format!("invalid --redo range: {p}").into());
+        }
+        v.extend(a..=b);
+    }
+    v.sort_unstable();
+    v.dedup();
+    Ok(v)
+}
+
 fn apply_defaults(args: &mut Args) {
     if args.out == PathBuf::new() {
         let stem = unsafe { args.inp.file_stem().unwrap_unchecked() }.to_string_lossy();
@@ -308,7 +427,11 @@ fn apply_defaults(args: &mut Args) {
 
     if args.sc_file == PathBuf::new() {
         let stem = unsafe { args.inp.file_stem().unwrap_unchecked() }.to_string_lossy();
-        args.sc_file = args.inp.with_file_name(format!("{stem}_scd.txt"));
+        args.sc_file = args.work_dir.as_ref().map_or_else(
+            || args.inp.with_file_name(format!("{stem}_scd.txt")),
+            // one flat dir for many inputs: the path hash keeps same-named files apart
+            |w| w.join(format!("{stem}.{}_scd.txt", &hash_inp(&args.inp)[..7])),
+        );
     }
 
     #[cfg(feature = "vship")]
@@ -373,11 +496,38 @@ macro_rules! arg {
     };
 }
 
+#[cfg(feature = "vship")]
+fn parse_smooth(v: &str) -> Result<f32, Xerr> {
+    let Some(d) = v.parse::<f32>().ok().filter(|d| *d > 0.0) else {
+        return Err(format!("Invalid --crf-smooth: {v} (use a CRF step such as 4)").into());
+    };
+    Ok(d)
+}
+
+// `--tq-sample`: `N`, `scdN` or `encN`, the frame step & how its frames are picked
+#[cfg(feature = "vship")]
+fn parse_sample(v: &str) -> Result<(usize, Sample), Xerr> {
+    let (n, by) = [("scd", Sample::Scd), ("enc", Sample::Enc)]
+        .into_iter()
+        .find_map(|(pre, by)| v.strip_prefix(pre).map(|n| (n, by)))
+        .unwrap_or((v, Sample::Step));
+    let Some(n) = n.parse::<usize>().ok().filter(|n| *n > 0) else {
+        return Err(
+            format!("Invalid --tq-sample: {v} (use a frame step such as 4, scd4 or enc4)").into(),
+        );
+    };
+    Ok((n, by))
+}
+
 fn parse_args_loop(args: &[String]) -> Result<Args, Xerr> {
     let (mut worker, mut chnk_buff, mut sc_only, mut hwdec) = (1usize, None, false, false);
+    let (mut au_report, mut progress) = (false, None);
+    let (mut keep_work, mut redo, mut work_dir, mut mem_limit) = (false, None, None, None);
+    let (mut retries, mut fallback, mut dump_failed, mut heavy_first) =
+        (2usize, None, false, false);
     let (mut sc_file, mut inp, mut out) = (PathBuf::new(), PathBuf::new(), PathBuf::new());
     let (mut encoder, mut params) = (Encoder::default(), String::new());
-    let (mut au, mut ranges) = (None, None);
+    let (mut au, mut ranges, mut pipe_cmd, mut queue) = (None, None, None, None);
     #[cfg(feature = "vship")]
     let (mut tq, mut qp_range, mut cvvdp_conf, mut alt_param) = (
         None::<String>,
@@ -386,7 +536,11 @@ fn parse_args_loop(args: &[String]) -> Result<Args, Xerr> {
         None::<String>,
     );
     #[cfg(feature = "vship")]
-    let (mut metric_mode, mut metric_worker) = ("mean".to_owned(), 1usize);
+    let (mut metric_mode, mut metric_worker) = (Agg::Mean, 1usize);
+    #[cfg(feature = "vship")]
+    let (mut tq_sample, mut tq_sample_by) = (1usize, Sample::Step);
+    #[cfg(feature = "vship")]
+    let (mut tq_and, mut crf_smooth, mut audit) = (Vec::new(), None, Vec::new());
 
     let mut i = 1;
     while i < args.len() {
@@ -397,15 +551,18 @@ fn parse_args_loop(args: &[String]) -> Result<Args, Xerr> {
                         Encoder::from_str(v).ok_or_else(|| format!("Unknown encoder: {v}"))?;
                 }
             }
-            "-w" | "--worker" => arg!(parse args, i, worker),
-            "-s" | "--sc" => arg!(path args, i, sc_file),
-            "-p" | "--param" => arg!(str args, i, params),
-            "-b" | "--buff" => arg!(opt_parse args, i, chnk_buff),
-            "-r" | "--range" => {
+            "-w" | "--worker" => {
                 if let Some(v) = next_arg(args, &mut i) {
-                    ranges = Some(parse_ranges(v)?);
+                    worker = if v == "auto" { 0 } else { v.parse()? };
+                    if worker == 0 && v != "auto" {
+                        return Err("-w must be at least 1 or auto".into());
+                    }
                 }
             }
+            "-s" | "--sc" => arg!(path args, i, sc_file),
+            "-p" | "--param" => arg!(str args, i, params),
+            "-b" | "--buff" => arg!(opt_parse args, i, chnk_buff),
+            "-r" | "--range" => ranges = next_arg(args, &mut i).map(parse_ranges).transpose()?,
             "-a" | "--audio" => {
                 if let Some(v) = next_arg(args, &mut i) {
                     au = Some(parse_au_arg(v)?);
@@ -414,7 +571,17 @@ fn parse_args_loop(args: &[String]) -> Result<Args, Xerr> {
             #[cfg(feature = "vship")]
             "-t" | "--tq" => arg!(opt args, i, tq),
             #[cfg(feature = "vship")]
-            "-m" | "--mode" => arg!(str args, i, metric_mode),
+            "-m" | "--mode" => {
+                if let Some(v) = next_arg(args, &mut i) {
+                    metric_mode = Agg::parse(v)?;
+                }
+            }
+            #[cfg(feature = "vship")]
+            "--tq-sample" => {
+                if let Some(v) = next_arg(args, &mut i) {
+                    (tq_sample, tq_sample_by) = parse_sample(v)?;
+                }
+            }
             #[cfg(feature = "vship")]
             "-f" | "--qp" => arg!(opt args, i, qp_range),
             #[cfg(feature = "vship")]
@@ -423,8 +590,68 @@ fn parse_args_loop(args: &[String]) -> Result<Args, Xerr> {
             "-d" | "--display" => arg!(opt args, i, cvvdp_conf),
             #[cfg(feature = "vship")]
             "-P" | "--alt-param" => arg!(opt args, i, alt_param),
+            #[cfg(feature = "vship")]
+            "--tq-and" => {
+                tq_and = next_arg(args, &mut i)
+                    .map(parse_also)
+                    .transpose()?
+                    .unwrap_or_default();
+            }
+            #[cfg(feature = "vship")]
+            "--audit" => {
+                audit = next_arg(args, &mut i)
+                    .map(parse_audit)
+                    .transpose()?
+                    .unwrap_or_default();
+            }
+            #[cfg(feature = "vship")]
+            "--crf-smooth" => crf_smooth = next_arg(args, &mut i).map(parse_smooth).transpose()?,
             "--hwdec" => hwdec = true,
             "--sc-only" => sc_only = true,
+            "--au-report" => au_report = true,
+            "--progress" => {
+                if let Some(v) = next_arg(args, &mut i) {
+                    if v != "json" {
+                        return Err(format!("Unknown --progress mode: {v}").into());
+                    }
+                    progress = Some(1);
+                }
+            }
+            "--keep-work" => keep_work = true,
+            "--retries" => arg!(parse args, i, retries),
+            "--fallback-param" => arg!(opt args, i, fallback),
+            "--dump-failed" => dump_failed = true,
+            "--heavy-first" => heavy_first = true,
+            "--pipe-cmd" => arg!(opt args, i, pipe_cmd),
+            "--queue" => {
+                let v = next_arg(args, &mut i)
+                    .ok_or("--queue needs a queue file or a directory to watch")?;
+                queue = Some((i, PathBuf::from(v)));
+            }
+            "--mem-limit" => {
+                if let Some(v) = next_arg(args, &mut i) {
+                    mem_limit = Some(mem::parse_mem(v)?);
+                }
+            }
+            "--work-dir" => {
+                if let Some(v) = next_arg(args, &mut i) {
+                    work_dir = Some(PathBuf::from(v));
+                }
+            }
+            "--redo" => {
+                if let Some(v) = next_arg(args, &mut i) {
+                    redo = Some((parse_redo(v)?, String::new()));
+                }
+            }
+            "--progress-fd" => {
+                if let Some(v) = next_arg(args, &mut i) {
+                    let fd: i32 = v.parse()?;
+                    if fd < 1 {
+                        return Err(format!("Invalid --progress-fd: {fd}").into());
+                    }
+                    progress = Some(fd);
+                }
+            }
             "-h" | "--help" => {
                 print_help();
                 return Err(Help);
@@ -455,9 +682,22 @@ fn parse_args_loop(args: &[String]) -> Result<Args, Xerr> {
         out,
         dec_strat: None,
         chnk_buff: worker + chnk_buff.unwrap_or(0),
+        buff: chnk_buff,
+        mem_limit,
+        retries,
+        fallback,
+        dump_failed,
+        heavy_first,
         ranges,
+        pipe_cmd,
         sc_only,
         hwdec,
+        au_report,
+        progress,
+        keep_work,
+        redo,
+        queue,
+        work_dir: work_dir.or_else(|| var("XAV_WORK_DIR").map(PathBuf::from)),
         #[cfg(feature = "vship")]
         tq,
         #[cfg(feature = "vship")]
@@ -472,6 +712,16 @@ fn parse_args_loop(args: &[String]) -> Result<Args, Xerr> {
         disp: None,
         #[cfg(feature = "vship")]
         alt_param,
+        #[cfg(feature = "vship")]
+        tq_and,
+        #[cfg(feature = "vship")]
+        crf_smooth,
+        #[cfg(feature = "vship")]
+        tq_sample,
+        #[cfg(feature = "vship")]
+        tq_sample_by,
+        #[cfg(feature = "vship")]
+        audit,
     })
 }
 
@@ -482,13 +732,29 @@ fn get_args(args: &[String], allow_resume: bool) -> Result<Args, Xerr> {
 
     let mut result = parse_args_loop(args)?;
 
+    if result.queue.is_some() {
+        return Ok(result);
+    }
     if result.inp == PathBuf::new() {
         return Err("Missing input".into());
     }
 
-    if allow_resume && let Ok(saved_args) = get_saved_args(&result.inp) {
+    if allow_resume
+        && !result.au_report
+        && let Ok((mut saved_args, saved)) = get_saved_args(&result.inp, result.work_dir.as_deref())
+    {
+        val_resume(args, &saved, &result)?;
+        saved_args.progress = result.progress;
+        saved_args.keep_work |= result.keep_work;
+        saved_args.dump_failed |= result.dump_failed;
+        saved_args.heavy_first |= result.heavy_first;
+        saved_args.redo = result.redo.map(|(r, _)| (r, result.params));
+        val_redo(&saved_args)?;
         return Ok(saved_args);
     }
+    if result.redo.is_some() && allow_resume {
+        return Err("--redo needs the tmp dir of an unfinished or --keep-work run".into());
+    }
     if result.out != PathBuf::new() {
         val_out(&result.out, result.encoder)?;
     }
@@ -512,23 +778,94 @@ fn get_args(args: &[String], allow_resume: bool) -> Result<Args, Xerr> {
                     .into(),
             );
         }
+        let target = tq_target(tq);
+        if is_cvvdp(target) && result.tq_and.iter().any(|a| a.metric == Metric::Cvvdp) {
+            return Err("--tq-and cvvdp repeats the -t metric: use -t alone".into());
+        }
+        let metric = if target < 8.0 {
+            Metric::Butter
+        } else if is_cvvdp(target) {
+            Metric::Cvvdp
+        } else {
+            Metric::Ssimu2
+        };
+        val_agg(metric, result.metric_mode)?;
+        if result.tq_sample > 1
+            && (metric == Metric::Cvvdp || result.tq_and.iter().any(|a| a.metric == Metric::Cvvdp))
+        {
+            return Err(
+                "--tq-sample can't be used with CVVDP: it scores frames in sequence".into(),
+            );
+        }
+    }
+    #[cfg(feature = "vship")]
+    if result.tq.is_none() && result.tq_sample > 1 {
+        return Err("--tq-sample needs -t/--tq".into());
+    }
+    #[cfg(feature = "vship")]
+    if result.tq.is_none() && !result.tq_and.is_empty() {
+        return Err("--tq-and needs -t/--tq".into());
+    }
+    #[cfg(feature = "vship")]
+    if result.crf_smooth.is_some() && (result.tq.is_none() || piped(&result)) {
+        return Err("--crf-smooth needs -t/--tq & a file input: it encodes chunks again".into());
+    }
+    #[cfg(feature = "vship")]
+    if !result.audit.is_empty() && piped(&result) {
+        return Err("--audit needs a file input: it decodes the source again".into());
     }
 
     if result.encoder == SvtAv1 {
         val(&result.params)?;
+        if let Some(ref fb) = result.fallback {
+            val(fb)?;
+        }
         #[cfg(feature = "vship")]
         if let Some(ref pp) = result.alt_param {
             val(pp)?;
         }
     }
 
-    if result.hwdec && is_pipe() {
+    if result.heavy_first && result.pipe_cmd.is_none() && is_pipe() {
+        return Err("--heavy-first needs a file input or --pipe-cmd: a pipe can not seek".into());
+    }
+    #[cfg(not(target_os = "linux"))]
+    if result.pipe_cmd.is_some() {
+        return Err("--pipe-cmd is only supported on Linux".into());
+    }
+
+    if result.hwdec && piped(&result) {
         return Err("Hardware accelerated decoding can not be used with a pipe".into());
     }
 
     Ok(result)
 }
 
+// Frames come from a pipe: into stdin or spawned by `--pipe-cmd`
+fn piped(args: &Args) -> bool {
+    args.pipe_cmd.is_some() || is_pipe()
+}
+
+fn val_redo(args: &Args) -> Result<(), Xerr> {
+    let Some((_, ref p)) = args.redo else {
+        return Ok(());
+    };
+    if piped(args) {
+        return Err("--redo can not be used with a pipe".into());
+    }
+    if p.is_empty() {
+        return Ok(());
+    }
+    #[cfg(feature = "vship")]
+    if args.tq.is_some() && has_rc(p) {
+        return Err("--redo -p must not set CRF/QP in target-quality mode".into());
+    }
+    if args.encoder == SvtAv1 {
+        val(p)?;
+    }
+    Ok(())
+}
+
 fn hash_inp(path: &Path) -> String {
     let canon = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
     let mut hasher = Fnv::new();
@@ -536,35 +873,62 @@ fn hash_inp(path: &Path) -> String {
     format!("{:x}", hasher.finish())
 }
 
-fn save_args(work_dir: &Path) -> Result<(), Xerr> {
-    let cmd: Vec<String> = env_args().collect();
-    let quoted_cmd: Vec<String> = cmd
-        .iter()
-        .map(|arg| {
-            if arg.contains(' ') {
-                format!("\"{arg}\"")
-            } else {
-                arg.clone()
-            }
-        })
-        .collect();
-    write_to(work_dir.join("cmd.txt"), quoted_cmd.join(" "))?;
-    Ok(())
+// `.<hash>` under `--work-dir` or beside the input, else found through the index
+fn locate_work_dir(inp: &Path, canon: &Path, base: Option<&Path>) -> PathBuf {
+    let name = format!(".{}", &hash_inp(canon)[..7]);
+    let dir = base.map_or_else(|| inp.with_file_name(&name), |b| b.join(&name));
+    manifest::adopt(canon, &dir);
+    if dir.exists() {
+        return dir;
+    }
+    manifest::index_find(canon).unwrap_or(dir)
 }
 
-fn get_saved_args(inp: &Path) -> Result<Args, Xerr> {
+fn get_saved_args(inp: &Path, base: Option<&Path>) -> Result<(Args, Vec<String>), Xerr> {
     let canon = inp.canonicalize()?;
-    let hash = hash_inp(&canon);
-    let work_dir = inp.with_file_name(format!(".{}", &hash[..7]));
-    let cmd_path = work_dir.join("cmd.txt");
+    let work_dir = locate_work_dir(inp, &canon, base);
 
-    if cmd_path.exists() && get_resume(&work_dir).is_some_and(|r| !r.chnks_done.is_empty()) {
-        let cmd_line = read_to_str(cmd_path)?;
-        let saved_args = parse_quoted_args(&cmd_line);
-        get_args(&saved_args, false)
+    if get_resume(&work_dir).is_none_or(|r| r.chnks_done.is_empty()) {
+        return Err("No tmp dir found".into());
+    }
+    let cmd_path = work_dir.join("cmd.txt");
+    let saved = if let Some(saved_args) = manifest::saved_args(&work_dir) {
+        saved_args
+    } else if cmd_path.exists() {
+        // tmp dirs from before manifest.txt
+        parse_quoted_args(&read_to_str(cmd_path)?)
     } else {
-        Err("No tmp dir found".into())
+        return Err("No tmp dir found".into());
+    };
+    Ok((get_args(&saved, false)?, saved))
+}
+
+// A resume may only repeat the saved run's settings, in order: new ones would be dropped
+fn val_resume(args: &[String], saved: &[String], cur: &Args) -> Result<(), Xerr> {
+    let inp = cur.inp.to_string_lossy();
+    let (mut rest, mut seen_inp, mut i) = (saved.iter().skip(1), false, 1);
+    while let Some(a) = args.get(i) {
+        i += 1;
+        let skip = match a.as_str() {
+            "--keep-work" | "--dump-failed" | "--heavy-first" => 0,
+            "--progress" | "--progress-fd" | "--redo" | "--work-dir" => 1,
+            "-p" | "--param" if cur.redo.is_some() => 1,
+            s if !seen_inp && s == inp => {
+                seen_inp = true;
+                0
+            }
+            s if rest.any(|g| g == s) => 0,
+            _ => {
+                return Err(format!(
+                    "`{a}` differs from the unfinished run; resume with only the input or delete \
+                     the tmp dir to start over"
+                )
+                .into());
+            }
+        };
+        i += skip;
     }
+    Ok(())
 }
 
 fn parse_quoted_args(cmd_line: &str) -> Vec<String> {
@@ -594,6 +958,7 @@ fn parse_quoted_args(cmd_line: &str) -> Vec<String> {
 
 fn ensure_sc_file(args: &Args, inf: &VidInf, crop: (u32, u32), line: usize) -> Result<(), Xerr> {
     if !args.sc_file.exists() {
+        events::phase("scd");
         fd_scenes(&args.inp, &args.sc_file, inf, crop, line, args.hwdec)?;
     }
     Ok(())
@@ -612,6 +977,21 @@ const fn scale_crop(
     (scaled_v, scaled_h)
 }
 
+// The filter pipe restarted at the first unfinished chunk & the index it starts from
+#[cfg(target_os = "linux")]
+fn start_pipe(
+    args: &Args,
+    chnks: &[Chunk],
+    work_dir: &Path,
+) -> Result<(usize, Option<PipeCmd>), Xerr> {
+    let Some(ref tmpl) = args.pipe_cmd else {
+        return Ok((vspipe_resume(chnks, work_dir).unwrap_or(0), None));
+    };
+    let pc = pipe_cmd_start(tmpl, chnks, work_dir, args.heavy_first)
+        .ok_or_else(|| format!("--pipe-cmd could not be started: {tmpl}"))?;
+    Ok((0, Some(pc)))
+}
+
 fn init_pipe_crop(
     inf: VidInf,
     crop: (u32, u32),
@@ -649,8 +1029,8 @@ fn acq_au(
     inf: &VidInf,
     work_dir: &Path,
 ) -> Result<Vec<(AuStream, PathBuf)>, Xerr> {
-    print!("\x1b[H\x1b[2J");
-    _ = stdout().flush();
+    events::phase("audio");
+    clear_screen();
     let samp_ranges = args.ranges.as_ref().map(|r| {
         r.iter()
             .map(|&(s, e)| {
@@ -676,19 +1056,46 @@ fn val_all_scenes(scenes: &[Scene], enc: Encoder) -> Result<(), Xerr> {
     Ok(())
 }
 
-fn main_with_args(args: &Args) -> Result<(), Xerr> {
-    print!("\x1b[?1049h\x1b[H\x1b[?25l");
-    _ = stdout().flush();
-    IN_ALT_SCREEN.store(true, Relaxed);
+fn clear_screen() {
+    if events::draw() {
+        print!("\x1b[H\x1b[2J");
+        _ = stdout().flush();
+    }
+}
+
+// `argv` is what a fresh tmp dir records for resume; `None` when nothing was muxed
+fn main_with_args(args: &Args, argv: &[String]) -> Result<Option<Sum>, Xerr> {
+    encode(args, argv)?.map(finish).transpose()
+}
+
+// What the encode hands to `finish`; a `--queue` muxes it while the next input encodes
+pub struct Encoded {
+    args: Args,
+    inf: VidInf,
+    chnks: Vec<Chunk>,
+    crop: (u32, u32),
+    work_dir: PathBuf,
+    enc_time: Durat,
+    redo: Option<String>,
+}
+
+// Everything up to the muxing; `None` when stopped or only scenes were wanted
+fn encode(args: &Args, argv: &[String]) -> Result<Option<Encoded>, Xerr> {
+    if events::draw() {
+        print!("\x1b[?1049h\x1b[H\x1b[?25l");
+        _ = stdout().flush();
+        IN_ALT_SCREEN.store(true, Relaxed);
+    }
 
     let canon_inp = args.inp.canonicalize()?;
-    let hash = hash_inp(&canon_inp);
-    let work_dir = args.inp.with_file_name(format!(".{}", &hash[..7]));
+    let work_dir = locate_work_dir(&args.inp, &canon_inp, args.work_dir.as_deref());
 
     create_dir_all(&work_dir)?;
 
     if get_resume(&work_dir).is_none_or(|r| r.chnks_done.is_empty()) {
-        save_args(&work_dir)?;
+        manifest::create(&work_dir, &canon_inp, argv.to_vec())?;
+    } else {
+        manifest::check_src(&work_dir, &canon_inp)?;
     }
 
     if args.sc_only && args.sc_file.exists() {
@@ -706,7 +1113,7 @@ fn main_with_args(args: &Args) -> Result<(), Xerr> {
     }
     #[cfg(feature = "vship")]
     if let Some(ref t) = args.tq
-        && is_cvvdp(tq_target(t))
+        && (is_cvvdp(tq_target(t)) || args.tq_and.iter().any(|a| a.metric == Metric::Cvvdp))
     {
         args.disp = Some(load_disp(args.cvvdp_conf.as_deref(), &inf)?);
     }
@@ -716,6 +1123,7 @@ fn main_with_args(args: &Args) -> Result<(), Xerr> {
         sample_cnt: 13,
         min_black_pix: 2,
     };
+    events::phase("crop");
     let crop = match detect_crop(&args.inp, &inf, &conf, thr, 1) {
         Ok(detected) if detected.has_crop() => detected.to_tuple(),
         _ => (0, 0),
@@ -723,8 +1131,7 @@ fn main_with_args(args: &Args) -> Result<(), Xerr> {
 
     ensure_sc_file(&args, &inf, crop, 3)?;
 
-    print!("\x1b[H\x1b[2J");
-    _ = stdout().flush();
+    clear_screen();
 
     #[cfg(feature = "vship")]
     let tq = args.tq.is_some();
@@ -741,20 +1148,25 @@ fn main_with_args(args: &Args) -> Result<(), Xerr> {
 
     val_all_scenes(&scenes, args.encoder)?;
     if args.sc_only {
-        return Ok(());
+        return Ok(None);
     }
 
     create_dir_all(work_dir.join("split"))?;
     create_dir_all(work_dir.join("encode"))?;
 
-    let chnks = chnkify(&scenes);
+    let mut chnks = chnkify(&scenes);
 
     #[cfg(target_os = "linux")]
-    let pipe_start = vspipe_resume(&chnks, &work_dir).unwrap_or(0);
+    let (pipe_start, pipe_cmd) = start_pipe(&args, &chnks, &work_dir)?;
     #[cfg(not(target_os = "linux"))]
     let pipe_start = 0usize;
 
     let (mut inf, crop, pipe_reader) = init_pipe_crop(inf, crop, pipe_start);
+    #[cfg(target_os = "linux")]
+    let pipe_reader = pipe_reader.map(|mut r| {
+        r.cmd = pipe_cmd;
+        r
+    });
 
     if args.hwdec {
         let mut dec = VidDecoder::new_hw(&args.inp, 1)?;
@@ -762,64 +1174,284 @@ fn main_with_args(args: &Args) -> Result<(), Xerr> {
     }
     args.dec_strat = Some(get_dec_strat(&inf, crop, args.hwdec, tq));
 
+    mem::set_limit(args.mem_limit);
+    let sizing = mem::size(&args, &inf, &chnks);
+    let auto = args.worker == 0;
+    if auto {
+        args.worker = sizing.worker;
+        args.chnk_buff = sizing.chnk_buff;
+        events::emit(
+            "sizing",
+            format_args!(
+                ",\"workers\":{},\"buff\":{}",
+                args.worker,
+                args.chnk_buff - args.worker
+            ),
+        );
+    }
+    // once `-w auto` is resolved; before the backoff, which ends when a new run begins
+    ctl::serve(&work_dir, args.worker);
+    if auto || args.mem_limit.is_some() {
+        ctl::backoff(sizing.low);
+    }
+
     let prior_secs = get_resume(&work_dir).map_or(0, |r| r.prior_secs);
     init_elapsed(prior_secs);
+    apply_redo(&work_dir, &mut chnks, args.redo.as_ref())?;
+    let redo = manifest::reconcile(
+        &work_dir,
+        &canon_inp,
+        args.encoder.version(),
+        crop,
+        format!("{:?}", unsafe { args.dec_strat.unwrap_unchecked() }),
+        &chnks,
+        pipe_reader.is_none(),
+    )?;
+    if let Some(ref r) = redo {
+        events::emit("redo", format_args!(",\"msg\":{}", json_str(r)));
+    }
     let enc_start = Mono::now();
-    enc_all(&chnks, &inf, &args, &args.inp, &work_dir, pipe_reader);
+    events::phase("encode");
+    let failed = enc_all(&chnks, &inf, &args, &args.inp, &work_dir, pipe_reader);
     let enc_time = enc_start.elapsed() + Durat::from_secs(prior_secs);
+    if ctl::stopping() {
+        park_if_interrupted();
+        restore();
+        IN_ALT_SCREEN.store(false, Relaxed);
+        events::phase("stopped");
+        _ = writeln!(
+            stderr(),
+            "{Y}Stopped after in-flight chunks; rerun the same command to resume{N}"
+        );
+        return Ok(None);
+    }
+    #[cfg(target_os = "linux")]
+    if let Some(e) = pipe_failure() {
+        restore();
+        IN_ALT_SCREEN.store(false, Relaxed);
+        return Err(e.into());
+    }
+    if !failed.is_empty() {
+        restore();
+        IN_ALT_SCREEN.store(false, Relaxed);
+        let list: Vec<_> = failed.iter().map(u16::to_string).collect();
+        let repro = if args.dump_failed {
+            format!("\nRepro: {}", work_dir.join("repro").display())
+        } else {
+            String::new()
+        };
+        return Err(format!(
+            "{} chunk(s) failed after all retries: {}\nLogs: {}{repro}\nFinished chunks are \
+             saved; rerun the same command to encode only these, or add --redo {} -p \"...\" to \
+             change their params",
+            failed.len(),
+            list.join(", "),
+            work_dir.join("logs").display(),
+            list.join(",")
+        )
+        .into());
+    }
+    Ok(Some(Encoded {
+        args,
+        inf,
+        chnks,
+        crop,
+        work_dir,
+        enc_time,
+        redo,
+    }))
+}
 
+// Audio, mux, audit, summary & cleanup; muted on a `--queue` thread, so the box is not drawn
+fn finish(e: Encoded) -> Result<Sum, Xerr> {
+    let Encoded {
+        args,
+        inf,
+        chnks,
+        crop,
+        work_dir,
+        enc_time,
+        redo,
+    } = e;
     let au_tracks = if let Some(ref au_spec) = args.au {
         acq_au(au_spec, &args, &inf, &work_dir)?
     } else {
         Vec::new()
     };
 
+    events::phase("mux");
     merge_out(&args, &work_dir.join("encode"), &inf, &au_tracks, crop)?;
 
     for t in &au_tracks {
         _ = rm_file(&t.1);
     }
 
-    print_sum(&args, &inf, &chnks, crop, enc_time);
-    rm_dir_all(&work_dir)?;
+    // scored from the muxed file: what ships is what gets measured
+    #[cfg(feature = "vship")]
+    let audit = if args.audit.is_empty() {
+        None
+    } else {
+        events::phase("audit");
+        audit::run(&chnks, &inf, &args, crop)?
+    };
+
+    let mut sum = print_sum(&args, &inf, &chnks, crop, enc_time);
+    report::write_report(
+        &args,
+        inf.fps_num as f32 / inf.fps_den as f32,
+        &chnks,
+        &work_dir,
+        sum.bytes,
+        enc_time,
+    );
+    if let Some(r) = redo {
+        sum.notes.push(r);
+    }
+    if args.keep_work {
+        sum.notes.push(format!(
+            "Kept {}; --redo can re-encode chunks from it",
+            work_dir.display()
+        ));
+    }
+    #[cfg(feature = "vship")]
+    {
+        let unmet: Vec<_> = unmet_chunks(&work_dir, &args.tq_and)
+            .iter()
+            .map(usize::to_string)
+            .collect();
+        if !unmet.is_empty() {
+            sum.notes.push(format!(
+                "{} chunk(s) met no --tq-and pick down to the lowest CRF and shipped it anyway: {}",
+                unmet.len(),
+                unmet.join(", ")
+            ));
+        }
+    }
+    #[cfg(feature = "vship")]
+    if let Some(a) = audit {
+        sum.notes.push(format!("Audit: {}", a.display()));
+    }
+    if !events::owns_stdout() && !events::muted() {
+        for n in &sum.notes {
+            println!("{Y}{n}{N}");
+        }
+    }
+    if !args.keep_work {
+        manifest::index_drop(&work_dir);
+        rm_dir_all(&work_dir)?;
+    }
+    Ok(sum)
+}
+
+fn au_report_main(args: &Args) -> Result<(), Xerr> {
+    if events::draw() {
+        print!("\x1b[?1049h\x1b[H\x1b[2J\x1b[?25l");
+        _ = stdout().flush();
+        IN_ALT_SCREEN.store(true, Relaxed);
+    }
+
+    let stem = unsafe { args.out.file_stem().unwrap_unchecked() }.to_string_lossy();
+    let out = args.out.with_file_name(format!("{stem}_au.json"));
+    let streams = args.au.as_ref().map_or(&AuStreams::All, |a| &a.streams);
+    let louds = au_report(streams, &args.inp, &out, 1)?;
+
+    restore();
+    IN_ALT_SCREEN.store(false, Relaxed);
+    if events::owns_stdout() {
+        return Ok(());
+    }
+
+    let db = |v: f32| to_db(v).map_or_else(|| "-inf".into(), |d: f32| format!("{d:.2}"));
+    for l in &louds {
+        println!(
+            "{C}#{:<3}{W}{:<4} {B}{:<9}{Y}I {W}{:>7.2} LUFS  {Y}LRA {W}{:>6.2} LU  {Y}TP {W}{:>7} \
+             dBTP  {Y}SP {W}{:>7} dBFS{N}",
+            l.stream.index,
+            l.stream.lang.as_deref().unwrap_or("und"),
+            layout(l.stream.channels),
+            l.lufs,
+            l.lra,
+            db(l.true_pk),
+            db(l.samp_pk)
+        );
+    }
+    println!("{G}{}{N}", out.display());
     Ok(())
 }
 
-fn print_sum(args: &Args, inf: &VidInf, chnks: &[Chunk], crop: (u32, u32), enc_time: Durat) {
+fn fmt_sz(b: u64) -> String {
+    if b >= 1_000_000_000 {
+        format!("{:.2} GB", b as f32 / 1_000_000_000.0)
+    } else if b >= 1_000_000 {
+        format!("{:.2} MB", b as f32 / 1_000_000.0)
+    } else {
+        format!("{} KB", b / 1_000)
+    }
+}
+
+// What a finished run reports: the `done` event, the run report & the `--queue` table
+pub struct Sum {
+    pub out: PathBuf,
+    pub bytes: u64,
+    pub in_bytes: u64,
+    pub frames: usize,
+    pub secs: f64,
+    pub w: u32,
+    pub h: u32,
+    pub fps: f32,
+    // printed under the box; a `--queue` prints them under the input's row
+    pub notes: Vec<String>,
+}
+
+fn print_sum(args: &Args, inf: &VidInf, chnks: &[Chunk], crop: (u32, u32), enc_time: Durat) -> Sum {
     let tot_frames: usize = chnks.iter().map(|c| c.end - c.start).sum();
     let inp_sz = vid_bytes(&args.inp, args.ranges.as_deref(), tot_frames);
     let out_sz = vid_bytes(&args.out, None, tot_frames);
+    let (final_width, final_height) = (inf.width - crop.1 * 2, inf.height - crop.0 * 2);
+    let fps_rate = inf.fps_num as f32 / inf.fps_den as f32;
+    let sum = Sum {
+        out: args.out.clone(),
+        bytes: out_sz,
+        in_bytes: inp_sz,
+        frames: tot_frames,
+        secs: enc_time.as_secs_f64(),
+        w: final_width,
+        h: final_height,
+        fps: fps_rate,
+        notes: Vec::new(),
+    };
 
-    print!("\x1b[?25h\x1b[?1049l");
-    _ = stdout().flush();
+    if !events::muted() {
+        restore();
+    }
+    events::emit(
+        "done",
+        format_args!(
+            ",\"out\":{},\"bytes\":{out_sz},\"frames\":{tot_frames},\"secs\":{:.3},\"in_bytes\":\
+             {inp_sz},\"w\":{final_width},\"h\":{final_height},\"fps\":{fps_rate:.3}",
+            json_str(&args.out.to_string_lossy()),
+            enc_time.as_secs_f64()
+        ),
+    );
+    if events::owns_stdout() || events::muted() {
+        return sum;
+    }
     let durat = tot_frames as f32 * inf.fps_den as f32 / inf.fps_num as f32;
     let inp_br = inp_sz as f32 * 8.0 / durat / 1000.0;
     let out_br = out_sz as f32 * 8.0 / durat / 1000.0;
     let change = ((out_sz as f32 / inp_sz as f32) - 1.0) * 100.0;
 
-    let fmt_sz = |b: u64| {
-        if b >= 1_000_000_000 {
-            format!("{:.2} GB", b as f32 / 1_000_000_000.0)
-        } else if b >= 1_000_000 {
-            format!("{:.2} MB", b as f32 / 1_000_000.0)
-        } else {
-            format!("{} KB", b / 1_000)
-        }
-    };
-
     let arrow = if change < 0.0 {
         "\u{f06c0}"
     } else {
         "\u{f06c3}"
     };
     let change_color = if change < 0.0 { G } else { R };
-    let fps_rate = inf.fps_num as f32 / inf.fps_den as f32;
     let enc_spd = tot_frames as f32 / enc_time.as_secs_f32();
     let enc_secs = enc_time.as_secs();
     let (eh, em, es) = (enc_secs / 3600, (enc_secs % 3600) / 60, enc_secs % 60);
     let dur_secs = durat as u64;
     let (dh, dm, ds) = (dur_secs / 3600, (dur_secs % 3600) / 60, dur_secs % 60);
-    let (final_width, final_height) = (inf.width - crop.1 * 2, inf.height - crop.0 * 2);
 
     println!(
         "\n{P}┏━━━━━━━━━━━┳━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━┓\n\
@@ -856,14 +1488,72 @@ fn print_sum(args: &Args, inf: &VidInf, chnks: &[Chunk], crop: (u32, u32), enc_t
         enc_spd,
         ""
     );
+    sum
+}
+
+fn set_signals() {
+    let h: usize = unsafe { transmute_copy(&(exit_restore as extern "C" fn(i32))) };
+    signal(SIGSEGV, h);
+    let h: usize = unsafe { transmute_copy(&(on_intr as extern "C" fn(i32))) };
+    for sig in [SIGINT, SIGTERM, SIGHUP] {
+        signal(sig, h);
+    }
+    // a killed encoder child must surface as EPIPE, not take xav down with it
+    signal(SIGPIPE, SIG_IGN);
+}
+
+// One `--queue` input up to its mux, run in this process so vship & the GPU are set up once
+fn run_one(argv: &[String]) -> Result<Option<Encoded>, Xerr> {
+    let args = get_args(argv, true)?;
+    if args.queue.is_some() {
+        return Err("--queue can not be given per queued input".into());
+    }
+    // a no-op once `queue::run` set the device; retried here so a GPU error fails this row only
+    #[cfg(feature = "vship")]
+    if args.tq.is_some() || !args.audit.is_empty() {
+        init_device()?;
+    }
+    events::init(args.progress);
+    let res = if args.au_report {
+        au_report_main(&args).map(|()| None)
+    } else {
+        encode(&args, argv)
+    };
+    restore();
+    IN_ALT_SCREEN.store(false, Relaxed);
+    res
 }
 
 fn run() -> Result<(), Xerr> {
+    let argv: Vec<String> = env_args().collect();
     let args = match parse_args() {
         Ok(a) => a,
         Err(Help) => return Ok(()),
         Err(e) => return Err(e),
     };
+    // everything else on the command line is the base every queued input is encoded with
+    if let Some((q, ref src)) = args.queue {
+        let base: Vec<String> = argv
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i + 1 != q && i != q)
+            .map(|(_, a)| a.clone())
+            .collect();
+        UNWIND.store(true, Relaxed);
+        set_signals();
+        watch_intr();
+        let res = queue::run(src, &base);
+        if INTERRUPTED.load(Relaxed) {
+            events::phase("interrupted");
+            _ = writeln!(
+                stderr(),
+                "{Y}Interrupted; finished chunks are saved, rerun the same command to resume{N}"
+            );
+            exit(130);
+        }
+        return res;
+    }
+    events::init(args.progress);
 
     #[cfg(any(not(target_os = "linux"), test))]
     {
@@ -876,13 +1566,16 @@ fn run() -> Result<(), Xerr> {
         }));
     }
 
-    let h: usize = unsafe { transmute_copy(&(exit_restore as extern "C" fn(i32))) };
-    signal(SIGINT, h);
-    signal(SIGSEGV, h);
+    set_signals();
+    watch_intr();
 
-    if let Err(e) = main_with_args(&args) {
-        print!("\x1b[?1049l");
-        _ = stdout().flush();
+    let res = if args.au_report {
+        au_report_main(&args)
+    } else {
+        main_with_args(&args, &argv).map(drop)
+    };
+    if let Err(e) = res {
+        restore();
         fatal(format_args!("{e}\n{}, FAIL", args.out.display()));
     }
 
@@ -0,0 +1,367 @@
+#[cfg(target_os = "linux")]
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::Write as _,
+    hash::{Hash as _, Hasher as _},
+};
+
+use crate::{
+    chunk::{Chunk, get_resume, save_resume},
+    error::Xerr,
+    fs::{
+        File, create_dir_all, metadata, mtime, read_at, read_dir, read_to_string as read_to_str,
+        remove_file, rename, write,
+    },
+    path::{Path, PathBuf},
+    process::{self, var},
+    util::Fnv,
+};
+
+// `manifest.txt` in the tmp dir: what a resume must still match before finished chunks are trusted
+const NAME: &str = "manifest.txt";
+const SAMPLE: usize = 64 * 1024;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Src {
+    size: u64,
+    mtime: u64,
+    sample: u64,
+}
+
+impl Src {
+    // Same content: mtime alone changes on copies & touches
+    const fn same(&self, o: &Self) -> bool {
+        self.size == o.size && self.sample == o.sample
+    }
+
+    fn key(&self) -> String {
+        format!("{:x}-{:016x}", self.size, self.sample)
+    }
+}
+
+// Size, mtime & a hash of three 64 KiB blocks at head, middle & tail
+fn fingerprint(path: &Path) -> Result<Src, Xerr> {
+    let size = metadata(path)?;
+    let f = File::open(path)?;
+    let mut h = Fnv::new();
+    size.hash(&mut h);
+    let mut buf = vec![0u8; SAMPLE];
+    let tail = size.saturating_sub(SAMPLE as u64);
+    for off in [0, tail / 2, tail] {
+        let n = read_at(&f, &mut buf, off)?;
+        h.write(&buf[..n]);
+    }
+    Ok(Src {
+        size,
+        mtime: mtime(path)?,
+        sample: h.finish(),
+    })
+}
+
+fn esc(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unesc(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut it = s.chars();
+    while let Some(c) = it.next() {
+        match (c, c == '\\' && it.as_str().starts_with('n')) {
+            ('\\', true) => {
+                it.next();
+                out.push('\n');
+            }
+            ('\\', false) => out.extend(it.next()),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn chunk_key(c: &Chunk) -> (u16, usize, usize, u64) {
+    let mut h = Fnv::new();
+    c.params.hash(&mut h);
+    (c.idx, c.start, c.end, h.finish())
+}
+
+struct Manifest {
+    src: PathBuf,
+    fp: Src,
+    args: Vec<String>,
+    enc: String,
+    crop: Option<(u32, u32)>,
+    strat: String,
+    chnks: Vec<(u16, usize, usize, u64)>,
+}
+
+impl Manifest {
+    fn load(work_dir: &Path) -> Option<Self> {
+        let content = read_to_str(work_dir.join(NAME)).ok()?;
+        let mut m = Self {
+            src: PathBuf::new(),
+            fp: Src {
+                size: 0,
+                mtime: 0,
+                sample: 0,
+            },
+            args: Vec::new(),
+            enc: String::new(),
+            crop: None,
+            strat: String::new(),
+            chnks: Vec::new(),
+        };
+        for line in content.lines() {
+            let (k, v) = line.split_once(' ').unwrap_or((line, ""));
+            let mut nums = v.split_whitespace();
+            let mut num = || nums.next()?.parse::<u64>().ok();
+            match k {
+                "src" => m.src = PathBuf::from(unesc(v)),
+                "size" => m.fp.size = num()?,
+                "mtime" => m.fp.mtime = num()?,
+                "sample" => m.fp.sample = u64::from_str_radix(v, 16).ok()?,
+                "arg" => m.args.push(unesc(v)),
+                "enc" => m.enc = unesc(v),
+                "crop" => m.crop = Some((num()? as u32, num()? as u32)),
+                "strat" => m.strat = v.into(),
+                "chunk" => m
+                    .chnks
+                    .push((num()? as u16, num()? as usize, num()? as usize, num()?)),
+                _ => {}
+            }
+        }
+        Some(m)
+    }
+
+    fn save(&self, work_dir: &Path) -> Result<(), Xerr> {
+        let mut s = String::new();
+        _ = writeln!(s, "src {}", esc(&self.src.to_string_lossy()));
+        _ = writeln!(s, "size {}", self.fp.size);
+        _ = writeln!(s, "mtime {}", self.fp.mtime);
+        _ = writeln!(s, "sample {:x}", self.fp.sample);
+        for a in &self.args {
+            _ = writeln!(s, "arg {}", esc(a));
+        }
+        if !self.enc.is_empty() {
+            _ = writeln!(s, "enc {}", esc(&self.enc));
+        }
+        if let Some((v, h)) = self.crop {
+            _ = writeln!(s, "crop {v} {h}");
+        }
+        if !self.strat.is_empty() {
+            _ = writeln!(s, "strat {}", self.strat);
+        }
+        for &(idx, st, end, h) in &self.chnks {
+            _ = writeln!(s, "chunk {idx} {st} {end} {h}");
+        }
+        write(work_dir.join(NAME), s)?;
+        Ok(())
+    }
+}
+
+// Fresh tmp dir: record the source & the exact argv, one argument per line
+pub fn create(work_dir: &Path, canon: &Path, args: Vec<String>) -> Result<(), Xerr> {
+    let fp = fingerprint(canon)?;
+    Manifest {
+        src: canon.to_path_buf(),
+        fp,
+        args,
+        enc: String::new(),
+        crop: None,
+        strat: String::new(),
+        chnks: Vec::new(),
+    }
+    .save(work_dir)?;
+    index_put(&fp.key(), Some(&work_dir.canonicalize()?));
+    Ok(())
+}
+
+// `<state>/xav/index/<fingerprint>`: the tmp dir holding that content, so a tmp dir
+// relocated with `--work-dir` is found again whatever the resuming command says. One file
+// per key: concurrent runs never rewrite each other's entries
+fn index_dir() -> Option<PathBuf> {
+    var("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var("HOME").map(|h| Path::new(&h).join(".local/state")))
+        .map(|d| d.join("xav").join("index"))
+}
+
+fn index_get(path: &Path) -> Option<PathBuf> {
+    read_to_str(path).ok().map(PathBuf::from)
+}
+
+// Sets (`Some`) or clears the entry for `key`; entries whose dir is gone are pruned. A set
+// goes through a tmp file & rename, so a reader never sees half a path
+fn index_put(key: &str, dir: Option<&Path>) {
+    let Some(idx) = index_dir() else {
+        return;
+    };
+    if let Ok(ents) = read_dir(&idx) {
+        for p in ents.flatten().map(|e| e.path()) {
+            if p.extension().is_none() && index_get(&p).is_some_and(|d| !d.exists()) {
+                _ = remove_file(&p);
+            }
+        }
+    }
+    let path = idx.join(key);
+    let Some(d) = dir else {
+        _ = remove_file(&path);
+        return;
+    };
+    _ = create_dir_all(&idx);
+    let tmp = idx.join(format!("{key}.{}", process::id()));
+    if write(&tmp, d.to_string_lossy().as_bytes()).is_ok() && rename(&tmp, &path).is_err() {
+        _ = remove_file(&tmp);
+    }
+}
+
+// Indexed tmp dir of this content, unless it belongs to a copy that still exists elsewhere
+pub fn index_find(canon: &Path) -> Option<PathBuf> {
+    let key = fingerprint(canon).ok()?.key();
+    let d = index_get(&index_dir()?.join(key))?;
+    let m = Manifest::load(&d)?;
+    (m.src.as_path() == canon || !m.src.exists()).then_some(d)
+}
+
+// Content key of a source, shared by anything kept per input outside its tmp dir
+#[cfg(feature = "vship")]
+pub fn src_key(path: &Path) -> Option<String> {
+    fingerprint(path).ok().map(|f| f.key())
+}
+
+// Finished & removed: the entry would only point at nothing
+pub fn index_drop(work_dir: &Path) {
+    if let Some(m) = Manifest::load(work_dir) {
+        index_put(&m.fp.key(), None);
+    }
+}
+
+pub fn saved_args(work_dir: &Path) -> Option<Vec<String>> {
+    Manifest::load(work_dir)
+        .map(|m| m.args)
+        .filter(|a| !a.is_empty())
+}
+
+// A moved or renamed source leaves its tmp dir behind under the old path hash;
+// take it over when its source is gone & the content fingerprint matches
+pub fn adopt(canon: &Path, work_dir: &Path) {
+    if work_dir.exists() {
+        return;
+    }
+    let Some(Ok(entries)) = canon.parent().map(read_dir) else {
+        return;
+    };
+    let mut fp = None;
+    for e in entries.flatten() {
+        let dir = e.path();
+        let is_tmp = dir
+            .file_name()
+            .and_then(Path::to_str)
+            .is_some_and(|n| n.len() == 8 && n.starts_with('.'));
+        let Some(m) = is_tmp.then(|| Manifest::load(&dir)).flatten() else {
+            continue;
+        };
+        if m.src.exists() {
+            continue;
+        }
+        if fp.is_none() {
+            fp = fingerprint(canon).ok();
+        }
+        if fp.is_some_and(|f| f.same(&m.fp)) && rename(&dir, work_dir).is_ok() {
+            return;
+        }
+    }
+}
+
+// Refuses a tmp dir whose source content changed; follows a path/mtime-only change
+pub fn check_src(work_dir: &Path, canon: &Path) -> Result<(), Xerr> {
+    let Some(mut m) = Manifest::load(work_dir) else {
+        return Ok(());
+    };
+    let fp = fingerprint(canon)?;
+    if !fp.same(&m.fp) {
+        return Err(format!(
+            "Source changed since {} was created; delete it to start over",
+            work_dir.display()
+        )
+        .into());
+    }
+    if fp != m.fp || m.src.as_path() != canon {
+        m.fp = fp;
+        m.src = canon.to_path_buf();
+        m.save(work_dir)?;
+    }
+    Ok(())
+}
+
+// Drops finished chunks the resolved settings no longer match: encoder version,
+// crop or decode path redo everything; scene edits redo only the chunks they touch.
+// Returns a note on what is redone. Call after `init_elapsed` so `done.txt` keeps its time
+pub fn reconcile(
+    work_dir: &Path,
+    canon: &Path,
+    enc: String,
+    crop: (u32, u32),
+    strat: String,
+    chnks: &[Chunk],
+    can_redo: bool,
+) -> Result<Option<String>, Xerr> {
+    let mut m = Manifest::load(work_dir).map_or_else(
+        || {
+            Ok::<_, Xerr>(Manifest {
+                src: canon.to_path_buf(),
+                fp: fingerprint(canon)?,
+                args: Vec::new(),
+                enc: String::new(),
+                crop: None,
+                strat: String::new(),
+                chnks: Vec::new(),
+            })
+        },
+        Ok,
+    )?;
+    let keys: Vec<_> = chnks.iter().map(chunk_key).collect();
+    let why = if !m.enc.is_empty() && m.enc != enc {
+        Some(format!("encoder changed ({} -> {enc})", m.enc))
+    } else if m.crop.is_some_and(|c| c != crop) {
+        Some("crop changed".into())
+    } else if !m.strat.is_empty() && m.strat != strat {
+        Some("decode path changed".into())
+    } else {
+        None
+    };
+
+    let mut note = None;
+    if let Some(mut res) = get_resume(work_dir) {
+        let before = res.chnks_done.len();
+        res.chnks_done.retain(|c| {
+            why.is_none()
+                && (m.chnks.is_empty()
+                    || keys
+                        .iter()
+                        .find(|k| k.0 == c.idx)
+                        .is_some_and(|k| m.chnks.contains(k)))
+        });
+        let redo = before - res.chnks_done.len();
+        if redo > 0 {
+            if !can_redo {
+                return Err(
+                    "Finished chunks no longer match settings or scenes; pipe input can not redo \
+                     them, delete the tmp dir to start over"
+                        .into(),
+                );
+            }
+            save_resume(&res, work_dir)?;
+            note = Some(format!(
+                "{redo} finished chunk(s) re-encode: {}",
+                why.as_deref().unwrap_or("scenes changed")
+            ));
+        }
+    }
+
+    m.enc = enc;
+    m.crop = Some(crop);
+    m.strat = strat;
+    m.chnks = keys;
+    m.save(work_dir)?;
+    Ok(note)
+}
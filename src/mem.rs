@@ -0,0 +1,177 @@
+use core::sync::atomic::{AtomicU64, Ordering::Relaxed};
+
+use crate::{
+    Args,
+    chunk::Chunk,
+    encoder::Encoder::{self, Avm, SvtAv1, Vvenc, X264, X265},
+    error::Xerr,
+    ffms::VidInf,
+    fs::read_to_string as read_to_str,
+    pipeline::Pipeline,
+    thread::available_parallelism,
+};
+
+// `-w auto` & `--mem-limit`: workers & the decoded-chunk buffer are sized from CPUs,
+// free RAM/cgroup room & what one worker holds: its encoder plus one decoded chunk
+static LIMIT: AtomicU64 = AtomicU64::new(u64::MAX);
+// OS, decoder & muxer share what is left over
+const SPARE_PCT: u64 = 10;
+// `_SC_PAGESIZE` on Linux
+const SC_PAGESIZE: i32 = 30;
+
+unsafe extern "C" {
+    fn sysconf(name: i32) -> i64;
+}
+
+// `8G`, `512M`, `2048K`, `1T` (powers of 1024) or plain bytes
+pub fn parse_mem(s: &str) -> Result<u64, Xerr> {
+    let s = s.trim();
+    let (num, mul) = match s.as_bytes().last().map(u8::to_ascii_uppercase) {
+        Some(b'K') => (&s[..s.len() - 1], 1u64 << 10),
+        Some(b'M') => (&s[..s.len() - 1], 1 << 20),
+        Some(b'G') => (&s[..s.len() - 1], 1 << 30),
+        Some(b'T') => (&s[..s.len() - 1], 1 << 40),
+        _ => (s, 1),
+    };
+    match num.parse::<f64>() {
+        Ok(v) if v > 0.0 => Ok((v * mul as f64) as u64),
+        _ => Err(format!("Invalid --mem-limit: {s} (use e.g. 8G or 512M)").into()),
+    }
+}
+
+fn kv(content: &str, key: &str) -> Option<u64> {
+    content
+        .lines()
+        .find_map(|l| l.strip_prefix(key))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn meminfo_avail() -> Option<u64> {
+    kv(&read_to_str("/proc/meminfo").ok()?, "MemAvailable:").map(|k| k * 1024)
+}
+
+// cgroup v2 `memory.max - memory.current` of our own cgroup
+fn cgroup_room() -> Option<u64> {
+    let cg = read_to_str("/proc/self/cgroup").ok()?;
+    let rel = cg.lines().find_map(|l| l.strip_prefix("0::"))?;
+    let dir = format!("/sys/fs/cgroup{}", rel.trim_end_matches('/'));
+    room(
+        &read_to_str(format!("{dir}/memory.max")).ok()?,
+        &read_to_str(format!("{dir}/memory.current")).ok()?,
+    )
+}
+
+// `max` is no limit
+fn room(max: &str, cur: &str) -> Option<u64> {
+    let max: u64 = max.trim().parse().ok()?;
+    Some(max.saturating_sub(cur.trim().parse().ok()?))
+}
+
+fn own_rss() -> u64 {
+    let page = unsafe { sysconf(SC_PAGESIZE) };
+    read_to_str("/proc/self/statm")
+        .ok()
+        .and_then(|s| s.split_whitespace().nth(1)?.parse::<u64>().ok())
+        .map_or(0, |pages| pages * page.max(0) as u64)
+}
+
+// Bytes we can still take: free RAM, cgroup room & `--mem-limit` minus what we already hold
+pub fn headroom() -> u64 {
+    let limit = LIMIT.load(Relaxed);
+    let own = if limit == u64::MAX {
+        u64::MAX
+    } else {
+        limit.saturating_sub(own_rss())
+    };
+    meminfo_avail()
+        .unwrap_or(u64::MAX)
+        .min(cgroup_room().unwrap_or(u64::MAX))
+        .min(own)
+}
+
+fn opt(params: &str, name: &str) -> Option<i32> {
+    let mut it = params.split_whitespace();
+    it.find(|t| *t == name)?;
+    it.next()?.parse().ok()
+}
+
+// Rough per-pixel resident size of one encoder instance, erring high: slower
+// SVT-AV1 presets keep more reference & analysis buffers, vvenc/avm the most
+fn enc_bytes(enc: Encoder, params: &str, px: u64) -> u64 {
+    let per_px = match enc {
+        SvtAv1 => match opt(params, "--preset").unwrap_or(4) {
+            ..=2 => 64,
+            3..=5 => 48,
+            _ => 36,
+        },
+        X264 => 24,
+        X265 => 40,
+        Vvenc => 96,
+        Avm => 128,
+    };
+    px * per_px + (64 << 20)
+}
+
+// CPU threads one worker keeps busy: SVT-AV1 follows `--lp`; the others run single-threaded
+fn enc_threads(enc: Encoder, params: &str) -> usize {
+    match (enc, opt(params, "--lp")) {
+        (SvtAv1, Some(1)) => 1,
+        (SvtAv1, Some(2)) => 2,
+        (SvtAv1, Some(3)) => 4,
+        (SvtAv1, Some(4)) => 6,
+        (SvtAv1, _) => 8,
+        _ => 1,
+    }
+}
+
+pub struct Sizing {
+    pub worker: usize,
+    pub chnk_buff: usize,
+    // one worker's worth: below this much headroom a worker is parked
+    pub low: u64,
+}
+
+// `-b` (if given) stays on top of the computed workers; otherwise spare memory
+// buffers up to half as many chunks again. `low` also serves a fixed `-w` with `--mem-limit`
+pub fn size(args: &Args, inf: &VidInf, chnks: &[Chunk]) -> Sizing {
+    let pipe = Pipeline::new(
+        inf,
+        unsafe { args.dec_strat.unwrap_unchecked() },
+        #[cfg(feature = "vship")]
+        args.tq.as_deref(),
+    );
+    let max_len = chnks.iter().map(|c| c.end - c.start).max().unwrap_or(1) as u64;
+    let chunk = pipe.frame_sz as u64 * max_len;
+    let enc = enc_bytes(
+        args.encoder,
+        &args.params,
+        (pipe.final_w * pipe.final_h) as u64,
+    );
+    let budget = headroom() / 100 * (100 - SPARE_PCT);
+
+    let cpu = (available_parallelism() / enc_threads(args.encoder, &args.params)).max(1);
+    let worker = cpu.min((budget / (enc + chunk)) as usize).max(1);
+    let left = budget.saturating_sub(worker as u64 * (enc + chunk));
+    let extra = args
+        .buff
+        .unwrap_or_else(|| ((left / chunk.max(1)) as usize).min(worker.div_ceil(2)));
+    Sizing {
+        worker,
+        chnk_buff: worker + extra,
+        low: enc + chunk,
+    }
+}
+
+pub fn set_limit(limit: Option<u64>) {
+    LIMIT.store(limit.unwrap_or(u64::MAX), Relaxed);
+}
+
+#[cfg(test)]
+pub mod test_access {
+    pub fn room(max: &str, cur: &str) -> Option<u64> {
+        super::room(max, cur)
+    }
+}
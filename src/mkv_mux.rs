@@ -1,6 +1,6 @@
 use alloc::borrow::Cow;
 #[cfg(target_os = "linux")]
-use alloc::{borrow::ToOwned as _, string::String, vec::Vec};
+use alloc::{string::String, vec::Vec};
 #[cfg(target_arch = "x86_64")]
 use core::arch::x86_64::_mm_sfence;
 use core::{
@@ -17,6 +17,7 @@ use crate::{
     copy::{Chapter, Stream, codec_map},
     encoder::Encoder::{self, Vvenc, X264, X265},
     error::Xerr,
+    events::draw,
     ffms::{AVMEDIA_TYPE_AUDIO, AVMEDIA_TYPE_SUBTITLE, VidInf},
     io::print_fmt,
     lang::lang_name,
@@ -385,7 +386,9 @@ pub fn mux_mkv(
     };
     let mut progs = ProgsBar::new();
     write_mux(out, &mux, &mut progs)?;
-    println!();
+    if draw() {
+        println!();
+    }
     Ok(())
 }
 
@@ -596,8 +599,7 @@ fn build_audio_tracks(
             default,
             name,
             lang: tag,
-            settings: "vbr=1 vbr-constraint=0 complexity=10 bandwidth=fullband application=audio"
-                .to_owned(),
+            settings: entry.0.opus.settings(),
             encoder: opus_version(),
             codec_id: b"A_OPUS",
             codec_name: b"Opus interactive speech and audio codec",
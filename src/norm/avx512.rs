@@ -3,6 +3,8 @@ unsafe extern "C" {
     fn xav_mix7(src: *const f32, dst: *mut f32, n: usize);
     fn xav_mix8(src: *const f32, dst: *mut f32, n: usize);
     fn xav_loud(src: *const f32, n: usize, stride: usize, st: *mut f64, out: *mut f64);
+    fn xav_tp(src: *const f32, n: usize, out: *mut f32);
+    fn xav_gain(dst: *mut f32, g: *const f32, n: usize);
 }
 
 #[inline(always)]
@@ -19,3 +21,13 @@ fn mix7(src: &[f32], dst: &mut [f32], n: usize) {
 fn mix8(src: &[f32], dst: &mut [f32], n: usize) {
     unsafe { xav_mix8(src.as_ptr(), dst.as_mut_ptr(), n) };
 }
+
+#[inline(always)]
+fn tp(src: &[f32], at: usize, out: &mut [f32]) {
+    unsafe { xav_tp(src.as_ptr().add(at * 2), out.len(), out.as_mut_ptr()) };
+}
+
+#[inline(always)]
+fn gain(dst: &mut [f32], g: &[f32]) {
+    unsafe { xav_gain(dst.as_mut_ptr(), g.as_ptr(), g.len()) };
+}
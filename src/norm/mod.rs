@@ -5,10 +5,10 @@ include!("avx2.rs");
 
 #[cfg(target_os = "linux")]
 use alloc::vec::Vec;
-use core::hint::cold_path;
+use core::{hint::cold_path, iter::repeat_n};
 
 #[cfg(all(target_os = "linux", not(test)))]
-use crate::fmath::{FloatExt as _, Log10 as _};
+use crate::fmath::{FloatExt as _, Log10 as _, Powf as _};
 
 #[inline(always)]
 pub fn downmix(src: &[f32], dst: &mut [f32], ch: usize, n: usize) {
@@ -50,11 +50,20 @@ fn loud(src: *const f32, n: usize, stride: usize, st: &mut [f64; 32], out: &mut
 }
 
 pub fn measure(stereo: &[f32]) -> (f32, f32) {
+    let s = blocks(stereo);
+    if s.is_empty() {
+        cold_path();
+        return (-70.0, 0.0);
+    }
+    (integrated(&s) as f32, range(&s) as f32)
+}
+
+fn blocks(stereo: &[f32]) -> Vec<f64> {
     let total = stereo.len() / 2;
     let nsub = total / HOP;
     if nsub == 0 {
         cold_path();
-        return (-70.0, 0.0);
+        return Vec::new();
     }
     let per = nsub / 4;
     let extra = nsub - 4 * per;
@@ -80,7 +89,7 @@ pub fn measure(stereo: &[f32]) -> (f32, f32) {
     }
     unsafe { s.set_len(4 * per) };
     remainder(stereo, 4 * per, extra, &st, &mut s);
-    (integrated(&s) as f32, range(&s) as f32)
+    s
 }
 
 #[cold]
@@ -177,3 +186,253 @@ fn range(s: &[f64]) -> f64 {
     let lo = q[relgated + rs1.mul_add(0.10, 0.5) as usize];
     10.0 * (hi / lo).log10()
 }
+
+fn prefix(s: &[f64]) -> Vec<f64> {
+    let mut pre = Vec::with_capacity(s.len() + 1);
+    pre.push(0f64);
+    for &e in s {
+        pre.push(pre[pre.len() - 1] + e);
+    }
+    pre
+}
+
+const ST_HALF: usize = 15;
+const GATE_LU: f64 = 20.0;
+const ATTACK: f32 = 0.5;
+const RELEASE: f32 = 0.9;
+
+pub fn compress(stereo: &mut [f32], lra: f32, target: f32) {
+    let s = blocks(stereo);
+    if s.len() < 2 * ST_HALF {
+        cold_path();
+        return;
+    }
+    let integ = integrated(&s);
+    let slope = f64::from(target / lra) - 1.0;
+    let pre = prefix(&s);
+    let mut cur = 0f32;
+    let lin: Vec<f32> = (0..s.len())
+        .map(|j| {
+            let lo = j.saturating_sub(ST_HALF);
+            let hi = (j + ST_HALF).min(s.len());
+            let ms = (pre[hi] - pre[lo]) / ((hi - lo) * HOP) as f64;
+            if ms >= ABS_Z {
+                let st = 0.691f64.mul_add(-1.0, 10.0 * ms.log10());
+                if st >= integ - GATE_LU {
+                    let want = ((st - integ) * slope) as f32;
+                    let k = if want < cur { ATTACK } else { RELEASE };
+                    cur = k.mul_add(cur - want, want);
+                }
+            }
+            10f32.powf(cur / 20.0)
+        })
+        .collect();
+    let n = stereo.len() / 2;
+    let mut g: Vec<f32> = Vec::with_capacity(n);
+    let last = lin.len() - 1;
+    let mid = HOP / 2;
+    g.extend(repeat_n(lin[0], mid));
+    for j in 0..last {
+        let step = (lin[j + 1] - lin[j]) / HOP as f32;
+        g.extend((0..HOP).map(|i| step.mul_add(i as f32, lin[j])));
+    }
+    g.resize(n, lin[last]);
+    gain(stereo, &g);
+}
+
+const TAPS: usize = 12;
+const LOOK: usize = 96;
+const REL: f32 = 0.999_583;
+const FIR: [[f32; TAPS]; 4] = [
+    [
+        0.001_708_984_4,
+        0.010_986_328,
+        -0.019_653_32,
+        0.033_203_125,
+        -0.059_448_242,
+        0.137_329_1,
+        0.972_167_97,
+        -0.102_294_92,
+        0.047_607_42,
+        -0.026_611_328,
+        0.014_892_578,
+        -0.008_300_781,
+    ],
+    [
+        -0.029_174_805,
+        0.029_296_875,
+        -0.051_757_812,
+        0.089_111_33,
+        -0.166_503_9,
+        0.465_087_9,
+        0.779_785_16,
+        -0.200_317_38,
+        0.101_562_5,
+        -0.058_227_54,
+        0.033_081_055,
+        -0.018_920_898,
+    ],
+    [
+        -0.018_920_898,
+        0.033_081_055,
+        -0.058_227_54,
+        0.101_562_5,
+        -0.200_317_38,
+        0.779_785_16,
+        0.465_087_9,
+        -0.166_503_9,
+        0.089_111_33,
+        -0.051_757_812,
+        0.029_296_875,
+        -0.029_174_805,
+    ],
+    [
+        -0.008_300_781,
+        0.014_892_578,
+        -0.026_611_328,
+        0.047_607_42,
+        -0.102_294_92,
+        0.972_167_97,
+        0.137_329_1,
+        -0.059_448_242,
+        0.033_203_125,
+        -0.019_653_32,
+        0.010_986_328,
+        0.001_708_984_4,
+    ],
+];
+
+pub fn limit(stereo: &mut [f32], ceil: f32) {
+    let n = stereo.len() / 2;
+    let head = n.min(TAPS - 1);
+    let mut r: Vec<f32> = vec![0.0; n];
+    for (i, v) in r[..head].iter_mut().enumerate() {
+        *v = tp_head(stereo, i);
+    }
+    if n > head {
+        tp(stereo, head, &mut r[head..]);
+    }
+    let mut hot = false;
+    for v in &mut r {
+        *v = if *v > ceil {
+            hot = true;
+            ceil / *v
+        } else {
+            1.0
+        };
+    }
+    if !hot {
+        return;
+    }
+    let mut g = hold(&r, TAPS, LOOK + TAPS);
+    drop(r);
+    ramp(&mut g);
+    gain(stereo, &g);
+}
+
+const PEAK_BLK: usize = 1 << 16;
+
+pub fn peaks(stereo: &[f32]) -> (f32, f32) {
+    let n = stereo.len() / 2;
+    let head = n.min(TAPS - 1);
+    let mut true_pk = (0..head).map(|i| tp_head(stereo, i)).fold(0f32, f32::max);
+    let mut buf = vec![0f32; PEAK_BLK];
+    let mut at = head;
+    while at < n {
+        let len = PEAK_BLK.min(n - at);
+        tp(stereo, at, &mut buf[..len]);
+        true_pk = buf[..len].iter().fold(true_pk, |m, &v| m.max(v));
+        at += len;
+    }
+    let samp_pk = stereo.iter().fold(0f32, |m, &v| m.max(v.abs()));
+    (true_pk.max(samp_pk), samp_pk)
+}
+
+pub fn timeline(stereo: &[f32], step: usize, win: usize) -> Vec<Option<f32>> {
+    let s = blocks(stereo);
+    let pre = prefix(&s);
+    (0..s.len().div_ceil(step))
+        .map(|k| {
+            let hi = ((k + 1) * step).min(s.len());
+            let lo = hi.saturating_sub(win);
+            let ms = (pre[hi] - pre[lo]) / ((hi - lo) * HOP) as f64;
+            (ms >= ABS_Z).then(|| 0.691f64.mul_add(-1.0, 10.0 * ms.log10()) as f32)
+        })
+        .collect()
+}
+
+#[cold]
+#[inline(never)]
+fn tp_head(stereo: &[f32], i: usize) -> f32 {
+    let mut pk = 0f32;
+    for ph in &FIR {
+        for c in 0..2 {
+            let mut acc = 0f32;
+            for (k, &h) in ph.iter().enumerate().take(i + 1) {
+                acc = h.mul_add(stereo[(i - k) * 2 + c], acc);
+            }
+            pk = pk.max(acc.abs());
+        }
+    }
+    pk
+}
+
+fn hold(r: &[f32], back: usize, fwd: usize) -> Vec<f32> {
+    let n = r.len();
+    let mut q: Vec<usize> = Vec::new();
+    let mut qh = 0usize;
+    let mut next = 0usize;
+    let mut h = Vec::with_capacity(n);
+    for i in 0..n {
+        let hi = (i + fwd).min(n - 1);
+        while next <= hi {
+            while q.len() > qh && r[q[q.len() - 1]] >= r[next] {
+                q.pop();
+            }
+            q.push(next);
+            next += 1;
+        }
+        while q[qh] + back < i {
+            qh += 1;
+        }
+        h.push(r[q[qh]]);
+    }
+    h
+}
+
+fn ramp(g: &mut [f32]) {
+    let mut win = [1f32; LOOK + 1];
+    let mut sum = (LOOK + 1) as f64;
+    let mut prev = 1f32;
+    for (i, v) in g.iter_mut().enumerate() {
+        let w = &mut win[i % (LOOK + 1)];
+        sum += f64::from(*v - *w);
+        *w = *v;
+        let a = (sum / (LOOK + 1) as f64) as f32;
+        prev = if a < prev {
+            a
+        } else {
+            REL.mul_add(prev - a, a)
+        };
+        *v = prev;
+    }
+}
+
+#[cfg(test)]
+pub mod test_access {
+    use super::*;
+
+    pub const HEAD: usize = TAPS - 1;
+
+    pub fn tp_simd(stereo: &[f32], at: usize, out: &mut [f32]) {
+        tp(stereo, at, out);
+    }
+
+    pub fn tp_scalar(stereo: &[f32], i: usize) -> f32 {
+        tp_head(stereo, i)
+    }
+
+    pub fn gain_simd(stereo: &mut [f32], g: &[f32]) {
+        gain(stereo, g);
+    }
+}
@@ -1,20 +1,103 @@
 #[cfg(target_os = "linux")]
-use alloc::{string::String, vec::Vec};
+use alloc::{string::String, vec, vec::Vec};
 use core::ffi::{CStr, c_char, c_int};
 
 use crate::{byte_range::ByteRange, error::Xerr};
 
 const FS: i32 = 48000;
-pub const FRAME: usize = 960;
+pub const MAX_PKT: usize = 1275 * 3 + 7;
 
+const APPLICATION_VOIP: c_int = 2048;
 const APPLICATION_AUDIO: c_int = 2049;
+const APPLICATION_RESTRICTED_LOWDELAY: c_int = 2051;
 const BANDWIDTH_FULLBAND: c_int = 1105;
 const SET_BITRATE: c_int = 4002;
 const SET_MAX_BANDWIDTH: c_int = 4004;
 const SET_VBR: c_int = 4006;
 const SET_COMPLEXITY: c_int = 4010;
+const SET_INBAND_FEC: c_int = 4012;
+const SET_PACKET_LOSS_PERC: c_int = 4014;
 const SET_VBR_CONSTRAINT: c_int = 4020;
 const GET_LOOKAHEAD: c_int = 4027;
+const GET_DEMIXING_MATRIX_GAIN: c_int = 6001;
+const GET_DEMIXING_MATRIX_SIZE: c_int = 6003;
+const GET_DEMIXING_MATRIX: c_int = 6005;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum App {
+    Voip,
+    Audio,
+    LowDelay,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Mapping {
+    Auto,
+    Discrete,
+    Ambisonic,
+    Projection,
+}
+
+#[derive(Clone, Copy)]
+pub struct OpusOpts {
+    pub app: App,
+    pub frame: usize,
+    pub complexity: u8,
+    pub cvbr: bool,
+    pub loss: u8,
+    pub mapping: Mapping,
+}
+
+impl OpusOpts {
+    pub const fn default() -> Self {
+        Self {
+            app: App::Audio,
+            frame: 960,
+            complexity: 10,
+            cvbr: false,
+            loss: 0,
+            mapping: Mapping::Auto,
+        }
+    }
+
+    pub const fn family(&self, channels: u8) -> u8 {
+        match self.mapping {
+            Mapping::Auto if channels <= 2 => 0,
+            Mapping::Auto if channels <= 8 => 1,
+            Mapping::Auto | Mapping::Discrete => 255,
+            Mapping::Ambisonic => 2,
+            Mapping::Projection => 3,
+        }
+    }
+
+    pub const fn surround(&self, channels: u8) -> bool {
+        self.family(channels) == 1
+    }
+
+    const fn app_ctl(&self) -> c_int {
+        match self.app {
+            App::Voip => APPLICATION_VOIP,
+            App::Audio => APPLICATION_AUDIO,
+            App::LowDelay => APPLICATION_RESTRICTED_LOWDELAY,
+        }
+    }
+
+    pub fn settings(&self) -> String {
+        format!(
+            "vbr=1 vbr-constraint={} complexity={} bandwidth=fullband application={} \
+             frame-duration={} packet-loss={}",
+            u8::from(self.cvbr),
+            self.complexity,
+            match self.app {
+                App::Voip => "voip",
+                App::Audio => "audio",
+                App::LowDelay => "lowdelay",
+            },
+            self.frame as f32 / 48.0,
+            self.loss
+        )
+    }
+}
 
 #[repr(C)]
 struct OpusEncoder {
@@ -26,6 +109,11 @@ struct OpusMSEncoder {
     _opaque: [u8; 0],
 }
 
+#[repr(C)]
+struct OpusProjectionEncoder {
+    _opaque: [u8; 0],
+}
+
 unsafe extern "C" {
     fn opus_encoder_create(fs: i32, ch: c_int, app: c_int, err: *mut c_int) -> *mut OpusEncoder;
     fn opus_encode_float(
@@ -56,6 +144,24 @@ unsafe extern "C" {
     ) -> i32;
     fn opus_multistream_encoder_ctl(st: *mut OpusMSEncoder, req: c_int, ...) -> c_int;
     fn opus_multistream_encoder_destroy(st: *mut OpusMSEncoder);
+    fn opus_projection_ambisonics_encoder_create(
+        fs: i32,
+        ch: c_int,
+        family: c_int,
+        streams: *mut c_int,
+        coupled: *mut c_int,
+        app: c_int,
+        err: *mut c_int,
+    ) -> *mut OpusProjectionEncoder;
+    fn opus_projection_encode_float(
+        st: *mut OpusProjectionEncoder,
+        pcm: *const f32,
+        n: c_int,
+        data: *mut u8,
+        max: i32,
+    ) -> i32;
+    fn opus_projection_encoder_ctl(st: *mut OpusProjectionEncoder, req: c_int, ...) -> c_int;
+    fn opus_projection_encoder_destroy(st: *mut OpusProjectionEncoder);
     fn opus_strerror(err: c_int) -> *const c_char;
     fn opus_get_version_string() -> *const c_char;
 }
@@ -90,64 +196,104 @@ pub struct OpusStream {
 enum Backend {
     Mono(*mut OpusEncoder),
     Multi(*mut OpusMSEncoder),
+    Proj(*mut OpusProjectionEncoder),
 }
 
 pub struct Encoder {
     backend: Backend,
     pre_skip: u16,
+    gain: i16,
     channels: u8,
     family: u8,
     streams: u8,
     coupled: u8,
-    mapping: [u8; 8],
+    table: Vec<u8>,
+    frame: usize,
 }
 
 impl Encoder {
-    pub fn new(channels: u8, brate: u16) -> Result<Self, Xerr> {
+    pub fn new(channels: u8, brate: u16, opts: &OpusOpts) -> Result<Self, Xerr> {
         let ch = c_int::from(channels);
+        let app = opts.app_ctl();
+        let family = opts.family(channels);
         let mut err: c_int = 0;
-        let (backend, family, streams, coupled, mapping) = if channels <= 2 {
-            let p = unsafe { opus_encoder_create(FS, ch, APPLICATION_AUDIO, &raw mut err) };
-            if p.is_null() {
-                return Err(oerr(err));
+        let mut s: c_int = 0;
+        let mut c: c_int = 0;
+        let (backend, table) = match family {
+            0 => {
+                let p = unsafe { opus_encoder_create(FS, ch, app, &raw mut err) };
+                if p.is_null() {
+                    return Err(oerr(err));
+                }
+                (Backend::Mono(p), Vec::new())
             }
-            (Backend::Mono(p), 0u8, 0u8, 0u8, [0u8; 8])
-        } else {
-            let mut s: c_int = 0;
-            let mut c: c_int = 0;
-            let mut m = [0u8; 8];
-            let p = unsafe {
-                opus_multistream_surround_encoder_create(
-                    FS,
-                    ch,
-                    1,
-                    &raw mut s,
-                    &raw mut c,
-                    m.as_mut_ptr(),
-                    APPLICATION_AUDIO,
-                    &raw mut err,
-                )
-            };
-            if p.is_null() {
-                return Err(oerr(err));
+            3 => {
+                let p = unsafe {
+                    opus_projection_ambisonics_encoder_create(
+                        FS,
+                        ch,
+                        3,
+                        &raw mut s,
+                        &raw mut c,
+                        app,
+                        &raw mut err,
+                    )
+                };
+                if p.is_null() {
+                    return Err(oerr(err));
+                }
+                (Backend::Proj(p), Vec::new())
+            }
+            _ => {
+                let mut m = vec![0u8; usize::from(channels)];
+                let p = unsafe {
+                    opus_multistream_surround_encoder_create(
+                        FS,
+                        ch,
+                        c_int::from(family),
+                        &raw mut s,
+                        &raw mut c,
+                        m.as_mut_ptr(),
+                        app,
+                        &raw mut err,
+                    )
+                };
+                if p.is_null() {
+                    return Err(oerr(err));
+                }
+                (Backend::Multi(p), m)
             }
-            (Backend::Multi(p), 1u8, s as u8, c as u8, m)
         };
         let mut e = Self {
             backend,
             pre_skip: 0,
+            gain: 0,
             channels,
             family,
-            streams,
-            coupled,
-            mapping,
+            streams: s as u8,
+            coupled: c as u8,
+            table,
+            frame: opts.frame,
         };
         e.set(SET_BITRATE, c_int::from(brate) * 1000)?;
         e.set(SET_VBR, 1)?;
-        e.set(SET_VBR_CONSTRAINT, 0)?;
-        e.set(SET_COMPLEXITY, 10)?;
+        e.set(SET_VBR_CONSTRAINT, c_int::from(opts.cvbr))?;
+        e.set(SET_COMPLEXITY, c_int::from(opts.complexity))?;
         e.set(SET_MAX_BANDWIDTH, BANDWIDTH_FULLBAND)?;
-        e.pre_skip = e.lookahead()? as u16;
+        e.set(SET_PACKET_LOSS_PERC, c_int::from(opts.loss))?;
+        e.set(SET_INBAND_FEC, c_int::from(opts.loss > 0))?;
+        e.pre_skip = e.get(GET_LOOKAHEAD)? as u16;
+        if let Backend::Proj(p) = e.backend {
+            e.gain = e.get(GET_DEMIXING_MATRIX_GAIN)? as i16;
+            let size = e.get(GET_DEMIXING_MATRIX_SIZE)?;
+            e.table = vec![0u8; size as usize];
+            let r = unsafe {
+                opus_projection_encoder_ctl(p, GET_DEMIXING_MATRIX, e.table.as_mut_ptr(), size)
+            };
+            if r != 0 {
+                return Err(oerr(r));
+            }
+        }
         Ok(e)
     }
 
@@ -155,10 +301,11 @@ impl Encoder {
         build_head(
             self.channels,
             self.pre_skip,
+            self.gain,
             self.family,
             self.streams,
             self.coupled,
-            &self.mapping,
+            &self.table,
         )
     }
 
@@ -167,39 +314,36 @@ impl Encoder {
             match self.backend {
                 Backend::Mono(p) => opus_encoder_ctl(p, req, val),
                 Backend::Multi(p) => opus_multistream_encoder_ctl(p, req, val),
+                Backend::Proj(p) => opus_projection_encoder_ctl(p, req, val),
             }
         };
         if r == 0 { Ok(()) } else { Err(oerr(r)) }
     }
 
-    fn lookahead(&self) -> Result<c_int, Xerr> {
+    fn get(&self, req: c_int) -> Result<c_int, Xerr> {
         let mut out: c_int = 0;
         let r = unsafe {
             match self.backend {
-                Backend::Mono(p) => opus_encoder_ctl(p, GET_LOOKAHEAD, &raw mut out),
-                Backend::Multi(p) => opus_multistream_encoder_ctl(p, GET_LOOKAHEAD, &raw mut out),
+                Backend::Mono(p) => opus_encoder_ctl(p, req, &raw mut out),
+                Backend::Multi(p) => opus_multistream_encoder_ctl(p, req, &raw mut out),
+                Backend::Proj(p) => opus_projection_encoder_ctl(p, req, &raw mut out),
             }
         };
         if r == 0 { Ok(out) } else { Err(oerr(r)) }
     }
 
     pub fn encode(&mut self, pcm: &[f32], out: &mut [u8]) -> Result<usize, Xerr> {
+        let n = self.frame as c_int;
+        let max = out.len() as i32;
         let len = unsafe {
             match self.backend {
-                Backend::Mono(p) => opus_encode_float(
-                    p,
-                    pcm.as_ptr(),
-                    FRAME as c_int,
-                    out.as_mut_ptr(),
-                    out.len() as i32,
-                ),
-                Backend::Multi(p) => opus_multistream_encode_float(
-                    p,
-                    pcm.as_ptr(),
-                    FRAME as c_int,
-                    out.as_mut_ptr(),
-                    out.len() as i32,
-                ),
+                Backend::Mono(p) => opus_encode_float(p, pcm.as_ptr(), n, out.as_mut_ptr(), max),
+                Backend::Multi(p) => {
+                    opus_multistream_encode_float(p, pcm.as_ptr(), n, out.as_mut_ptr(), max)
+                }
+                Backend::Proj(p) => {
+                    opus_projection_encode_float(p, pcm.as_ptr(), n, out.as_mut_ptr(), max)
+                }
             }
         };
         if len < 0 {
@@ -215,39 +359,57 @@ impl Drop for Encoder {
             match self.backend {
                 Backend::Mono(p) => opus_encoder_destroy(p),
                 Backend::Multi(p) => opus_multistream_encoder_destroy(p),
+                Backend::Proj(p) => opus_projection_encoder_destroy(p),
             }
         }
     }
 }
 
+// Family 1/2/255 carry a per-channel mapping table, family 3 the demixing matrix.
 fn build_head(
     channels: u8,
     pre_skip: u16,
+    gain: i16,
     family: u8,
     streams: u8,
     coupled: u8,
-    mapping: &[u8],
+    table: &[u8],
 ) -> Vec<u8> {
-    let mut h = Vec::with_capacity(if family == 0 {
-        19
-    } else {
-        21 + channels as usize
-    });
+    let mut h = Vec::with_capacity(if family == 0 { 19 } else { 21 + table.len() });
     h.extend_from_slice(b"OpusHead");
     h.push(1);
     h.push(channels);
     h.extend_from_slice(&pre_skip.to_le_bytes());
     h.extend_from_slice(&(FS as u32).to_le_bytes());
-    h.extend_from_slice(&0i16.to_le_bytes());
+    h.extend_from_slice(&gain.to_le_bytes());
     h.push(family);
     if family != 0 {
         h.push(streams);
         h.push(coupled);
-        h.extend_from_slice(&mapping[..channels as usize]);
+        h.extend_from_slice(table);
     }
     h
 }
 
+// Duration from the TOC byte; every stream of a multistream packet shares it.
+fn toc_samples(p: &[u8]) -> u32 {
+    let Some(&toc) = p.first() else {
+        return 0;
+    };
+    let cfg = toc >> 3;
+    let fs = match cfg {
+        0..=11 => [480, 960, 1920, 2880][usize::from(cfg & 3)],
+        12..=15 => 480 << (cfg & 1),
+        _ => 120 << (cfg & 3),
+    };
+    let n = match toc & 3 {
+        0 => 1,
+        1 | 2 => 2,
+        _ => p.get(1).map_or(0, |&c| u32::from(c & 0x3f)),
+    };
+    fs * n
+}
+
 pub fn read(buf: &[u8]) -> OpusStream {
     let hl = u16::from_le_bytes(unsafe { [*buf.get_unchecked(0), *buf.get_unchecked(1)] }) as usize;
     let head = unsafe { buf.get_unchecked(2..2 + hl) }.to_vec();
@@ -263,7 +425,7 @@ pub fn read(buf: &[u8]) -> OpusStream {
         pos += 2;
         packets.push(OpusPacket {
             range: ByteRange { offset: pos, len },
-            samples: FRAME as u32,
+            samples: toc_samples(unsafe { buf.get_unchecked(pos..pos + len) }),
         });
         pos += len;
     }
@@ -26,12 +26,15 @@ use crate::{
 };
 
 pub type WriteFn = fn(&mut ChildStdin, &[u8], usize, &mut [u8], &Pipeline);
+// One decoded frame into the 10-bit planar layout encoders are fed
+pub type ConvFn = fn(&[u8], &mut [u8], usize, usize);
 
 #[cfg(feature = "vship")]
 pub struct MetricProgs<'a> {
     pub prog: &'a ProgsTrack,
     pub slot: usize,
-    pub crf: f32,
+    // None for `--audit`, which has no CRF to show
+    pub crf: Option<f32>,
     pub last_score: Option<f32>,
 }
 
@@ -56,30 +59,25 @@ macro_rules! make_write_frames {
     };
 }
 
-make_write_frames!(
-    write_frames_8b,
-    |f: &[u8], b: &mut [u8], _w: usize, _h: usize| conv_10b(f, b)
-);
-make_write_frames!(
-    write_frames_8b_rem,
-    |f: &[u8], b: &mut [u8], _w: usize, _h: usize| conv_10b_rem(f, b)
-);
-make_write_frames!(
-    write_frames_unpack,
-    |f: &[u8], b: &mut [u8], _w: usize, _h: usize| unpack_10b(f, b)
-);
-make_write_frames!(
-    write_frames_unpack_rem,
-    |f: &[u8], b: &mut [u8], w: usize, h: usize| unpack_10b_rem(f, b, w, h)
-);
-make_write_frames!(
-    write_frames_nv12,
-    |f: &[u8], b: &mut [u8], w: usize, h: usize| nv12_10b(f, b, w, h)
-);
-make_write_frames!(
-    write_frames_nv12_rem,
-    |f: &[u8], b: &mut [u8], w: usize, h: usize| nv12_10b_rem(f, b, w, h)
-);
+fn conv_8b(f: &[u8], b: &mut [u8], _: usize, _: usize) {
+    conv_10b(f, b);
+}
+fn conv_8b_rem(f: &[u8], b: &mut [u8], _: usize, _: usize) {
+    conv_10b_rem(f, b);
+}
+fn conv_unpack(f: &[u8], b: &mut [u8], _: usize, _: usize) {
+    unpack_10b(f, b);
+}
+fn conv_raw(f: &[u8], b: &mut [u8], _: usize, _: usize) {
+    b[..f.len()].copy_from_slice(f);
+}
+
+make_write_frames!(write_frames_8b, conv_8b);
+make_write_frames!(write_frames_8b_rem, conv_8b_rem);
+make_write_frames!(write_frames_unpack, conv_unpack);
+make_write_frames!(write_frames_unpack_rem, unpack_10b_rem);
+make_write_frames!(write_frames_nv12, nv12_10b);
+make_write_frames!(write_frames_nv12_rem, nv12_10b_rem);
 
 const fn write_frames_raw(_: &mut ChildStdin, _: &[u8], _: usize, _: &mut [u8], _: &Pipeline) {
     assume_unreachable();
@@ -96,6 +94,7 @@ pub struct Pipeline {
     #[cfg(feature = "vship")]
     pub unpack_buf_sz: usize,
     pub write_frames: WriteFn,
+    pub conv: ConvFn,
     #[cfg(feature = "vship")]
     pub reset_cvvdp: bool,
     #[cfg(feature = "vship")]
@@ -157,26 +156,26 @@ impl Pipeline {
 
         let is_nv12_10 = matches!(strat, HwNv12To10 | HwNv12To10Stride | HwNv12CropTo10 { .. });
 
-        let write_frames: WriteFn = if is_nv12_10 {
+        let (write_frames, conv): (WriteFn, ConvFn) = if is_nv12_10 {
             let y_ok = (final_w * final_h).is_multiple_of(SHIFT_CHUNK);
             let uv_ok = (final_w / 2 * (final_h / 2)).is_multiple_of(SHIFT_CHUNK * 2);
             if y_ok && uv_ok {
-                write_frames_nv12
+                (write_frames_nv12, nv12_10b)
             } else {
-                write_frames_nv12_rem
+                (write_frames_nv12_rem, nv12_10b_rem)
             }
         } else if is_raw {
-            write_frames_raw
+            (write_frames_raw, conv_raw)
         } else if !is_10b_out {
             if frame_sz.is_multiple_of(SHIFT_CHUNK) {
-                write_frames_8b
+                (write_frames_8b, conv_8b)
             } else {
-                write_frames_8b_rem
+                (write_frames_8b_rem, conv_8b_rem)
             }
         } else if has_rem {
-            write_frames_unpack_rem
+            (write_frames_unpack_rem, unpack_10b_rem)
         } else {
-            write_frames_unpack
+            (write_frames_unpack, conv_unpack)
         };
 
         #[cfg(feature = "vship")]
@@ -192,6 +191,7 @@ impl Pipeline {
             #[cfg(feature = "vship")]
             unpack_buf_sz,
             write_frames,
+            conv,
             #[cfg(feature = "vship")]
             reset_cvvdp,
             #[cfg(feature = "vship")]
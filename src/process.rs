@@ -1,16 +1,20 @@
 #[cfg(target_os = "linux")]
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 #[cfg(target_os = "linux")]
-use core::{ptr::null, slice::from_raw_parts};
+use core::{iter::once, ptr::null, slice::from_raw_parts, sync::atomic::Ordering::Relaxed};
 
+#[cfg(target_os = "linux")]
+use crate::error::{INTERRUPTED, SIG_DFL, SIGPIPE};
 #[cfg(target_os = "linux")]
 use crate::io::{Error, Read, Result, Write};
 #[cfg(target_os = "linux")]
 use crate::process::Stdio::{Inherit, Null, Piped};
 #[cfg(target_os = "linux")]
+use crate::sync::Mutex;
+#[cfg(target_os = "linux")]
 use crate::sys::{
-    O_CLOEXEC, O_RDONLY, O_WRONLY, close, dup2, execve, exit_group, faccessat, fork, getpid,
-    openat, pipe2, read as sys_read, wait4, write as sys_write,
+    O_CLOEXEC, O_RDONLY, O_WRONLY, close, dup2, execve, exit_group, faccessat, fork, getpid, kill,
+    openat, pipe2, read as sys_read, sigaction, wait4, write as sys_write,
 };
 
 #[cfg(target_os = "linux")]
@@ -21,6 +25,10 @@ unsafe extern "C" {
 #[cfg(target_os = "linux")]
 const DEVNULL: &[u8] = b"/dev/null\0";
 
+// Children not yet reaped, so an interrupt can take them down with us
+#[cfg(target_os = "linux")]
+static LIVE: Mutex<Vec<i64>> = Mutex::new(Vec::new());
+
 #[cfg(target_os = "linux")]
 const fn err<T>(ret: i64) -> Result<T> {
     Err(Error::from_raw_os_error((-ret) as i32))
@@ -56,6 +64,17 @@ fn getenv(name: &[u8]) -> Option<&'static [u8]> {
     None
 }
 
+#[cfg(target_os = "linux")]
+pub fn var(name: &str) -> Option<String> {
+    getenv(name.as_bytes())
+        .filter(|v| !v.is_empty())
+        .map(|v| String::from_utf8_lossy(v).into_owned())
+}
+#[cfg(not(target_os = "linux"))]
+pub fn var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
 #[cfg(target_os = "linux")]
 fn resolve(prog: &[u8]) -> Option<Vec<u8>> {
     let name = &prog[..prog.len() - 1];
@@ -231,6 +250,9 @@ impl Command {
         let (out_p, out_c) = setup(self.stdout, false)?;
         let (err_p, err_c) = setup(self.stderr, false)?;
 
+        // held across fork & push so `kill_all` can't run in between & miss the child; one
+        // that already ran is caught by the INTERRUPTED check below
+        let mut live = LIVE.lock();
         let pid = unsafe { fork() };
         if pid < 0 {
             return err(pid);
@@ -245,10 +267,17 @@ impl Command {
             if let Some(fd) = err_c {
                 unsafe { dup2(fd, 2) };
             }
+            // xav ignores SIGPIPE & exec keeps ignored signals: give the child the default back
+            sigaction(SIGPIPE, SIG_DFL);
             unsafe { execve(path.as_ptr(), argv.as_ptr(), envp.as_ptr()) };
             unsafe { exit_group(127) };
         }
 
+        live.push(pid);
+        if INTERRUPTED.load(Relaxed) {
+            kill(pid as i32, 9);
+        }
+        drop(live);
         if let Some(fd) = in_c {
             unsafe { close(fd) };
         }
@@ -274,6 +303,45 @@ impl Command {
     }
 }
 
+// Word for a POSIX shell line: bare when safe, else single-quoted
+fn sh_word(w: &str, out: &mut String) {
+    if !w.is_empty()
+        && w.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"_-./:=,+%@".contains(&b))
+    {
+        out.push_str(w);
+    } else {
+        out.push('\'');
+        out.push_str(&w.replace('\'', "'\\''"));
+        out.push('\'');
+    }
+}
+
+// Shell line that runs the same program & arguments; env & stdio are not included
+#[cfg(target_os = "linux")]
+pub fn cmd_line(cmd: &Command) -> String {
+    let mut out = String::new();
+    for w in once(&cmd.prog).chain(&cmd.args) {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        let w = w.strip_suffix(&[0]).unwrap_or(w);
+        sh_word(&String::from_utf8_lossy(w), &mut out);
+    }
+    out
+}
+#[cfg(not(target_os = "linux"))]
+pub fn cmd_line(cmd: &Command) -> String {
+    let mut out = String::new();
+    for w in core::iter::once(cmd.get_program()).chain(cmd.get_args()) {
+        if !out.is_empty() {
+            out.push(' ');
+        }
+        sh_word(&w.to_string_lossy(), &mut out);
+    }
+    out
+}
+
 #[cfg(target_os = "linux")]
 pub struct Child {
     pid: i64,
@@ -290,6 +358,7 @@ impl Child {
         self.stdin = None;
         let mut status = 0i32;
         let r = wait4(self.pid as i32, &raw mut status, 0);
+        LIVE.lock().retain(|&p| p != self.pid);
         if r < 0 {
             return err(r);
         }
@@ -416,6 +485,16 @@ impl ExitStatus {
     pub const fn success(&self) -> bool {
         self.0.trailing_zeros() >= 7 && (self.0 >> 8).trailing_zeros() >= 8
     }
+
+    // Exit code, or `None` when a signal ended it
+    #[inline]
+    pub const fn code(&self) -> Option<i32> {
+        if self.0.trailing_zeros() >= 7 {
+            Some((self.0 >> 8) & 0xff)
+        } else {
+            None
+        }
+    }
 }
 
 #[cfg(target_os = "linux")]
@@ -438,3 +517,12 @@ pub fn id() -> u32 {
 pub fn id() -> u32 {
     std::process::id()
 }
+
+#[cfg(target_os = "linux")]
+pub fn kill_all() {
+    for &pid in LIVE.lock().iter() {
+        kill(pid as i32, 9);
+    }
+}
+#[cfg(not(target_os = "linux"))]
+pub const fn kill_all() {}
@@ -1,6 +1,6 @@
 use alloc::sync::Arc;
 #[cfg(target_os = "linux")]
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use core::{
     fmt::{self, Write as _},
     iter::repeat_with,
@@ -8,7 +8,7 @@ use core::{
     str::from_utf8,
     sync::atomic::{
         AtomicBool, AtomicU32, AtomicU64, AtomicUsize,
-        Ordering::{Relaxed, Release},
+        Ordering::{Acquire, Relaxed, Release},
     },
     time::Duration as Durat,
 };
@@ -21,6 +21,7 @@ use crate::{
         Encoder::{Avm, SvtAv1, Vvenc, X264, X265},
     },
     error::eprint,
+    events::{CrfScore, draw, emit, muted, on},
     ffms::VidInf,
     io::{Read, Write as _, print_fmt, stdout as io_stdout},
     sync::{Guard, Mutex},
@@ -30,6 +31,7 @@ use crate::{
 const BAR_WIDTH: usize = 20;
 pub const INTERVAL_MS: u64 = 512;
 const READ_CAP: usize = 8192;
+const EV_TICKS: usize = 4;
 
 use crate::util::{C, G, P, R, W, Y, assume_unreachable};
 
@@ -157,6 +159,9 @@ impl ProgsBar {
     }
 
     pub fn up_frames(&mut self, current: usize, tot: usize, line: usize, label: &str) {
+        if !draw() {
+            return;
+        }
         unsafe {
             xav_pb_frames(
                 &raw mut *self,
@@ -170,6 +175,9 @@ impl ProgsBar {
     }
 
     pub fn up_au(&mut self, current: usize, tot: usize, line: usize, pass: u8, track_id: u8) {
+        if !draw() {
+            return;
+        }
         unsafe {
             xav_pb_au(
                 &raw mut *self,
@@ -183,7 +191,9 @@ impl ProgsBar {
     }
 
     pub fn up_copy(&mut self, current: usize, tot: usize) {
-        unsafe { xav_pb_copy(&raw mut *self, current, tot) }
+        if draw() {
+            unsafe { xav_pb_copy(&raw mut *self, current, tot) }
+        }
     }
 }
 
@@ -195,6 +205,12 @@ pub fn monitor_au(
     pass: u8,
     tid: u8,
 ) {
+    if !draw() {
+        while !stop.load(Relaxed) {
+            sleep(Durat::from_millis(INTERVAL_MS / 8));
+        }
+        return;
+    }
     unsafe {
         xav_pb_mon(
             (&raw const *done).cast(),
@@ -258,9 +274,15 @@ pub struct ProgsTrack {
     inner: Arc<Shared>,
 }
 
+static LIVE: Mutex<Option<Arc<Shared>>> = Mutex::new(None);
+
 impl Drop for ProgsTrack {
     fn drop(&mut self) {
         self.inner.stop.store(true, Relaxed);
+        let mut live = LIVE.lock();
+        if live.as_ref().is_some_and(|s| Arc::ptr_eq(s, &self.inner)) {
+            *live = None;
+        }
     }
 }
 
@@ -274,8 +296,10 @@ impl ProgsTrack {
         completed_frames: Arc<AtomicUsize>,
         tot_sz: Arc<AtomicU64>,
     ) -> (Self, JoinHandle<()>) {
-        print!("\x1b[s");
-        _ = io_stdout().flush();
+        if draw() {
+            print!("\x1b[s");
+            _ = io_stdout().flush();
+        }
 
         let tot_frames = chnks.iter().map(|c| c.end - c.start).sum();
 
@@ -294,6 +318,10 @@ impl ProgsTrack {
             init_frames,
         });
 
+        // a muted finisher's audit is not what `status` asks about
+        if !muted() {
+            *LIVE.lock() = Some(Arc::clone(&inner));
+        }
         let disp = Arc::clone(&inner);
         let handle = spawn(move || display_loop(&disp));
 
@@ -303,10 +331,20 @@ impl ProgsTrack {
     pub fn watch_enc<R: Read + Send + 'static>(&self, stderr: R, w: Watch, encoder: Encoder) {
         let inner = Arc::clone(&self.inner);
 
-        spawn(move || match encoder {
-            SvtAv1 | Avm => assume_unreachable(),
-            X265 | X264 => watch_x265(&inner, stderr, w),
-            Vvenc => watch_vvenc(&inner, stderr, w),
+        spawn(move || {
+            let started = Mono::now();
+            ev_start(w.worker_id, w.chnk_idx, w.frames, w.crf_score, "enc");
+            match encoder {
+                SvtAv1 | Avm => assume_unreachable(),
+                X265 | X264 => watch_x265(&inner, stderr, w),
+                Vvenc => watch_vvenc(&inner, stderr, w),
+            }
+            ev_pass(
+                w.worker_id,
+                usize::from(w.chnk_idx),
+                w.frames,
+                started.elapsed(),
+            );
         });
     }
 }
@@ -329,9 +367,32 @@ struct Draw {
     fps_den: u32,
 }
 
+fn ev_start(worker: usize, idx: u16, frames: usize, cs: Option<(f32, Option<f32>)>, stage: &str) {
+    emit(
+        "chunk_start",
+        format_args!(
+            ",\"chunk\":{idx},\"worker\":{worker},\"frames\":{frames},\"stage\":\"{stage}\"{}",
+            CrfScore(cs)
+        ),
+    );
+}
+
+fn ev_pass(worker: usize, idx: usize, frames: usize, took: Durat) {
+    let secs = took.as_secs_f64();
+    emit(
+        "chunk_pass",
+        format_args!(
+            ",\"chunk\":{idx},\"worker\":{worker},\"frames\":{frames},\"secs\":{secs:.3},\"fps\":\
+             {:.2}",
+            frames as f64 / secs.max(0.001)
+        ),
+    );
+}
+
 pub struct Tracker {
     slot: *mut Slot,
     prc: *mut u8,
+    wid: usize,
 }
 
 impl Tracker {
@@ -362,9 +423,14 @@ impl Tracker {
             (*slot).s = s;
             (*slot).tag.store(tag, Release);
         }
+        if on() {
+            let stage = if tag == TAG_LIB { "enc" } else { "metric" };
+            ev_start(worker_id, chnk_idx, tot, crf_score, stage);
+        }
         Self {
             slot,
             prc: (&raw const prog.inner.processed).cast_mut().cast(),
+            wid: worker_id,
         }
     }
 
@@ -410,6 +476,12 @@ impl Tracker {
     }
 
     pub fn finish(&self) {
+        if on() {
+            let (idx, tot, start) =
+                unsafe { ((*self.slot).idx, (*self.slot).tot, (*self.slot).start) };
+            let took = Durat::from_nanos(Mono::now().raw().saturating_sub(start));
+            ev_pass(self.wid, idx, tot, took);
+        }
         unsafe { xav_pb_fin(self.slot.cast(), self.prc) }
     }
 }
@@ -625,13 +697,78 @@ fn display_loop(s: &Shared) {
         fps_num: s.fps_num,
         fps_den: s.fps_den,
     };
+    let show = draw();
+    let ev = on() && !muted();
+    let mut tick = 0usize;
     loop {
         sleep(Durat::from_millis(INTERVAL_MS));
         unsafe { xav_svt_drain_tick(nb) };
         if s.stop.load(Relaxed) {
             break;
         }
+        if show {
+            unsafe { xav_pb_draw(&raw const dw) }
+        }
+        tick += 1;
+        if ev && tick.is_multiple_of(EV_TICKS) {
+            ev_progress(s);
+        }
+    }
+    if show {
         unsafe { xav_pb_draw(&raw const dw) }
     }
-    unsafe { xav_pb_draw(&raw const dw) }
+    if ev {
+        ev_progress(s);
+    }
+}
+
+// Same counters the bar is drawn from, as JSON fields
+fn progress_json(s: &Shared, out: &mut String) {
+    let secs = s.start.elapsed().as_secs_f64().max(0.001);
+    let fps = s.processed.load(Relaxed) as f64 / secs;
+    let done_frames = s.completed_frames.load(Relaxed);
+    let left = s.tot_frames.saturating_sub(done_frames) as f64;
+    _ = write!(
+        out,
+        ",\"chunks_done\":{},\"chunks\":{},\"frames_done\":{done_frames},\"frames\":{},\"bytes\":\
+         {},\"fps\":{fps:.2},\"eta\":{:.0},\"workers\":[",
+        s.completed.load(Relaxed),
+        s.tot_chnks,
+        s.tot_frames,
+        s.tot_sz.load(Relaxed),
+        if fps > 0.0 { left / fps } else { -1.0 }
+    );
+    let now = Mono::now().raw();
+    let mut first = true;
+    for (i, b) in s.boards.iter().enumerate() {
+        let tag = b.tag.load(Acquire);
+        if tag == TAG_EMPTY || tag == TAG_TXT {
+            continue;
+        }
+        let enced = b.enced.load(Relaxed);
+        let w_secs = now.saturating_sub(b.start) as f64 / 1e9;
+        _ = write!(
+            out,
+            "{}{{\"worker\":{i},\"chunk\":{},\"done\":{enced},\"frames\":{},\"fps\":{:.2}}}",
+            if first { "" } else { "," },
+            b.idx,
+            b.tot,
+            enced as f64 / w_secs.max(0.001)
+        );
+        first = false;
+    }
+    out.push(']');
+}
+
+fn ev_progress(s: &Shared) {
+    let mut body = String::new();
+    progress_json(s, &mut body);
+    emit("progress", format_args!("{body}"));
+}
+
+// Live counters for the control socket `status` reply; empty outside the encode
+pub fn status(out: &mut String) {
+    if let Some(ref s) = *LIVE.lock() {
+        progress_json(s, out);
+    }
 }
@@ -0,0 +1,325 @@
+#[cfg(target_os = "linux")]
+use alloc::{
+    string::{String, ToString as _},
+    vec::Vec,
+};
+use core::{sync::atomic::Ordering::Relaxed, time::Duration as Durat};
+
+#[cfg(feature = "vship")]
+use crate::vship::init_device;
+use crate::{
+    Encoded, Sum,
+    clk::Mono,
+    error::{INTERRUPTED, Xerr},
+    events::mute,
+    finish, fmt_sz,
+    fs::{metadata, read_dir, read_to_string as read_to_str},
+    io::{Write as _, print_fmt, println_fmt, stdout},
+    parse_quoted_args,
+    path::{Path, PathBuf},
+    run_one,
+    thread::{JoinHandle, sleep, spawn},
+    util::{B, C, G, N, P, R, W, Y},
+};
+
+macro_rules! print {
+    ($($arg:tt)*) => { print_fmt(format_args!($($arg)*)) };
+}
+macro_rules! println {
+    ($($arg:tt)*) => { println_fmt(format_args!($($arg)*)) };
+}
+
+// `--queue <file|dir>`: every input runs in this process, one after another, so
+// vship & the GPU are set up once; a failing input only fails its own row. An
+// input's audio, mux & audit run on a muted thread while the next one encodes
+const POLL_MS: u64 = 200;
+const WATCH_SECS: u64 = 5;
+const VID_EXTS: [&str; 8] = ["mkv", "mp4", "mov", "webm", "ts", "m2ts", "avi", "y4m"];
+
+struct Job {
+    inp: PathBuf,
+    extra: Vec<String>,
+}
+
+// `input [output] [overrides...]` per line; `#` starts a comment
+fn read_queue(path: &Path) -> Result<Vec<Job>, Xerr> {
+    let mut jobs = Vec::new();
+    for line in read_to_str(path)?.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut toks = parse_quoted_args(line).into_iter();
+        let Some(inp) = toks.next() else {
+            continue;
+        };
+        jobs.push(Job {
+            inp: PathBuf::from(inp),
+            extra: toks.collect(),
+        });
+    }
+    Ok(jobs)
+}
+
+fn is_vid(p: &Path) -> bool {
+    let stem = p.file_stem().map(Path::to_string_lossy).unwrap_or_default();
+    !stem.starts_with('.')
+        && !stem.ends_with("_xav")
+        && p.extension()
+            .and_then(Path::to_str)
+            .is_some_and(|e| VID_EXTS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+// Watch mode: videos whose size held still across two polls & that have no
+// `<stem>_xav.mkv` yet; `<input>.xav` next to a video holds its overrides
+fn scan(dir: &Path, seen: &mut Vec<PathBuf>, sizes: &mut Vec<(PathBuf, u64)>) -> Vec<Job> {
+    let Ok(entries) = read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut jobs = Vec::new();
+    let mut now = Vec::new();
+    for p in entries.flatten().map(|e| e.path()) {
+        if !is_vid(&p) || seen.contains(&p) {
+            continue;
+        }
+        let stem = p.file_stem().map(Path::to_string_lossy).unwrap_or_default();
+        if p.with_file_name(format!("{stem}_xav.mkv")).exists() {
+            seen.push(p);
+            continue;
+        }
+        let Ok(sz) = metadata(&p) else {
+            continue;
+        };
+        if sizes.iter().any(|e| e.0 == p && e.1 == sz) {
+            let side = p.with_file_name(format!(
+                "{}.xav",
+                p.file_name().map(Path::to_string_lossy).unwrap_or_default()
+            ));
+            let extra = read_to_str(side)
+                .map(|s| parse_quoted_args(s.trim()))
+                .unwrap_or_default();
+            seen.push(p.clone());
+            jobs.push(Job { inp: p, extra });
+        } else {
+            now.push((p, sz));
+        }
+    }
+    *sizes = now;
+    jobs
+}
+
+struct Row {
+    n: usize,
+    inp: PathBuf,
+    done: Option<Sum>,
+    err: Option<String>,
+    stopped: bool,
+}
+
+// The previous input's finish, joined once the next one is encoded
+struct Fin {
+    n: usize,
+    inp: PathBuf,
+    h: JoinHandle<Result<Sum, Xerr>>,
+}
+
+impl Fin {
+    fn spawn(n: usize, inp: PathBuf, enc: Encoded) -> Self {
+        let h = spawn(move || {
+            mute();
+            finish(enc)
+        });
+        Self { n, inp, h }
+    }
+
+    fn join(self) -> Row {
+        report(self.n, self.inp, self.h.join().map(Some))
+    }
+}
+
+fn exec(job: Job, base: &[String]) -> Result<Option<Encoded>, Xerr> {
+    let mut argv = base.to_vec();
+    argv.push(job.inp.to_string_lossy().into_owned());
+    argv.extend(job.extra);
+    run_one(&argv)
+}
+
+fn report(n: usize, inp: PathBuf, res: Result<Option<Sum>, Xerr>) -> Row {
+    let name = inp
+        .file_name()
+        .map(Path::to_string_lossy)
+        .unwrap_or_default()
+        .into_owned();
+    let mut row = Row {
+        n,
+        inp,
+        done: None,
+        err: None,
+        stopped: false,
+    };
+    match res {
+        Ok(Some(d)) => {
+            println!(
+                "{G}[{n}] done {W}{name} {P}→ {B}{} {C}({}){N}",
+                d.out.display(),
+                fmt_sz(d.bytes)
+            );
+            for note in &d.notes {
+                println!("{Y}    {note}{N}");
+            }
+            row.done = Some(d);
+        }
+        Ok(None) => {
+            println!("{Y}[{n}] stopped {W}{name}{N}");
+            row.stopped = true;
+        }
+        Err(e) => {
+            println!("{R}[{n}] FAIL {W}{name}{R}: {e}{N}");
+            row.err = Some(e.to_string());
+        }
+    }
+    row
+}
+
+fn hms(secs: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+fn summary(rows: &[Row]) {
+    println!(
+        "\n{P}  # {Y}{:<32} {:>10} {:>10} {:>8} {:>9} {:>9} {:>9} {:>8}{N}",
+        "Input", "In", "Out", "Change", "Video", "Length", "Time", "fps"
+    );
+    for r in rows {
+        let name = r
+            .inp
+            .file_name()
+            .map(Path::to_string_lossy)
+            .unwrap_or_default();
+        print!("{P}{:>3} {W}{:<32.32} ", r.n, name);
+        let Some(ref d) = r.done else {
+            if r.stopped {
+                println!("{Y}stopped; rerun to resume{N}");
+            } else {
+                println!("{R}FAIL {}{N}", r.err.as_deref().unwrap_or(""));
+            }
+            continue;
+        };
+        let (frames, secs, fps) = (d.frames as f64, d.secs, f64::from(d.fps));
+        let change = (d.bytes as f64 / d.in_bytes as f64 - 1.0) * 100.0;
+        println!(
+            "{W}{:>10} {:>10} {}{:>+7.2}% {W}{:>4}x{:<4} {:>9} {:>9} {B}{:>8.2}{N}",
+            fmt_sz(d.in_bytes),
+            fmt_sz(d.bytes),
+            if change < 0.0 { G } else { R },
+            change,
+            d.w,
+            d.h,
+            hms(if fps > 0.0 { (frames / fps) as u64 } else { 0 }),
+            hms(secs as u64),
+            if secs > 0.0 { frames / secs } else { 0.0 }
+        );
+    }
+    _ = stdout().flush();
+}
+
+// `base` is the command line without `--queue`; each job appends its input & overrides
+pub fn run(src: &Path, base: &[String]) -> Result<(), Xerr> {
+    let watch = read_dir(src).is_ok();
+    let mut pending: Vec<Job> = if watch { Vec::new() } else { read_queue(src)? };
+    pending.reverse();
+    let total = pending.len();
+    let (mut seen, mut sizes) = (Vec::new(), Vec::new());
+    let mut rows: Vec<Row> = Vec::new();
+    let mut fin: Option<Fin> = None;
+    let mut last_scan: Option<Mono> = None;
+
+    // a failure is left to `run_one`, so only the inputs that score fail their rows
+    #[cfg(feature = "vship")]
+    {
+        _ = init_device();
+    }
+
+    if watch {
+        println!("{C}Watching {W}{}{C}; Ctrl-C to stop{N}", src.display());
+    }
+    loop {
+        let intr = INTERRUPTED.load(Relaxed);
+        if watch && !intr && last_scan.is_none_or(|t| t.elapsed().as_secs() >= WATCH_SECS) {
+            last_scan = Some(Mono::now());
+            let mut found = scan(src, &mut seen, &mut sizes);
+            found.reverse();
+            found.append(&mut pending);
+            pending = found;
+        }
+
+        if !intr && let Some(job) = pending.pop() {
+            let n = rows.len() + usize::from(fin.is_some()) + 1;
+            let of = if watch {
+                String::new()
+            } else {
+                format!("/{total}")
+            };
+            println!("{C}[{n}{of}] {W}{}{N}", job.inp.display());
+            _ = stdout().flush();
+            let inp = job.inp.clone();
+            let res = exec(job, base);
+            if let Some(f) = fin.take() {
+                rows.push(f.join());
+            }
+            match res {
+                Ok(Some(enc)) => fin = Some(Fin::spawn(n, inp, enc)),
+                Ok(None) => rows.push(report(n, inp, Ok(None))),
+                Err(e) => rows.push(report(n, inp, Err(e))),
+            }
+            continue;
+        }
+        // nothing left to encode alongside it
+        if let Some(f) = fin.take() {
+            rows.push(f.join());
+        }
+
+        if intr || !watch {
+            break;
+        }
+        sleep(Durat::from_millis(POLL_MS));
+    }
+    // Ctrl-C: what never started is listed too, so the table shows the whole queue
+    while let Some(job) = pending.pop() {
+        rows.push(Row {
+            n: rows.len() + 1,
+            inp: job.inp,
+            done: None,
+            err: None,
+            stopped: true,
+        });
+    }
+
+    summary(&rows);
+    let failed = rows
+        .iter()
+        .filter(|r| r.done.is_none() && !r.stopped)
+        .count();
+    if failed > 0 {
+        return Err(format!("{failed} of {} queued files failed", rows.len()).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod test_access {
+    use alloc::string::String;
+
+    use crate::{Encoded, path::PathBuf};
+
+    // The error a finish thread's row records; `None` when it muxed
+    pub fn fin_err(enc: Encoded) -> Option<String> {
+        super::Fin::spawn(1, PathBuf::from("in.mkv"), enc)
+            .join()
+            .err
+    }
+}
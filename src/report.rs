@@ -0,0 +1,181 @@
+#[cfg(target_os = "linux")]
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString as _},
+    vec::Vec,
+};
+use core::{fmt::Write as _, time::Duration as Durat};
+
+use crate::{
+    Args,
+    chunk::{Chunk, get_resume},
+    fs::{OpenOptions, read_to_string as read_to_str, write},
+    io::Write as _,
+    path::Path,
+    util::json_str,
+};
+
+// How each chunk was encoded, by `id`: worker, wall seconds & zone params
+pub type Runs = BTreeMap<u16, (usize, f32, String)>;
+
+// `encodes.txt`: `<id> <worker> <secs> [<zone params>]` per finished chunk; a chunk encoded
+// again (`--redo`, `--crf-smooth`) appends a line & the last one counts
+pub fn log_chunk(work_dir: &Path, idx: u16, worker: usize, secs: f32, zone: Option<&str>) {
+    let line = format!("{idx} {worker} {secs:.3} {}\n", zone.unwrap_or_default());
+    if let Ok(mut file) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(work_dir.join("encodes.txt"))
+    {
+        _ = file.write_all(line.as_bytes());
+    }
+}
+
+pub fn read_runs(work_dir: &Path) -> Runs {
+    let mut out = BTreeMap::new();
+    for line in read_to_str(work_dir.join("encodes.txt"))
+        .unwrap_or_default()
+        .lines()
+    {
+        let mut it = line.splitn(4, ' ');
+        let (Some(id), Some(worker), Some(secs)) = (
+            it.next().and_then(|v| v.parse().ok()),
+            it.next().and_then(|v| v.parse().ok()),
+            it.next().and_then(|v| v.parse().ok()),
+        ) else {
+            continue;
+        };
+        out.insert(id, (worker, secs, it.next().unwrap_or("").trim().into()));
+    }
+    out
+}
+
+// One chunk's row: its `encodes.txt` line, if any, & size from `done.txt`
+struct Row<'a> {
+    c: &'a Chunk,
+    run: Option<&'a (usize, f32, String)>,
+    sz: u64,
+}
+
+impl Row<'_> {
+    const fn frames(&self) -> usize {
+        self.c.end - self.c.start
+    }
+
+    fn fps(&self) -> Option<f32> {
+        self.run.map(|r| self.frames() as f32 / r.1.max(0.001))
+    }
+
+    fn kbs(&self, fps_rate: f32) -> Option<f32> {
+        kbs(self.sz, self.frames(), fps_rate)
+    }
+}
+
+// `None` without frames to spread the bytes over, so no `inf`/`NaN` reaches the JSON
+fn kbs(bytes: u64, frames: usize, fps_rate: f32) -> Option<f32> {
+    (frames > 0 && fps_rate > 0.0).then(|| bytes as f32 * 8.0 / (frames as f32 / fps_rate) / 1000.0)
+}
+
+// `<output>_report.json` & `.csv`: every chunk's frame range, zone params, worker, wall time,
+// encode fps, bytes & kb/s; the totals are the summary box's (`out_sz`: video bytes in the output)
+pub fn write_report(
+    args: &Args,
+    fps_rate: f32,
+    chnks: &[Chunk],
+    work_dir: &Path,
+    out_sz: u64,
+    enc_time: Durat,
+) {
+    let runs = read_runs(work_dir);
+    let szs: BTreeMap<u16, u64> = get_resume(work_dir)
+        .map(|r| r.chnks_done.iter().map(|c| (c.idx, c.sz)).collect())
+        .unwrap_or_default();
+    let rows: Vec<Row> = chnks
+        .iter()
+        .map(|c| Row {
+            c,
+            run: runs.get(&c.idx),
+            sz: szs.get(&c.idx).copied().unwrap_or(0),
+        })
+        .collect();
+
+    let tot_frames: usize = chnks.iter().map(|c| c.end - c.start).sum();
+    let secs = enc_time.as_secs_f32();
+    // a run resumed with every chunk done encodes nothing
+    let tot_fps = (secs > 0.0).then(|| tot_frames as f32 / secs);
+    let tot_kbs = kbs(out_sz, tot_frames, fps_rate);
+    let opt = |v: Option<String>| v.unwrap_or_else(|| "null".into());
+    let fps_s = |v: Option<f32>| v.map(|f| format!("{f:.2}"));
+    let kbs_s = |v: Option<f32>| v.map(|k| format!("{k:.0}"));
+
+    let mut js = String::new();
+    _ = writeln!(js, "{{");
+    _ = writeln!(
+        js,
+        "  \"input\": {},",
+        json_str(&args.inp.to_string_lossy())
+    );
+    _ = writeln!(
+        js,
+        "  \"output\": {},",
+        json_str(&args.out.to_string_lossy())
+    );
+    _ = writeln!(js, "  \"params\": {},", json_str(&args.params));
+    _ = writeln!(js, "  \"chunks\": [");
+    for (i, r) in rows.iter().enumerate() {
+        let comma = if i + 1 < rows.len() { "," } else { "" };
+        _ = writeln!(
+            js,
+            "    {{ \"id\": {}, \"start\": {}, \"end\": {}, \"frames\": {}, \"zone\": {}, \
+             \"worker\": {}, \"secs\": {}, \"fps\": {}, \"bytes\": {}, \"kbs\": {} }}{comma}",
+            r.c.idx,
+            r.c.start,
+            r.c.end,
+            r.frames(),
+            json_str(r.run.map_or("", |run| &run.2)),
+            opt(r.run.map(|run| run.0.to_string())),
+            opt(r.run.map(|run| format!("{:.3}", run.1))),
+            opt(fps_s(r.fps())),
+            r.sz,
+            opt(kbs_s(r.kbs(fps_rate)))
+        );
+    }
+    _ = writeln!(js, "  ],");
+    _ = writeln!(js);
+    _ = writeln!(
+        js,
+        "  \"total\": {{ \"frames\": {tot_frames}, \"secs\": {secs:.3}, \"fps\": {}, \"bytes\": \
+         {out_sz}, \"kbs\": {} }}",
+        opt(fps_s(tot_fps)),
+        opt(kbs_s(tot_kbs))
+    );
+    _ = writeln!(js, "}}");
+
+    let mut csv = String::from("chunk,start,end,frames,worker,secs,fps,bytes,kbs,zone\n");
+    for r in &rows {
+        _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},\"{}\"",
+            r.c.idx,
+            r.c.start,
+            r.c.end,
+            r.frames(),
+            r.run.map(|run| run.0.to_string()).unwrap_or_default(),
+            r.run.map(|run| format!("{:.3}", run.1)).unwrap_or_default(),
+            fps_s(r.fps()).unwrap_or_default(),
+            r.sz,
+            kbs_s(r.kbs(fps_rate)).unwrap_or_default(),
+            r.run.map_or("", |run| &run.2).replace('"', "\"\"")
+        );
+    }
+    _ = writeln!(
+        csv,
+        "total,,,{tot_frames},,{secs:.3},{},{out_sz},{},",
+        fps_s(tot_fps).unwrap_or_default(),
+        kbs_s(tot_kbs).unwrap_or_default()
+    );
+
+    let stem = unsafe { args.out.file_stem().unwrap_unchecked() }.to_string_lossy();
+    _ = write(args.out.with_file_name(format!("{stem}_report.json")), js);
+    _ = write(args.out.with_file_name(format!("{stem}_report.csv")), csv);
+}
@@ -11,8 +11,8 @@ use crate::{
     chan::SpscRing,
     error::Xerr,
     ffms::{VidDecoder, VidInf},
-    fs::write as fs_write,
-    path::Path,
+    fs::{read, write as fs_write},
+    path::{Path, PathBuf},
     progs::ProgsBar,
     thread::{available_parallelism, spawn},
 };
@@ -23,7 +23,7 @@ fn detect<const HW: bool>(
     tot: usize,
     line: usize,
     md: usize,
-) -> Vec<u8> {
+) -> (Vec<u8>, Vec<f32>) {
     let ring = Arc::new(SpscRing::new());
     let ring2 = Arc::clone(&ring);
     let det = spawn(move || {
@@ -40,8 +40,12 @@ fn detect<const HW: bool>(
                 out.as_mut_ptr(),
             )
         };
-        unsafe { out.set_len(n) };
-        out
+        unsafe {
+            out.set_len(n);
+            // every frame got a weight, `-1` where none was measured
+            wt.set_len(tot);
+        }
+        (out, wt)
     });
     let (rp, mut pb) = (Arc::as_ptr(&ring).cast(), ProgsBar::new());
     let p = (&raw mut pb).cast();
@@ -79,12 +83,29 @@ pub fn fd_scenes(
     .map_err(|e| e.to_string())?;
 
     let md = usize::from(inf.is_10b) << usize::from(hwdec);
-    let content = if hwdec {
+    let (content, wt) = if hwdec {
         detect::<true>(&mut dec, dims, tot, line, md)
     } else {
         detect::<false>(&mut dec, dims, tot, line, md)
     };
 
     fs_write(sc_file, content)?;
+    let wt: Vec<u8> = wt.iter().flat_map(|w| w.to_le_bytes()).collect();
+    _ = fs_write(wt_path(sc_file), wt);
     Ok(())
 }
+
+// Per-frame SCD weights (LE f32) beside the scene file, for TQ CRF prediction & `--heavy-first`
+pub fn wt_path(sc_file: &Path) -> PathBuf {
+    sc_file.with_extension("wt")
+}
+
+// Weights from the last SCD run on this source; empty if missing or from another source
+pub fn load_wt(sc_file: &Path, frames: usize) -> Vec<f32> {
+    let wt: Vec<f32> = read(wt_path(sc_file))
+        .unwrap_or_default()
+        .chunks_exact(4)
+        .map(|b| b.try_into().map_or(-1.0, f32::from_le_bytes))
+        .collect();
+    if wt.len() == frames { wt } else { Vec::new() }
+}
@@ -0,0 +1,364 @@
+#[cfg(target_os = "linux")]
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write as _;
+
+use crate::{
+    byte_range::ByteRange,
+    cc608::CcScan,
+    copy::{Packet, Stream},
+    ffms::{AVMEDIA_TYPE_SUBTITLE, codec_id},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Sty {
+    i: bool,
+    b: bool,
+    u: bool,
+    color: Option<u32>,
+}
+
+const PLAIN: Sty = Sty {
+    i: false,
+    b: false,
+    u: false,
+    color: None,
+};
+
+type Cue = Vec<(Sty, String)>;
+
+// split per section: rustfmt rewraps one long literal across the blank line before `[Events]`
+const ASS_HEAD: &str = concat!(
+    "[Script Info]\nScriptType: v4.00+\nPlayResX: 384\nPlayResY: 288\nScaledBorderAndShadow: \
+     yes\n\n",
+    "[V4+ Styles]\nFormat: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, \
+     OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, \
+     Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n",
+    "Style: Default,Arial,16,&Hffffff,&Hffffff,&H0,&H0,0,0,0,0,100,100,0,0,1,1,0,2,10,10,10,0\n\n",
+    "[Events]\nFormat: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+);
+
+// Text subtitle codecs Matroska has no mapping for; rewritten in place to SRT or ASS blocks
+pub fn convert(s: &mut Stream, name: &str) -> bool {
+    let parse: fn(&[u8]) -> Cue = match name {
+        "mov_text" => tx3g,
+        "microdvd" => microdvd,
+        "sami" => sami,
+        "mpl2" | "vplayer" => mpl2,
+        "subviewer" | "subviewer1" | "text" => subviewer,
+        "eia_608" => {
+            let mut cc = CcScan::new();
+            for p in &s.packets {
+                cc.feed_cc(p.pts, &s.data[p.range.offset..p.range.offset + p.range.len]);
+            }
+            let cues = cc.decode();
+            *s = cc_stream(s, cues);
+            return !s.packets.is_empty();
+        }
+        _ => return false,
+    };
+    let mut cues: Vec<(i64, i64, Cue)> = Vec::with_capacity(s.packets.len());
+    for (k, p) in s.packets.iter().enumerate() {
+        let cue = parse(&s.data[p.range.offset..p.range.offset + p.range.len]);
+        if cue.iter().all(|c| c.1.trim().is_empty()) {
+            continue;
+        }
+        let dur = if p.duration > 0 {
+            p.duration
+        } else {
+            s.packets.get(k + 1).map_or(0, |n| n.pts - p.pts)
+        };
+        cues.push((p.pts, dur.max(0), cue));
+    }
+    let ass = cues
+        .iter()
+        .any(|c| c.2.iter().any(|&(st, _)| st.color.is_some()));
+    let mut data = Vec::new();
+    let mut packets = Vec::with_capacity(cues.len());
+    for (n, &(pts, duration, ref cue)) in cues.iter().enumerate() {
+        let text = if ass {
+            format!("{n},0,Default,,0,0,0,,{}", to_ass(cue))
+        } else {
+            to_srt(cue)
+        };
+        packets.push(Packet {
+            range: ByteRange {
+                offset: data.len(),
+                len: text.len(),
+            },
+            pts,
+            duration,
+        });
+        data.extend_from_slice(text.as_bytes());
+    }
+    s.data = data;
+    s.packets = packets;
+    s.codec_id = codec_id(if ass { c"ass" } else { c"subrip" });
+    s.extradata = if ass {
+        ASS_HEAD.as_bytes().to_vec()
+    } else {
+        Vec::new()
+    };
+    !s.packets.is_empty()
+}
+
+// Captions carried in the video SEI become an SRT track on the video's timeline
+pub fn cc_stream(tmpl: &Stream, cues: Vec<(i64, i64, String)>) -> Stream {
+    let mut data = Vec::new();
+    let mut packets = Vec::with_capacity(cues.len());
+    for (s, e, text) in cues {
+        packets.push(Packet {
+            range: ByteRange {
+                offset: data.len(),
+                len: text.len(),
+            },
+            pts: s,
+            duration: e - s,
+        });
+        data.extend_from_slice(text.as_bytes());
+    }
+    Stream {
+        data,
+        packets,
+        codec_id: codec_id(c"subrip"),
+        codec_type: AVMEDIA_TYPE_SUBTITLE,
+        channels: 0,
+        sample_rate: 0,
+        bit_depth: 0,
+        tb_num: tmpl.tb_num,
+        tb_den: tmpl.tb_den,
+        origin: tmpl.origin,
+        extradata: Vec::new(),
+        lang: tmpl.lang.clone(),
+    }
+}
+
+fn push(cue: &mut Cue, st: Sty, t: &str) {
+    match cue.last_mut() {
+        Some(&mut (s, ref mut text)) if s == st => text.push_str(t),
+        _ => cue.push((st, t.into())),
+    }
+}
+
+// tx3g sample: u16 text length, text, then style/highlight/karaoke boxes we drop
+fn tx3g(b: &[u8]) -> Cue {
+    let n = match *b {
+        [h, l, ..] => usize::from(u16::from_be_bytes([h, l])),
+        _ => 0,
+    };
+    let raw = b.get(2..2 + n).unwrap_or_default();
+    let text = if raw.starts_with(&[0xfe, 0xff]) {
+        let u: Vec<u16> = raw[2..]
+            .as_chunks::<2>()
+            .0
+            .iter()
+            .map(|&c| u16::from_be_bytes(c))
+            .collect();
+        String::from_utf16_lossy(&u)
+    } else {
+        String::from_utf8_lossy(raw).into_owned()
+    };
+    vec![(PLAIN, text.replace("\r\n", "\n").replace('\r', "\n"))]
+}
+
+// MicroDVD: `{y:i}`-style codes (lowercase = this line, uppercase = rest of cue), `|` breaks
+fn microdvd(b: &[u8]) -> Cue {
+    let src = String::from_utf8_lossy(b);
+    let mut cue = Cue::new();
+    let mut global = PLAIN;
+    for (k, line) in src.split('|').enumerate() {
+        let mut st = global;
+        let mut l = line;
+        if let Some(r) = l.strip_prefix('/') {
+            st.i = true;
+            l = r;
+        }
+        while let Some(r) = l.strip_prefix('{')
+            && let Some(end) = r.find('}')
+        {
+            let code = &r[..end];
+            let apply = |s: &mut Sty| match code.get(..2).map(str::to_ascii_lowercase).as_deref() {
+                Some("y:") => {
+                    s.i |= code.contains('i');
+                    s.b |= code.contains('b');
+                    s.u |= code.contains('u');
+                }
+                Some("c:") => {
+                    s.color = u32::from_str_radix(code[2..].trim_start_matches('$'), 16)
+                        .ok()
+                        .map(swap_rb);
+                }
+                _ => {}
+            };
+            apply(&mut st);
+            if code.starts_with(|c: char| c.is_ascii_uppercase()) {
+                apply(&mut global);
+            }
+            l = &r[end + 1..];
+        }
+        if k > 0 {
+            push(&mut cue, st, "\n");
+        }
+        push(&mut cue, st, l);
+    }
+    cue
+}
+
+// BGR <-> RGB
+const fn swap_rb(c: u32) -> u32 {
+    ((c >> 16) & 0xff) | (c & 0xff00) | ((c & 0xff) << 16)
+}
+
+fn named_color(c: &str) -> Option<u32> {
+    if let Some(h) = c.strip_prefix('#') {
+        return u32::from_str_radix(h, 16).ok();
+    }
+    Some(match c.to_ascii_lowercase().as_str() {
+        "white" => 0xff_ffff,
+        "black" => 0,
+        "red" => 0xff_0000,
+        "green" | "lime" => 0x00_ff00,
+        "blue" => 0x00_00ff,
+        "yellow" => 0xff_ff00,
+        "cyan" | "aqua" => 0x00_ffff,
+        "magenta" | "fuchsia" => 0xff_00ff,
+        "gray" | "grey" => 0x80_8080,
+        _ => return None,
+    })
+}
+
+fn entity(e: &str) -> &'static str {
+    match e {
+        "amp" => "&",
+        "lt" => "<",
+        "gt" => ">",
+        "quot" => "\"",
+        "apos" => "'",
+        _ => " ",
+    }
+}
+
+// SAMI: an HTML fragment per SYNC; keep <br>, <i>, <b>, <u> and <font color>
+fn sami(b: &[u8]) -> Cue {
+    let src = String::from_utf8_lossy(b);
+    let mut cue = Cue::new();
+    let mut stack = vec![PLAIN];
+    let mut st = PLAIN;
+    let mut rest = src.as_ref();
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix('<') {
+            let end = r.find('>').unwrap_or(r.len());
+            let tag = r[..end].trim();
+            let lower = tag.to_ascii_lowercase();
+            let name = lower.split_whitespace().next().unwrap_or_default();
+            match name {
+                "br" | "br/" => push(&mut cue, st, "\n"),
+                "i" => st.i = true,
+                "b" => st.b = true,
+                "u" => st.u = true,
+                "/i" => st.i = false,
+                "/b" => st.b = false,
+                "/u" => st.u = false,
+                "font" => {
+                    stack.push(st);
+                    if let Some(at) = lower.find("color=") {
+                        let v = tag[at + 6..].trim_matches(|c| c == '"' || c == '\'');
+                        let v = v.split(['"', '\'', ' ']).next().unwrap_or_default();
+                        st.color = named_color(v).or(st.color);
+                    }
+                }
+                "/font" => st = stack.pop().unwrap_or(PLAIN),
+                _ => {}
+            }
+            rest = r.get(end + 1..).unwrap_or_default();
+        } else if let Some(r) = rest.strip_prefix('&') {
+            let end = r.find(';').filter(|&e| e <= 6);
+            let (ent, next) = end.map_or(("", r), |e| (entity(&r[..e]), &r[e + 1..]));
+            push(&mut cue, st, if end.is_some() { ent } else { "&" });
+            rest = next;
+        } else {
+            let end = rest.find(['<', '&']).unwrap_or(rest.len());
+            let t: String = rest[..end].split_whitespace().collect::<Vec<_>>().join(" ");
+            if !t.is_empty() {
+                let lead = cue.last().is_some_and(|c| !c.1.ends_with(['\n', ' ']))
+                    && rest.starts_with(char::is_whitespace);
+                push(&mut cue, st, if lead { " " } else { "" });
+                push(&mut cue, st, &t);
+            }
+            rest = &rest[end..];
+        }
+    }
+    for &mut (_, ref mut t) in &mut cue {
+        *t = t.replace(" \n", "\n").replace("\n ", "\n");
+    }
+    cue
+}
+
+// MPL2/VPlayer: `|` line breaks, a leading `/` italicizes the line
+fn mpl2(b: &[u8]) -> Cue {
+    let src = String::from_utf8_lossy(b);
+    let mut cue = Cue::new();
+    for (k, line) in src.split('|').enumerate() {
+        let (st, l) = line
+            .strip_prefix('/')
+            .map_or((PLAIN, line), |r| (Sty { i: true, ..PLAIN }, r));
+        if k > 0 {
+            push(&mut cue, PLAIN, "\n");
+        }
+        push(&mut cue, st, l);
+    }
+    cue
+}
+
+fn subviewer(b: &[u8]) -> Cue {
+    let t = String::from_utf8_lossy(b)
+        .replace("[br]", "\n")
+        .replace("\r\n", "\n");
+    vec![(PLAIN, t)]
+}
+
+fn to_srt(cue: &Cue) -> String {
+    let mut out = String::new();
+    let mut cur = PLAIN;
+    for &(st, ref t) in cue {
+        for (on, was, open, close) in [
+            (st.b, cur.b, "<b>", "</b>"),
+            (st.i, cur.i, "<i>", "</i>"),
+            (st.u, cur.u, "<u>", "</u>"),
+        ] {
+            if on != was {
+                out.push_str(if on { open } else { close });
+            }
+        }
+        cur = st;
+        out.push_str(t);
+    }
+    for (on, close) in [(cur.u, "</u>"), (cur.i, "</i>"), (cur.b, "</b>")] {
+        if on {
+            out.push_str(close);
+        }
+    }
+    out.trim().into()
+}
+
+fn to_ass(cue: &Cue) -> String {
+    let mut out = String::new();
+    let mut cur = PLAIN;
+    for &(st, ref t) in cue {
+        if st != cur {
+            _ = write!(
+                out,
+                "{{\\i{}\\b{}\\u{}",
+                u8::from(st.i),
+                u8::from(st.b),
+                u8::from(st.u)
+            );
+            match st.color {
+                Some(c) => _ = write!(out, "\\c&H{:06X}&}}", swap_rb(c)),
+                None => out.push_str("\\c}"),
+            }
+            cur = st;
+        }
+        out.push_str(&t.replace('\n', "\\N"));
+    }
+    out
+}
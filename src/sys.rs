@@ -1,4 +1,8 @@
-use core::{arch::asm, ffi::c_void};
+use core::{
+    arch::{asm, naked_asm},
+    ffi::c_void,
+    mem::transmute_copy,
+};
 
 macro_rules! syscall {
     ($nr:expr) => { syscall!(@ $nr,) };
@@ -126,14 +130,35 @@ struct Sigaction {
     mask: u64,
 }
 
-pub fn sigaction(sig: i32, handler: usize) {
+const SA_RESTORER: usize = 0x0400_0000;
+const SA_RESTART: usize = 0x1000_0000;
+
+// x86_64 handlers return through rt_sigreturn, which the kernel needs as sa_restorer
+#[unsafe(naked)]
+unsafe extern "C" fn sigreturn() {
+    naked_asm!("mov eax, 15", "syscall");
+}
+
+// Returns the handler it replaced
+pub fn sigaction(sig: i32, handler: usize) -> usize {
     let act = Sigaction {
         handler,
+        flags: SA_RESTORER | SA_RESTART,
+        restorer: unsafe { transmute_copy(&(sigreturn as unsafe extern "C" fn())) },
+        mask: 0,
+    };
+    let mut old = Sigaction {
+        handler: 0,
         flags: 0,
         restorer: 0,
         mask: 0,
     };
-    syscall!(13, sig, &raw const act, 0usize, 8usize);
+    syscall!(13, sig, &raw const act, &raw mut old, 8usize);
+    old.handler
+}
+
+pub fn kill(pid: i32, sig: i32) -> i32 {
+    syscall!(62, pid, sig) as i32
 }
 
 #[repr(C)]
@@ -216,6 +241,10 @@ pub fn unlinkat(path: *const u8, flags: i32) -> i32 {
     syscall!(263, AT_FDCWD, path, flags) as i32
 }
 
+pub fn renameat(old: *const u8, new: *const u8) -> i32 {
+    syscall!(264, AT_FDCWD, old, AT_FDCWD, new) as i32
+}
+
 pub fn getdents64(fd: i32, buf: *mut u8, count: usize) -> isize {
     syscall!(217, fd, buf, count) as isize
 }
@@ -259,13 +288,46 @@ pub fn ftruncate(fd: i32, len: i64) -> i32 {
     syscall!(77, fd, len) as i32
 }
 
+pub const AF_UNIX: i32 = 1;
+pub const SOCK_STREAM: i32 = 1;
+pub const SOCK_CLOEXEC: i32 = O_CLOEXEC;
+pub const SHUT_RDWR: i32 = 2;
+
+#[repr(C)]
+pub struct SockaddrUn {
+    pub family: u16,
+    pub path: [u8; 108],
+}
+
+pub fn socket(domain: i32, ty: i32, proto: i32) -> i32 {
+    syscall!(41, domain, ty, proto) as i32
+}
+
+pub fn bind(fd: i32, addr: *const SockaddrUn, len: u32) -> i32 {
+    syscall!(49, fd, addr, len) as i32
+}
+
+pub fn listen(fd: i32, backlog: i32) -> i32 {
+    syscall!(50, fd, backlog) as i32
+}
+
+pub fn accept4(fd: i32, flags: i32) -> i32 {
+    syscall!(288, fd, 0usize, 0usize, flags) as i32
+}
+
+pub fn shutdown(fd: i32, how: i32) -> i32 {
+    syscall!(48, fd, how) as i32
+}
+
 #[repr(C)]
 pub struct Stat {
     _pre: [u8; 24],
     pub st_mode: u32,
     _mid: [u8; 20],
     pub st_size: i64,
-    _post: [u8; 88],
+    _blk: [u8; 32],
+    pub st_mtime: i64,
+    _post: [u8; 48],
 }
 
 #[cfg(feature = "vship")]
@@ -273,6 +335,10 @@ pub fn copy_file_range(fd_in: i32, fd_out: i32, len: usize) -> isize {
     syscall!(326, fd_in, 0usize, fd_out, 0usize, len, 0usize) as isize
 }
 
+pub fn pread64(fd: i32, buf: *mut u8, count: usize, off: u64) -> isize {
+    syscall!(17, fd, buf, count, off) as isize
+}
+
 pub fn fstat(fd: i32, buf: *mut Stat) -> i32 {
     syscall!(5, fd, buf) as i32
 }
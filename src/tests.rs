@@ -13,10 +13,11 @@ use std::{
 #[cfg(feature = "vship")]
 use crate::vship::{VshipProcessor, init_device, load_disp};
 use crate::{
-    chan::{Semaphore, SpscRing, sem_release, spsc_close, spsc_recv, spsc_send},
-    chunk::{chnkify, load_scenes},
+    chan::{Semaphore, SpscRing, sem_close, sem_release, spsc_close, spsc_recv, spsc_send},
+    chunk::{Chunk, chnkify, load_scenes},
     dec::dec_chnks,
     encoder::{EncConfig, set_svt_base, set_svt_crf},
+    error::{SIG_IGN, SIGPIPE, signal},
     ffms::{DecStrat, VidDecoder, VidInf, get_dec_strat, get_vidinf},
     fs::{File, metadata, remove_file},
     io::{BufWriter, Write},
@@ -436,8 +437,7 @@ fn val_tq(
         pipe.final_w as u32,
         pipe.final_h as u32,
         inf,
-        true,
-        false,
+        [false, true, false],
         Some(disp),
     )
     .unwrap();
@@ -550,7 +550,7 @@ fn run_test(
             let send = move |p: WorkPkg| unsafe {
                 spsc_send(rp, Box::into_raw(Box::new(p)) as u64);
             };
-            dec_chnks(&chnks, &inp, &inf, &send, &BTreeSet::new(), strat, &sem);
+            dec_chnks(&chnks, &inp, &inf, &send, &BTreeSet::new(), strat, &sem).unwrap();
             unsafe { spsc_close(rp) };
         }
     });
@@ -593,6 +593,115 @@ fn run_test(
     strat
 }
 
+// Every chunk as `(idx, frames, first frame)`, decoded in the order given
+fn dec_firsts(
+    chnks: &[Chunk],
+    inp: &Path,
+    inf: &VidInf,
+    strat: DecStrat,
+) -> Vec<(u16, usize, Vec<u8>)> {
+    let ring = Arc::new(SpscRing::new());
+    let ring2 = Arc::clone(&ring);
+    let sem = Arc::new(Semaphore::new(1));
+    let handle = pspawn({
+        let (chnks, inp, inf) = (chnks.to_vec(), inp.to_path_buf(), inf.clone());
+        let sem = Arc::clone(&sem);
+        move || {
+            let rp = Arc::as_ptr(&ring);
+            let send = move |p: WorkPkg| unsafe {
+                spsc_send(rp, Box::into_raw(Box::new(p)) as u64);
+            };
+            dec_chnks(&chnks, &inp, &inf, &send, &BTreeSet::new(), strat, &sem).unwrap();
+            unsafe { spsc_close(rp) };
+        }
+    });
+
+    let mut out = Vec::new();
+    loop {
+        let m = unsafe { spsc_recv(Arc::as_ptr(&ring2)) };
+        if m == 0 {
+            break;
+        }
+        let pkg = unsafe { Box::from_raw(m as *mut WorkPkg) };
+        let fsz = pkg.yuv.len() / pkg.frame_cnt;
+        out.push((pkg.chnk.idx, pkg.frame_cnt, pkg.yuv[..fsz].to_vec()));
+        sem_release(&sem);
+    }
+    handle.join();
+    out
+}
+
+// `--heavy-first` order: chunk 0 last, after the decoder has moved past it
+#[test]
+fn dec_out_of_order() {
+    let inp = test_path("8b_768x480.mp4");
+    let inf = get_vidinf(&inp).unwrap();
+    let strat = get_dec_strat(&inf, (0, 0), false, false);
+    let scenes = load_scenes(&test_path("scenes.txt"), inf.frames, false).unwrap();
+    let chnks = chnkify(&scenes);
+    assert!(chnks.len() > 1, "need several scenes to reorder");
+
+    let mut want = dec_firsts(&chnks, &inp, &inf, strat);
+    let rev: Vec<Chunk> = chnks.iter().rev().cloned().collect();
+    let mut got = dec_firsts(&rev, &inp, &inf, strat);
+    assert_eq!(got.first().map(|g| g.0), chnks.last().map(|c| c.idx));
+
+    want.sort_by_key(|w| w.0);
+    got.sort_by_key(|g| g.0);
+    assert_eq!(want.len(), got.len());
+    for (w, g) in want.iter().zip(&got) {
+        assert_eq!(w.1, g.1, "chunk {} frame count", w.0);
+        assert!(w.2 == g.2, "chunk {} first frame differs out of order", w.0);
+    }
+}
+
+// A stop returns no permits; closing the semaphore must still end the decoder
+#[test]
+fn dec_stops_on_sem_close() {
+    let inp = test_path("8b_768x480.mp4");
+    let inf = get_vidinf(&inp).unwrap();
+    let strat = get_dec_strat(&inf, (0, 0), false, false);
+    let scenes = load_scenes(&test_path("scenes.txt"), inf.frames, false).unwrap();
+    let chnks = chnkify(&scenes);
+    assert!(chnks.len() > 1, "need several scenes to stop early");
+
+    let ring = Arc::new(SpscRing::new());
+    let ring2 = Arc::clone(&ring);
+    let sem = Arc::new(Semaphore::new(1));
+    let handle = pspawn({
+        let sem = Arc::clone(&sem);
+        move || {
+            let rp = Arc::as_ptr(&ring);
+            let send = move |p: WorkPkg| unsafe {
+                spsc_send(rp, Box::into_raw(Box::new(p)) as u64);
+            };
+            dec_chnks(&chnks, &inp, &inf, &send, &BTreeSet::new(), strat, &sem).unwrap();
+            unsafe { spsc_close(rp) };
+        }
+    });
+
+    let first = unsafe { spsc_recv(Arc::as_ptr(&ring2)) };
+    assert_ne!(first, 0);
+    drop(unsafe { Box::from_raw(first as *mut WorkPkg) });
+    sem_close(&sem);
+    handle.join();
+    assert_eq!(unsafe { spsc_recv(Arc::as_ptr(&ring2)) }, 0);
+}
+
+// xav ignores SIGPIPE & exec keeps that, so spawn must hand children the default back
+#[test]
+fn child_gets_default_sigpipe() {
+    let prev = signal(SIGPIPE, SIG_IGN);
+    let out = Command::new("grep")
+        .args(["SigIgn", "/proc/self/status"])
+        .output();
+    signal(SIGPIPE, prev);
+    let out = out.unwrap();
+    let line = String::from_utf8_lossy(&out.stdout);
+    let mask = u64::from_str_radix(line.trim_start_matches("SigIgn:").trim(), 16).unwrap();
+    assert_eq!(mask & (1 << (SIGPIPE - 1)), 0);
+}
+
 #[test]
 fn strat_coverage() {
     use DecStrat::*;
@@ -1193,4 +1302,1020 @@ mod tq {
     );
     tq_hw!(dim_hw_8b_2w2h, "8b_718x478.mp4", (0, 0), true, HwNv12Stride);
     tq_hw!(dim_hw_8b_4w8h, "8b_716x480.mp4", (0, 0), true, HwNv12Stride);
+
+    // An interrupt ends the decoder early: the coordinator goes by what it sent, not the total
+    #[test]
+    fn coord_ends_with_stopped_decoder() {
+        use crate::{
+            chan::{SeqRing, mpsc_send, spmc_recv},
+            enc::test_access::tq_coord,
+        };
+
+        let (coord, enc) = (SeqRing::new(), SeqRing::new());
+        let sent = AtomicUsize::new(1);
+        unsafe {
+            mpsc_send(&raw const coord, 1);
+            mpsc_send(&raw const coord, 2);
+        }
+        tq_coord(&coord, &enc, 5, &Semaphore::new(4), &sent);
+        assert_eq!(unsafe { spmc_recv(&raw const enc) }, 0);
+    }
+
+    // `scdN` keeps a step's frame count: the first frame, then the highest weights
+    #[test]
+    fn scd_pick_takes_busiest_frames() {
+        use crate::tq::scd_pick;
+
+        let wt = [9.0, 0.1, 5.0, 0.2, -1.0, 3.0, 3.0, 0.0, 0.3];
+        let picked: Vec<usize> = scd_pick(&wt, 4)
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &p)| p.then_some(i))
+            .collect();
+        assert_eq!(picked, [0, 2, 5], "ties go to the earlier frame");
+        assert!(
+            scd_pick(&wt, 1).iter().all(|&p| p),
+            "a step of 1 scores all"
+        );
+    }
+
+    // Reopening compacts: one line per config, chunk & CRF, the latest kept
+    #[test]
+    fn probe_cache_dedups_on_open() {
+        use crate::{
+            fs::{read_to_string, remove_dir_all},
+            manifest::src_key,
+            parse_args_loop,
+            tq::Probe,
+            tqcache::ProbeCache,
+        };
+
+        let tmp = env::temp_dir().to_string_lossy().into_owned();
+        let dir = PathBuf::from(format!("{tmp}/xav_test_cache_{}", process::id()));
+        let inp = test_path("8b_768x480.mp4");
+        let argv: Vec<String> = ["xav", "-t", "80", "in.mkv"].map(String::from).into();
+        let args = parse_args_loop(&argv).unwrap();
+        let inf = get_vidinf(&inp).unwrap();
+        let chnks = chnkify(&load_scenes(&test_path("scenes.txt"), inf.frames, true).unwrap());
+        let probe = |crf, score| Probe {
+            crf,
+            score,
+            also: Vec::new(),
+        };
+
+        let cache = ProbeCache::open_in(&dir, &inp, &args, "ssimu2").unwrap();
+        for (crf, score) in [(30.0, 80.0), (30.0, 81.0), (30.0, 82.0), (34.0, 75.0)] {
+            cache.put(&chnks[0], &probe(crf, score), 1000);
+        }
+        drop(cache);
+
+        let cache = ProbeCache::open_in(&dir, &inp, &args, "ssimu2").unwrap();
+        let mut got = cache.get(&chnks[0]);
+        got.sort_by(|a, b| a.0.crf.total_cmp(&b.0.crf));
+        assert_eq!(got.len(), 2);
+        assert!((got[0].0.score - 82.0).abs() < 1e-3);
+        let file = dir.join(format!("{}.txt", src_key(&inp).unwrap()));
+        let text = read_to_string(&file).unwrap();
+        _ = remove_dir_all(&dir);
+        assert_eq!(text.lines().count(), 2);
+    }
+
+    // Probes every 4 CRF across `fc ± 20` of a score falling `slope` per CRF, 80 at `fc`
+    fn line(fc: f32, slope: f32) -> Vec<(f32, f32)> {
+        (0..=10)
+            .map(|i| {
+                let crf = 4.0f32.mul_add(i as f32, fc - 20.0);
+                (crf, slope.mul_add(fc - crf, 80.0))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn crf_band_stays_in_tolerance() {
+        use crate::tq::test_access::crf_band;
+
+        let tq = (80.0, 2.0);
+        assert_eq!(
+            crf_band(&mut line(30.0, 1.0), 30.0, tq, 0.25, false),
+            (28.0, 32.0)
+        );
+        assert_eq!(
+            crf_band(&mut line(30.0, 1.0), 30.0, tq, 0.25, true),
+            (28.0, 30.0)
+        );
+        // a CRF outside tolerance has no band to move in
+        assert_eq!(
+            crf_band(&mut line(30.0, 1.0), 35.0, tq, 0.25, false),
+            (35.0, 35.0)
+        );
+        // nor past the probed span: a flat curve is cut at its last probe
+        assert_eq!(
+            crf_band(&mut line(30.0, 0.0), 30.0, tq, 0.25, false),
+            (10.0, 50.0)
+        );
+        assert_eq!(
+            crf_band(&mut Vec::new(), 30.0, tq, 0.25, false),
+            (30.0, 30.0)
+        );
+    }
+
+    #[test]
+    fn smooth_limits_neighbour_delta() {
+        use crate::tq::smooth_crfs;
+
+        let finals = [20.0, 30.0, 20.0, 30.0, 26.0];
+        let mut logs: Vec<_> = finals.iter().map(|&f| (line(f, 0.1), f)).collect();
+        let crfs = smooth_crfs(&mut logs, (80.0, 2.0), 0.25, false, 4.0);
+        assert_eq!(crfs, [20.0, 24.0, 20.0, 24.0, 26.0]);
+        for (a, b) in crfs.iter().zip(crfs.iter().skip(1)) {
+            assert!((a - b).abs() <= 4.0, "{crfs:?}");
+        }
+    }
+
+    // A narrow band wins over `delta`: no chunk leaves `-t` to please a neighbour
+    #[test]
+    fn smooth_keeps_each_band() {
+        use crate::tq::smooth_crfs;
+
+        let chunks = [(20.0, 0.1), (30.0, 2.0), (20.0, 0.1), (34.0, 0.5)];
+        let mut logs: Vec<_> = chunks.iter().map(|&(f, s)| (line(f, s), f)).collect();
+        for down_only in [false, true] {
+            let crfs = smooth_crfs(&mut logs, (80.0, 2.0), 0.25, down_only, 2.0);
+            for (&c, &(f, slope)) in crfs.iter().zip(&chunks) {
+                assert!(
+                    slope * (c - f).abs() <= 2.0 + 1e-3,
+                    "{c} left the band of {f}: {crfs:?}"
+                );
+                assert!(!down_only || c <= f, "{c} rose from {f}");
+            }
+            assert!((crfs[1] - 29.0).abs() < 1e-3, "{crfs:?}");
+        }
+    }
+
+    // `encN` probes see frames 0, N, 2N..; the whole chunk is back once the probe is done
+    #[test]
+    fn thin_probe_takes_every_nth_frame() {
+        use crate::enc::test_access::with_thin;
+
+        let yuv: Vec<u8> = (0..10u8).flat_map(|f| [f, f]).collect();
+        let mut pkg = WorkPkg::new(chunk(0, 0, 10), yuv.clone(), 10, 2, 1);
+        let seen = with_thin(&mut pkg, 2, 4, |p| (p.frame_cnt, p.yuv.clone()));
+        assert_eq!(seen, (3, vec![0, 0, 4, 4, 8, 8]));
+        assert_eq!((pkg.frame_cnt, &pkg.yuv), (10, &yuv));
+        assert_eq!(with_thin(&mut pkg, 2, 1, |p| p.frame_cnt), 10);
+    }
+
+    // Nothing meets `--tq-and`: the search halves its way down & ends on a probe at `qp_min`
+    #[test]
+    fn unmet_search_reaches_qp_min() {
+        use crate::enc::test_access::unmet_search;
+
+        let crfs = unmet_search(10.0, 50.0);
+        assert!(crfs.len() > 2 && crfs.len() < 32, "{crfs:?}");
+        assert!(crfs.windows(2).all(|w| w[1] < w[0]), "{crfs:?}");
+        assert_eq!(crfs.last().copied(), Some(10.0));
+    }
+
+    fn chunk(idx: u16, start: usize, end: usize) -> Chunk {
+        Chunk {
+            idx,
+            tmpl: 0,
+            start,
+            end,
+            params: None,
+        }
+    }
+
+    // No prediction until `MIN_SEEDS` (4) chunks finished
+    #[test]
+    fn seeds_need_four_chunks() {
+        use crate::tq::CrfSeeds;
+
+        let seeds = CrfSeeds::with_wt(Vec::new());
+        let c = chunk(9, 0, 100);
+        for i in 0..3 {
+            seeds.add(&chunk(i, 0, 100), 30.0);
+            assert!(seeds.predict(&c, 10.0, 50.0).is_none(), "{} seeds", i + 1);
+        }
+        seeds.add(&chunk(3, 0, 100), 30.0);
+        assert!(seeds.predict(&c, 10.0, 50.0).is_some());
+    }
+
+    // Seeds that agree leave no deviation: the window is still `MIN_HALF` (2) either side,
+    // cut at the CRF range
+    #[test]
+    fn seeds_window_floor() {
+        use crate::tq::CrfSeeds;
+
+        let seeds = CrfSeeds::with_wt(Vec::new());
+        for i in 0..4 {
+            seeds.add(&chunk(i, 0, 100), 30.0);
+        }
+        let c = chunk(9, 0, 100);
+        assert_eq!(seeds.predict(&c, 10.0, 50.0), Some((30.0, 28.0, 32.0)));
+        assert_eq!(seeds.predict(&c, 29.0, 50.0), Some((30.0, 29.0, 32.0)));
+    }
+
+    // Without SCD weights length alone counts: a long chunk leans to the long seed's CRF
+    #[test]
+    fn seeds_weigh_length() {
+        use crate::tq::CrfSeeds;
+
+        let seeds = CrfSeeds::with_wt(Vec::new());
+        for i in 0..3 {
+            seeds.add(&chunk(i, 0, 100), 20.0);
+        }
+        seeds.add(&chunk(3, 0, 1000), 40.0);
+        let (long, ..) = seeds.predict(&chunk(9, 0, 1000), 10.0, 50.0).unwrap();
+        let (short, ..) = seeds.predict(&chunk(9, 0, 100), 10.0, 50.0).unwrap();
+        assert!(long > 30.0 && short < 23.0, "long {long}, short {short}");
+    }
+
+    // Equal lengths: a busy chunk follows the busy seeds, a static one the static seeds
+    #[test]
+    fn seeds_weigh_scd_activity() {
+        use crate::tq::CrfSeeds;
+
+        // 100-frame chunks: static ones at 0.1 per frame, busy ones at 0.9
+        let act = [0.1, 0.9, 0.1, 0.9, 0.9, 0.1];
+        let wt: Vec<f32> = act.iter().flat_map(|&a| [a; 100]).collect();
+        let seeds = CrfSeeds::with_wt(wt);
+        for (i, crf) in [36.0, 24.0, 36.0, 24.0].into_iter().enumerate() {
+            seeds.add(&chunk(i as u16, i * 100, (i + 1) * 100), crf);
+        }
+        let (busy, lo, hi) = seeds.predict(&chunk(4, 400, 500), 10.0, 50.0).unwrap();
+        let (calm, ..) = seeds.predict(&chunk(5, 500, 600), 10.0, 50.0).unwrap();
+        assert!(busy < 28.0 && calm > 32.0, "busy {busy}, static {calm}");
+        assert!(lo < busy && busy < hi, "{lo}..{hi}");
+    }
+}
+
+mod subs {
+    use crate::{
+        byte_range::ByteRange,
+        cc608::CcScan,
+        copy::{Packet, Stream},
+        ffms::AVMEDIA_TYPE_SUBTITLE,
+        subconv::convert,
+    };
+
+    fn stream(cues: &[&str]) -> Stream {
+        let mut data = Vec::new();
+        let mut packets = Vec::new();
+        for (k, c) in cues.iter().enumerate() {
+            packets.push(Packet {
+                range: ByteRange {
+                    offset: data.len(),
+                    len: c.len(),
+                },
+                pts: k as i64 * 100,
+                duration: 100,
+            });
+            data.extend_from_slice(c.as_bytes());
+        }
+        Stream {
+            data,
+            packets,
+            codec_id: 0,
+            codec_type: AVMEDIA_TYPE_SUBTITLE,
+            channels: 0,
+            sample_rate: 0,
+            bit_depth: 0,
+            tb_num: 1,
+            tb_den: 1000,
+            origin: 0,
+            extradata: Vec::new(),
+            lang: None,
+        }
+    }
+
+    fn texts(s: &Stream) -> Vec<&str> {
+        s.packets
+            .iter()
+            .map(|p| str::from_utf8(&s.data[p.range.offset..p.range.offset + p.range.len]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn microdvd_color_to_ass() {
+        let mut s = stream(&["{c:$0000FF}red|{y:i}slanted"]);
+        assert!(convert(&mut s, "microdvd"));
+        let head = str::from_utf8(&s.extradata).unwrap();
+        assert!(head.contains("\n\n[Events]\nFormat: Layer,"), "{head:?}");
+        assert!(!head.contains('\\'), "{head:?}");
+        let t = texts(&s)[0];
+        assert!(t.starts_with("0,0,Default,,0,0,0,,"), "{t}");
+        assert!(t.contains("\\c&H0000FF&}red"), "{t}");
+        assert!(t.contains("\\N"), "{t}");
+    }
+
+    #[test]
+    fn sami_to_srt() {
+        let mut s = stream(&["Hi<br><i>there</i> you", "  ", "a&lt;b"]);
+        assert!(convert(&mut s, "sami"));
+        assert!(s.extradata.is_empty());
+        assert_eq!(texts(&s), ["Hi\n<i>there</i> you", "a<b"]);
+        assert_eq!(s.packets[1].pts, 200);
+    }
+
+    #[test]
+    fn mpl2_italic_line() {
+        let mut s = stream(&["/slanted|plain"]);
+        assert!(convert(&mut s, "mpl2"));
+        assert_eq!(texts(&s), ["<i>slanted</i>\nplain"]);
+        assert_eq!(s.packets[0].duration, 100);
+    }
+
+    // cc_data triplets on field 1 for each pair
+    fn cc(pairs: &[[u8; 2]]) -> Vec<u8> {
+        pairs.iter().flat_map(|p| [0xfc, p[0], p[1]]).collect()
+    }
+
+    const RCL: [u8; 2] = [0x14, 0x20];
+    const EOC: [u8; 2] = [0x14, 0x2f];
+    const EDM: [u8; 2] = [0x14, 0x2c];
+
+    #[test]
+    fn cc608_pop_on() {
+        let mut sc = CcScan::new();
+        sc.feed_cc(0, &cc(&[RCL, RCL, *b"HI"]));
+        sc.feed_cc(1000, &cc(&[EOC, EOC]));
+        sc.feed_cc(3000, &cc(&[EDM, EDM]));
+        assert_eq!(sc.decode(), [(1000, 3000, "HI".into())]);
+    }
+
+    #[test]
+    fn cc608_flushes_at_eof() {
+        let mut sc = CcScan::new();
+        sc.feed_cc(0, &cc(&[RCL, *b"HI"]));
+        sc.feed_cc(1000, &cc(&[EOC]));
+        sc.feed_cc(2000, &cc(&[*b"  "]));
+        assert_eq!(sc.decode(), [(1000, 3000, "HI".into())]);
+    }
+}
+
+mod resume {
+    use crate::{parse_args_loop, val_resume};
+
+    fn argv(s: &str) -> Vec<String> {
+        s.split(' ').map(String::from).collect()
+    }
+
+    fn check(saved: &str, cur: &str) -> bool {
+        let cur = argv(cur);
+        let Ok(args) = parse_args_loop(&cur) else {
+            return false;
+        };
+        val_resume(&cur, &argv(saved), &args).is_ok()
+    }
+
+    #[test]
+    fn same_or_subset_resumes() {
+        let saved = "xav -e svt-av1 -p --crf=30 in.mkv out.mkv";
+        assert!(check(saved, "xav in.mkv"));
+        assert!(check(
+            saved,
+            "xav --keep-work --progress json in.mkv out.mkv"
+        ));
+        assert!(check(saved, "xav -e svt-av1 -p --crf=30 in.mkv out.mkv"));
+        assert!(check(saved, "xav --redo 3 -p --crf=20 in.mkv"));
+    }
+
+    #[test]
+    fn changed_settings_refuse() {
+        let saved = "xav -e svt-av1 -p --crf=30 in.mkv out.mkv";
+        assert!(!check(saved, "xav -p --crf=20 in.mkv"));
+        assert!(!check(saved, "xav -b 2 in.mkv"));
+        assert!(!check(saved, "xav in.mkv other.mkv"));
+    }
+
+    // No chunks at all: the error names the count, it can't underflow to a last index
+    #[test]
+    fn redo_without_chunks_errs() {
+        use crate::{chunk::apply_redo, path::Path};
+
+        let redo = (vec![0u16], String::new());
+        let e = apply_redo(Path::new("/nonexistent"), &mut [], Some(&redo)).unwrap_err();
+        assert!(e.to_string().contains("there are 0 chunks"), "{e}");
+    }
+}
+
+mod norm {
+    use std::{
+        f32::consts::{FRAC_PI_4, TAU},
+        iter::repeat_with,
+    };
+
+    use crate::{
+        audio::{AuBrate, parse_au_arg},
+        norm::{
+            compress, limit, measure, peaks,
+            test_access::{HEAD, gain_simd, tp_scalar, tp_simd},
+        },
+    };
+
+    // Deterministic stereo noise in [-1, 1)
+    fn noise(frames: usize) -> Vec<f32> {
+        let mut x: u32 = 0x2545_f491;
+        repeat_with(|| {
+            x = x.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            (x >> 8) as f32 / 8_388_608.0 - 1.0
+        })
+        .take(frames * 2)
+        .collect()
+    }
+
+    // A tone at a quarter of the rate, 45° off: every sample sits at ±0.707, peaks fall between
+    fn tone(lead: usize, frames: usize, amp: f32) -> Vec<f32> {
+        (0..lead * 2 + frames)
+            .flat_map(|n| {
+                let on = (lead..lead + frames).contains(&n);
+                let v = if on {
+                    amp * (FRAC_PI_4 * (2 * n + 1) as f32).sin()
+                } else {
+                    0.0
+                };
+                [v, v * 0.5]
+            })
+            .collect()
+    }
+
+    // Only `norm`/`drc` alone or with `(...)`: a repeated prefix is not a bitrate either
+    #[test]
+    fn norm_arg_forms() {
+        let norm = |arg: &str| match parse_au_arg(arg).map(|s| s.brate) {
+            Ok(AuBrate::Norm(n)) => Some((n.i, n.brate, n.drc)),
+            _ => None,
+        };
+        assert!(norm("norm all").is_some_and(|n| !n.2));
+        assert!(norm("drc all").is_some_and(|n| n.2));
+        assert_eq!(norm("norm(-16,-1.5,16,192) all"), Some((-16.0, 192, false)));
+        assert_eq!(norm("drc(-20,-2,12) all"), Some((-20.0, 128, true)));
+        for arg in ["normnorm all", "normdrc all", "drcnorm all", "norm-16 all"] {
+            assert!(parse_au_arg(arg).is_err(), "{arg}");
+        }
+    }
+
+    #[test]
+    fn limit_holds_true_peak_ceiling() {
+        let mut pcm = tone(1000, 4000, 1.0);
+        let (true_pk, samp_pk) = peaks(&pcm);
+        assert!(
+            true_pk > samp_pk * 1.3,
+            "no inter-sample peak: {true_pk} vs {samp_pk}"
+        );
+
+        let ceil = 0.5;
+        limit(&mut pcm, ceil);
+        let (true_pk, samp_pk) = peaks(&pcm);
+        assert!(true_pk <= ceil * 1.001, "true peak {true_pk} over {ceil}");
+        assert!(
+            samp_pk > ceil * 0.6,
+            "limited far below the ceiling: {samp_pk}"
+        );
+    }
+
+    #[test]
+    fn limit_leaves_quiet_audio() {
+        let mut pcm = tone(1000, 4000, 0.25);
+        let orig = pcm.clone();
+        limit(&mut pcm, 0.5);
+        assert_eq!(pcm, orig, "gain applied under the ceiling");
+    }
+
+    // Odd lengths reach the kernels' tails
+    #[test]
+    fn tp_simd_matches_scalar() {
+        for frames in [HEAD + 1, HEAD + 7, 1000, 4099] {
+            let pcm = noise(frames);
+            let mut out = vec![0f32; frames - HEAD];
+            tp_simd(&pcm, HEAD, &mut out);
+            for (i, &v) in out.iter().enumerate() {
+                let want = tp_scalar(&pcm, HEAD + i);
+                assert!(
+                    (v - want).abs() <= 1e-5,
+                    "frames {frames}, at {}: {v} vs {want}",
+                    HEAD + i
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn gain_simd_matches_scalar() {
+        for frames in [1, 7, 1000, 4099] {
+            let mut pcm = noise(frames);
+            let g: Vec<f32> = (0..frames)
+                .map(|i| 1.0 - i as f32 / frames as f32)
+                .collect();
+            let want: Vec<f32> = pcm
+                .chunks_exact(2)
+                .zip(&g)
+                .flat_map(|(s, &g)| s.iter().map(move |&v| v * g))
+                .collect();
+            gain_simd(&mut pcm, &g);
+            assert_eq!(pcm, want, "frames {frames}");
+        }
+    }
+
+    // 4 s loud & 4 s quiet in turn: a wide range the compressor should pull in
+    #[test]
+    fn compress_moves_lra_to_target() {
+        let rate: usize = 48_000;
+        let pcm: Vec<f32> = (0..rate * 48)
+            .flat_map(|n| {
+                let amp = if (n / (rate * 4)).is_multiple_of(2) {
+                    0.5
+                } else {
+                    0.1
+                };
+                // 1 kHz: a period of 48 samples
+                let v = amp * (n.rem_euclid(48) as f32 * TAU / 48.0).sin();
+                [v, v]
+            })
+            .collect();
+        let (_, before) = measure(&pcm);
+        let target = 5.0;
+        assert!(before > target + 4.0, "source range too narrow: {before}");
+
+        let mut out = pcm;
+        compress(&mut out, before, target);
+        let (_, after) = measure(&out);
+        assert!(
+            (after - target).abs() < (before - target).abs() - 2.0,
+            "LRA {before} -> {after}, target {target}"
+        );
+    }
+}
+
+#[cfg(feature = "vship")]
+mod stats {
+    use crate::tq::{Agg, Metric, parse_also, reduce, val_agg};
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-4
+    }
+
+    // Higher is better unless `worse_high`; one frame per second so `winN` spans N scores
+    fn agg(s: &str, scores: &[f32], worse_high: bool) -> f32 {
+        reduce(
+            &mut scores.to_vec(),
+            Agg::parse(s).unwrap(),
+            worse_high,
+            1.0,
+        )
+    }
+
+    #[test]
+    fn agg_parse_round_trips() {
+        for s in [
+            "mean",
+            "hmean",
+            "min",
+            "max",
+            "median",
+            "p5",
+            "p100",
+            "mean-1.5sd",
+            "win2",
+            "win0.5",
+        ] {
+            assert_eq!(Agg::parse(s).unwrap().name(), s);
+        }
+    }
+
+    #[test]
+    fn agg_parse_rejects() {
+        for s in [
+            "", "avg", "p0", "p101", "p-5", "pinf", "pNaN", "mean-sd", "mean-0sd", "mean-2",
+            "win0", "win", "winx", "Mean",
+        ] {
+            let e = Agg::parse(s).err().unwrap_or_else(|| panic!("{s} parsed"));
+            assert!(e.to_string().contains("Unknown stat"), "{s}: {e}");
+        }
+    }
+
+    // Zero & negative scores clamp to 1e-3: the mean stays finite & near zero
+    #[test]
+    fn hmean_clamps() {
+        assert!(close(agg("hmean", &[80.0, 40.0], false), 160.0 / 3.0));
+        for low in [0.0, -5.0] {
+            let h = agg("hmean", &[low, 100.0], false);
+            assert!(h.is_finite() && h > 0.0 && h < 0.01, "{low}: {h}");
+        }
+    }
+
+    #[test]
+    fn min_max() {
+        let s = [3.0, 1.0, 2.0];
+        assert!(close(agg("min", &s, false), 1.0));
+        assert!(close(agg("max", &s, true), 3.0));
+    }
+
+    #[test]
+    fn median_odd_even() {
+        assert!(close(agg("median", &[5.0, 1.0, 3.0], false), 3.0));
+        assert!(close(agg("median", &[4.0, 1.0, 3.0, 2.0], false), 2.5));
+        assert!(close(agg("median", &[7.0], false), 7.0));
+    }
+
+    // Mean 2, SD 1: the deviation goes towards the worse side
+    #[test]
+    fn mean_minus_sd() {
+        let s = [1.0, 3.0];
+        assert!(close(agg("mean-1.5sd", &s, false), 0.5));
+        assert!(close(agg("mean-1.5sd", &s, true), 3.5));
+    }
+
+    #[test]
+    fn win_takes_worst_window() {
+        let s = [5.0, 1.0, 1.0, 5.0, 5.0];
+        assert!(close(agg("win2", &s, false), 1.0));
+        assert!(close(agg("win2", &s, true), 5.0));
+        let w = reduce(&mut s.to_vec(), Agg::parse("win1").unwrap(), false, 2.0);
+        assert!(close(w, 1.0), "fps scales the window: {w}");
+    }
+
+    // A scene shorter than the window is scored as one window: its mean
+    #[test]
+    fn win_longer_than_scene() {
+        assert!(close(agg("win10", &[4.0, 2.0], false), 3.0));
+        assert!(close(agg("win10", &[4.0, 2.0], true), 3.0));
+        assert!(close(agg("win0.1", &[4.0, 2.0], false), 2.0));
+    }
+
+    fn also_err(s: &str) -> String {
+        parse_also(s)
+            .err()
+            .unwrap_or_else(|| panic!("{s} parsed"))
+            .to_string()
+    }
+
+    #[test]
+    fn also_parses_terms() {
+        let t = parse_also("ssimu2:p5>=78, butter:max<=2.5,cvvdp>=9.5,butter<=1").unwrap();
+        let got: Vec<(String, bool, f32)> = t.iter().map(|a| (a.key(), a.ge, a.val)).collect();
+        assert_eq!(
+            got,
+            [
+                ("ssimu2_p5".to_owned(), true, 78.0),
+                ("butter_max".to_owned(), false, 2.5),
+                ("cvvdp".to_owned(), true, 9.5),
+                ("butter_mean".to_owned(), false, 1.0),
+            ]
+        );
+        assert!(t[0].metric == Metric::Ssimu2 && t[2].metric == Metric::Cvvdp);
+        assert!(t[0].ok(78.0) && !t[0].ok(77.9));
+        assert!(t[1].ok(2.5) && !t[1].ok(2.6));
+    }
+
+    // CVVDP has no stat of its own to take
+    #[test]
+    fn also_rejects_unknown_metrics() {
+        for s in [
+            "psnr>=40",
+            "vmaf:mean>=90",
+            "cvvdp:p5>=9",
+            "ssimu2,butter<=2",
+            "",
+        ] {
+            assert!(also_err(s).contains("Invalid --tq-and term"), "{s}");
+        }
+        assert!(also_err("ssimu2:avg>=70").contains("Unknown stat"));
+    }
+
+    #[test]
+    fn also_rejects_bad_comparators() {
+        for s in [
+            "ssimu2>78",
+            "ssimu2=78",
+            "ssimu2=>78",
+            "butter<2",
+            "ssimu2:p5 78",
+            "ssimu2>=",
+            "ssimu2>=x",
+            "ssimu2>=<=78",
+        ] {
+            assert!(also_err(s).contains("Invalid --tq-and term"), "{s}");
+        }
+    }
+
+    #[test]
+    fn also_rejects_out_of_range() {
+        for s in [
+            "ssimu2>=101",
+            "cvvdp>=10.5",
+            "butter:max<=-1",
+            "ssimu2>=inf",
+            "ssimu2>=NaN",
+        ] {
+            assert!(also_err(s).contains("Out of range"), "{s}");
+        }
+        for s in ["ssimu2>=100", "ssimu2>=-10", "cvvdp>=10", "butter<=0"] {
+            assert!(parse_also(s).is_ok(), "{s}");
+        }
+    }
+
+    // Harmonic mean is SSIMULACRA2's alone, as `-m` or in a term
+    #[test]
+    fn hmean_only_for_ssimu2() {
+        let h = Agg::parse("hmean").unwrap();
+        assert!(val_agg(Metric::Ssimu2, h).is_ok());
+        assert!(val_agg(Metric::Butter, h).is_err());
+        assert!(val_agg(Metric::Cvvdp, h).is_err());
+        assert!(also_err("butter:hmean<=2").contains("only for SSIMULACRA2"));
+        assert!(parse_also("ssimu2:hmean>=70").is_ok());
+    }
+}
+
+mod lang {
+    use std::borrow::Cow;
+
+    use crate::{
+        audio::{AuStream, parse_au_arg, test_access::select},
+        lang::lang_matches,
+        opus::OpusOpts,
+    };
+
+    #[test]
+    fn lang_matches_codes_and_subtags() {
+        for (tag, want) in [
+            ("en", "eng"),
+            ("eng", "en"),
+            ("EN", "eng"),
+            ("fre", "fra"),
+            ("en-US", "en"),
+            ("en-US", "en-us"),
+            ("zh-Hant-TW", "zh-Hant"),
+        ] {
+            assert!(lang_matches(tag, want), "{tag} vs {want}");
+        }
+        for (tag, want) in [
+            ("en", "en-US"),
+            ("en-USA", "en-US"),
+            ("de", "en"),
+            ("und", "en"),
+            ("zh-Hans", "zh-Hant"),
+        ] {
+            assert!(!lang_matches(tag, want), "{tag} vs {want}");
+        }
+    }
+
+    fn stream(index: u8, channels: u8, lang: Option<&'static str>, title: &str) -> AuStream {
+        AuStream {
+            index,
+            channels,
+            lang: lang.map(Cow::Borrowed),
+            bitrate: 0,
+            opus: OpusOpts::default(),
+            title: (!title.is_empty()).then(|| title.to_owned()),
+            comment: title == "Commentary",
+        }
+    }
+
+    fn streams() -> Vec<AuStream> {
+        vec![
+            stream(0, 6, Some("eng"), "Main"),
+            stream(1, 2, Some("eng"), "Commentary"),
+            stream(2, 2, Some("fre"), ""),
+            stream(3, 6, Some("jpn"), ""),
+            stream(4, 2, Some("en-GB"), "Descriptive audio"),
+            stream(5, 2, None, ""),
+        ]
+    }
+
+    fn picked(arg: &str) -> Vec<u8> {
+        let all = streams();
+        let spec = parse_au_arg(arg).unwrap();
+        select(&all, &spec.streams)
+            .unwrap()
+            .iter()
+            .map(|s| s.index)
+            .collect()
+    }
+
+    // Listed languages in order of preference, source order within each
+    #[test]
+    fn select_by_lang() {
+        assert_eq!(picked("auto lang:jpn,en"), [3, 0, 1, 4]);
+        assert_eq!(picked("auto lang:en-GB"), [4]);
+        assert_eq!(picked("auto lang:und"), [5]);
+    }
+
+    // `commentary` also goes by the stream's flag; other words by title, any case
+    #[test]
+    fn select_exclude() {
+        assert_eq!(picked("auto lang:en+exclude:commentary"), [0, 4]);
+        assert_eq!(picked("auto all+exclude:DESCRIPTIVE,main"), [1, 2, 3, 5]);
+    }
+
+    #[test]
+    fn select_max_channels() {
+        assert_eq!(picked("auto all+max-channels:2"), [1, 2, 4, 5]);
+        assert_eq!(
+            picked("auto lang:en+max-channels:2+exclude:commentary"),
+            [4]
+        );
+    }
+
+    #[test]
+    fn select_first_per_lang() {
+        assert_eq!(picked("auto all+first-per-lang"), [0, 2, 3, 5]);
+        assert_eq!(picked("auto lang:fr,en+first-per-lang"), [2, 0]);
+        assert_eq!(picked("auto 1,4+first-per-lang"), [1]);
+    }
+
+    #[test]
+    fn select_ids() {
+        assert_eq!(picked("auto 1,3"), [1, 3]);
+        assert_eq!(picked("auto all"), [0, 1, 2, 3, 4, 5]);
+    }
+
+    // Plain ids may miss; selectors that match nothing are an error
+    #[test]
+    fn select_no_match_errs() {
+        let all = streams();
+        for arg in [
+            "auto lang:de",
+            "auto lang:en+max-channels:1",
+            "auto lang:jpn+exclude:commentary+max-channels:2",
+        ] {
+            let spec = parse_au_arg(arg).unwrap();
+            let e = select(&all, &spec.streams).err().unwrap();
+            assert!(
+                e.to_string().contains("No audio stream matches"),
+                "{arg}: {e}"
+            );
+        }
+        assert!(picked("auto 9").is_empty());
+    }
+}
+
+mod queue {
+    use std::{env, process, time::Duration};
+
+    use super::test_path;
+    use crate::{
+        Encoded,
+        ffms::get_vidinf,
+        fs::{create_dir_all, remove_dir_all},
+        parse_args_loop,
+        path::PathBuf,
+        queue::test_access::fin_err,
+    };
+
+    // A finish that fails lands in its own row; the process, and so the queue, lives on
+    #[test]
+    fn failing_finish_fails_its_row() {
+        let tmp = env::temp_dir().to_string_lossy().into_owned();
+        let dir = PathBuf::from(format!("{tmp}/xav_test_fin_{}", process::id()));
+        create_dir_all(&dir).unwrap();
+        let out = dir.join("out.mkv").to_string_lossy().into_owned();
+        let argv: Vec<String> = ["xav", "in.mkv", &out].map(String::from).into();
+        // no `encode/` in the tmp dir, so the mux step fails
+        let enc = Encoded {
+            args: parse_args_loop(&argv).unwrap(),
+            inf: get_vidinf(&test_path("8b_768x480.mp4")).unwrap(),
+            chnks: Vec::new(),
+            crop: (0, 0),
+            work_dir: dir.clone(),
+            enc_time: Duration::ZERO,
+            redo: None,
+        };
+        let err = fin_err(enc);
+        _ = remove_dir_all(&dir);
+        assert!(err.is_some());
+    }
+}
+
+mod mem {
+    use crate::mem::{parse_mem, test_access::room};
+
+    #[test]
+    fn parse_mem_suffixes() {
+        for (s, want) in [
+            ("4096", 4096),
+            ("2048K", 2048 << 10),
+            ("512m", 512 << 20),
+            ("8G", 8 << 30),
+            ("1.5G", 3 << 29),
+            ("1T", 1 << 40),
+            (" 2g ", 2 << 30),
+        ] {
+            assert_eq!(parse_mem(s).unwrap(), want, "{s}");
+        }
+        for s in ["", "G", "0", "-1G", "8X", "eight"] {
+            assert!(parse_mem(s).is_err(), "{s}");
+        }
+    }
+
+    // `memory.max` reads `max` when the cgroup has no limit
+    #[test]
+    fn cgroup_room_max_is_unlimited() {
+        assert_eq!(room("max\n", "1048576\n"), None);
+        assert_eq!(room("4194304\n", "1048576\n"), Some(3 << 20));
+        assert_eq!(room("1048576\n", "4194304\n"), Some(0));
+    }
+}
+
+mod opus_args {
+    use crate::{
+        audio::parse_au_arg,
+        opus::{App, Mapping},
+    };
+
+    #[test]
+    fn opus_kv_sets_each_option() {
+        let spec = parse_au_arg("auto all app=voip,frame=2.5,cx=5,cvbr,loss=10,map=amb").unwrap();
+        let o = spec.opus;
+        assert!(o.app == App::Voip && o.mapping == Mapping::Ambisonic && o.cvbr);
+        assert_eq!((o.frame, o.complexity, o.loss), (120, 5, 10));
+    }
+
+    // `<id>:` groups start from the shared options & override only what they name
+    #[test]
+    fn opus_kv_per_stream() {
+        let spec = parse_au_arg("128 all cx=8;1:frame=60;2:cx=3").unwrap();
+        assert_eq!(spec.opus.complexity, 8);
+        let per: Vec<_> = spec
+            .opus_per
+            .iter()
+            .map(|&(id, o)| (id, o.frame, o.complexity))
+            .collect();
+        assert_eq!(per, [(1, 2880, 8), (2, 960, 3)]);
+    }
+
+    #[test]
+    fn opus_kv_rejects_bad_values() {
+        for o in [
+            "app=music",
+            "frame=15",
+            "cx=11",
+            "loss=101",
+            "map=mono",
+            "bitrate=96",
+            "cx=x",
+        ] {
+            let arg = format!("auto all {o}");
+            assert!(parse_au_arg(&arg).is_err(), "{o}");
+        }
+    }
+}
+
+mod report {
+    use std::{env, process, time::Duration};
+
+    use crate::{
+        chunk::Chunk,
+        fs::{create_dir_all, read_to_string, remove_dir_all},
+        parse_args_loop,
+        path::PathBuf,
+        report::{log_chunk, write_report},
+    };
+
+    // Without a run line, frames or a known rate the numbers are `null` in JSON & empty in CSV
+    #[test]
+    fn report_nulls() {
+        let tmp = env::temp_dir().to_string_lossy().into_owned();
+        let dir = PathBuf::from(format!("{tmp}/xav_test_report_{}", process::id()));
+        create_dir_all(&dir).unwrap();
+        let out = dir.join("out.mkv").to_string_lossy().into_owned();
+        let argv: Vec<String> = ["xav", "in.mkv", &out].map(String::from).into();
+        let args = parse_args_loop(&argv).unwrap();
+        let chnks: Vec<Chunk> = [(0, 0, 10), (1, 10, 20)]
+            .map(|(idx, start, end)| Chunk {
+                idx,
+                tmpl: 0,
+                start,
+                end,
+                params: None,
+            })
+            .into();
+        log_chunk(&dir, 1, 2, 0.5, None);
+        write_report(&args, 0.0, &chnks, &dir, 0, Duration::ZERO);
+        let js = read_to_string(dir.join("out_report.json")).unwrap();
+        let csv = read_to_string(dir.join("out_report.csv")).unwrap();
+        _ = remove_dir_all(&dir);
+
+        assert!(js.contains(
+            "\"id\": 0, \"start\": 0, \"end\": 10, \"frames\": 10, \"zone\": \"\", \"worker\": \
+             null, \"secs\": null, \"fps\": null, \"bytes\": 0, \"kbs\": null }"
+        ));
+        assert!(js.contains(
+            "\"worker\": 2, \"secs\": 0.500, \"fps\": 20.00, \"bytes\": 0, \"kbs\": null"
+        ));
+        assert!(js.contains(
+            "\"total\": { \"frames\": 20, \"secs\": 0.000, \"fps\": null, \"bytes\": 0, \"kbs\": \
+             null }"
+        ));
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            rows[1..],
+            [
+                "0,0,10,10,,,,0,,\"\"",
+                "1,10,20,10,2,0.500,20.00,0,,\"\"",
+                "total,,,20,,0.000,,0,,"
+            ]
+        );
+    }
 }
@@ -19,6 +19,7 @@ use core::{
 #[cfg(not(target_os = "linux"))]
 use std::thread::{available_parallelism as std_ap, sleep as std_sleep};
 
+use crate::events::{mute, muted};
 #[cfg(target_os = "linux")]
 use crate::sys::{futex_wake, nanosleep, sched_getaffinity};
 
@@ -38,6 +39,18 @@ unsafe extern "C" {
     fn pthread_join(t: u64, ret: *mut *mut c_void) -> i32;
 }
 
+// Spawned threads start muted when their parent is, so a muted finisher never draws
+#[cfg(not(target_os = "linux"))]
+fn inherit<F: FnOnce() -> T, T>(f: F) -> impl FnOnce() -> T {
+    let m = muted();
+    move || {
+        if m {
+            mute();
+        }
+        f()
+    }
+}
+
 #[cfg(target_os = "linux")]
 #[thread_local]
 static CUR: Cell<*const AtomicU32> = Cell::new(null());
@@ -47,6 +60,7 @@ struct Start<F, T> {
     f: F,
     result: *mut MaybeUninit<T>,
     park: Arc<AtomicU32>,
+    mute: bool,
 }
 #[cfg(target_os = "linux")]
 unsafe impl<F: Send, T> Send for Start<F, T> {}
@@ -54,8 +68,16 @@ unsafe impl<F: Send, T> Send for Start<F, T> {}
 #[cfg(target_os = "linux")]
 extern "C" fn start<F: FnOnce() -> T, T>(arg: *mut c_void) -> *mut c_void {
     let boxed = unsafe { Box::from_raw(arg.cast::<Start<F, T>>()) };
-    let Start { f, result, park } = *boxed;
+    let Start {
+        f,
+        result,
+        park,
+        mute: m,
+    } = *boxed;
     CUR.set(Arc::as_ptr(&park));
+    if m {
+        mute();
+    }
     let r = f();
     unsafe { (*result).write(r) };
     null_mut()
@@ -102,7 +124,12 @@ where
 {
     let park = Arc::new(AtomicU32::new(0));
     let result = Box::into_raw(Box::new(MaybeUninit::<T>::uninit()));
-    let arg = Box::into_raw(Box::new(Start { f, result, park }));
+    let arg = Box::into_raw(Box::new(Start {
+        f,
+        result,
+        park,
+        mute: muted(),
+    }));
     let mut t = 0u64;
     unsafe { pthread_create(&raw mut t, null(), start::<F, T>, arg.cast()) };
     JoinHandle { t, result }
@@ -113,14 +140,17 @@ pub struct PHandle(u64);
 
 #[cfg(all(target_os = "linux", feature = "vship"))]
 extern "C" fn pstart<F: FnOnce()>(arg: *mut c_void) -> *mut c_void {
-    let f = unsafe { *Box::from_raw(arg.cast::<F>()) };
+    let (f, m) = unsafe { *Box::from_raw(arg.cast::<(F, bool)>()) };
+    if m {
+        mute();
+    }
     f();
     null_mut()
 }
 
 #[cfg(all(target_os = "linux", feature = "vship"))]
 pub fn pspawn<F: FnOnce() + Send + 'static>(f: F) -> PHandle {
-    let arg = Box::into_raw(Box::new(f));
+    let arg = Box::into_raw(Box::new((f, muted())));
     let mut t: u64 = 0;
     unsafe { pthread_create(&raw mut t, null(), pstart::<F>, arg.cast()) };
     PHandle(t)
@@ -179,6 +209,7 @@ struct ScopedStart<F, T> {
     f: F,
     slot: *mut Slot<T>,
     park: Arc<AtomicU32>,
+    mute: bool,
 }
 #[cfg(target_os = "linux")]
 unsafe impl<F: Send, T> Send for ScopedStart<F, T> {}
@@ -186,8 +217,16 @@ unsafe impl<F: Send, T> Send for ScopedStart<F, T> {}
 #[cfg(target_os = "linux")]
 extern "C" fn scoped_start<F: FnOnce() -> T, T>(arg: *mut c_void) -> *mut c_void {
     let boxed = unsafe { Box::from_raw(arg.cast::<ScopedStart<F, T>>()) };
-    let ScopedStart { f, slot, park } = *boxed;
+    let ScopedStart {
+        f,
+        slot,
+        park,
+        mute: m,
+    } = *boxed;
     CUR.set(Arc::as_ptr(&park));
+    if m {
+        mute();
+    }
     let r = f();
     unsafe { (*slot).val.write(r) };
     null_mut()
@@ -233,6 +272,7 @@ impl<'scope> Scope<'scope, '_> {
             f,
             slot,
             park: Arc::clone(&park),
+            mute: muted(),
         }));
         let mut t = 0u64;
         unsafe {
@@ -348,7 +388,7 @@ impl<'scope> Scope<'scope, '_> {
         F: FnOnce() -> T + Send + 'scope,
         T: Send + 'scope,
     {
-        ScopedJoinHandle(self.0.spawn(f))
+        ScopedJoinHandle(self.0.spawn(inherit(f)))
     }
 }
 
@@ -358,7 +398,7 @@ where
     F: FnOnce() -> T + Send + 'static,
     T: Send + 'static,
 {
-    JoinHandle(std::thread::spawn(f))
+    JoinHandle(std::thread::spawn(inherit(f)))
 }
 
 #[cfg(all(not(target_os = "linux"), feature = "vship"))]
@@ -366,7 +406,7 @@ pub struct PHandle(std::thread::JoinHandle<()>);
 
 #[cfg(all(not(target_os = "linux"), feature = "vship"))]
 pub fn pspawn<F: FnOnce() + Send + 'static>(f: F) -> PHandle {
-    PHandle(std::thread::spawn(f))
+    PHandle(std::thread::spawn(inherit(f)))
 }
 
 #[cfg(all(not(target_os = "linux"), feature = "vship"))]
@@ -1,19 +1,23 @@
 #[cfg(target_os = "linux")]
-use alloc::vec::Vec;
-use core::slice::from_raw_parts;
+use alloc::{borrow::ToOwned as _, string::String, vec::Vec};
+use core::{cell::RefCell, iter::once, slice::from_raw_parts};
 
 #[cfg(all(target_os = "linux", not(test)))]
 use crate::fmath::{FloatExt as _, Powf as _};
 use crate::{
+    chunk::Chunk,
     dav1d::Dav1dDec,
     enc::SplitPath,
-    error::fatal,
+    error::{Xerr, Xerr::Msg, fatal},
     ffms::VidDecoder,
     fs::metadata,
     interp::{fc_spline, lerp, pchip},
     pack::{unpack_10b, unpack_10b_rem},
+    path::Path,
     pipeline::{MetricProgs, Pipeline},
     progs::Tracker,
+    scd::load_wt,
+    sync::Mutex,
     vship::VshipProcessor,
     worker::WorkPkg,
 };
@@ -52,6 +56,16 @@ pub fn prep_ff(d: &mut ProbeDec, _: &WorkPkg, sp: &mut SplitPath, idx: u16, crf:
     sz
 }
 
+// `--audit`: the muxed output at `at`, each chunk's frames in place of a probe
+pub fn prep_out(d: &mut ProbeDec, path: &Path, at: usize) -> Result<(), Xerr> {
+    let vid = match d.vid.as_mut() {
+        Some(v) => v,
+        None => d.vid.insert(VidDecoder::new(path, d.threads)?),
+    };
+    vid.skip_to(at);
+    Ok(())
+}
+
 fn frame_dav1d(d: &mut ProbeDec) -> ([*const u8; 3], [i64; 3]) {
     unsafe { d.dav1d.as_mut().unwrap_unchecked() }.dec_next()
 }
@@ -126,16 +140,199 @@ pub fn jod(q: f32) -> f32 {
     JOD_A.mul_add(-q.powf(JOD_EXP), 10.0)
 }
 
+// `--tq-sample` kinds: score every Nth frame, as many by SCD weight, or encode every Nth only
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Sample {
+    Step,
+    Scd,
+    Enc,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Metric {
+    Ssimu2,
+    Butter,
+    Cvvdp,
+}
+
+impl Metric {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Ssimu2 => "ssimu2",
+            Self::Butter => "butter",
+            Self::Cvvdp => "cvvdp",
+        }
+    }
+
+    // Reachable `--tq-and` limits: SSIMULACRA2 up to 100, CVVDP to 10, Butteraugli from 0
+    const fn bounds(self) -> (f32, f32) {
+        match self {
+            Self::Ssimu2 => (f32::NEG_INFINITY, 100.0),
+            Self::Butter => (0.0, f32::INFINITY),
+            Self::Cvvdp => (f32::NEG_INFINITY, 10.0),
+        }
+    }
+}
+
+// Pools per-frame scores into a chunk score; CVVDP `mean` is its own pooled score
+#[derive(Copy, Clone)]
+pub enum Agg {
+    Mean,
+    // harmonic mean: SSIMULACRA2 only, leans to the low frames
+    HMean,
+    Max,
+    Min,
+    Median,
+    // mean of the worst N%
+    Pct(f32),
+    // mean minus k standard deviations (plus for lower-is-better)
+    Dev(f32),
+    // mean of the worst window of N seconds
+    Win(f32),
+}
+
+impl Agg {
+    // `mean`, `hmean`, `min`, `max`, `median`, `p5`, `mean-1.5sd`, `win1`
+    pub fn parse(s: &str) -> Result<Self, Xerr> {
+        let num = |v: &str| v.parse::<f32>().ok().filter(|n| *n > 0.0 && n.is_finite());
+        let pct = s.strip_prefix('p').and_then(num).filter(|p| *p <= 100.0);
+        let dev = s
+            .strip_prefix("mean-")
+            .and_then(|k| num(k.strip_suffix("sd")?));
+        let win = s.strip_prefix("win").and_then(num);
+        let agg = match s {
+            "mean" => Some(Self::Mean),
+            "hmean" => Some(Self::HMean),
+            "max" => Some(Self::Max),
+            "min" => Some(Self::Min),
+            "median" => Some(Self::Median),
+            _ => pct
+                .map(Self::Pct)
+                .or_else(|| dev.map(Self::Dev))
+                .or_else(|| win.map(Self::Win)),
+        };
+        agg.ok_or_else(|| {
+            Msg(format!(
+                "Unknown stat: {s} (mean, hmean, min, max, median, pN, mean-Ksd or winN)"
+            ))
+        })
+    }
+
+    #[must_use]
+    pub fn name(self) -> String {
+        match self {
+            Self::Mean => "mean".to_owned(),
+            Self::HMean => "hmean".to_owned(),
+            Self::Max => "max".to_owned(),
+            Self::Min => "min".to_owned(),
+            Self::Median => "median".to_owned(),
+            Self::Pct(p) => format!("p{p}"),
+            Self::Dev(k) => format!("mean-{k}sd"),
+            Self::Win(t) => format!("win{t}"),
+        }
+    }
+}
+
+// One `--tq-and` term: `metric:agg>=value` or `<=`, checked on every probe next to `-t`
+#[derive(Clone)]
+pub struct Also {
+    pub metric: Metric,
+    pub agg: Agg,
+    pub ge: bool,
+    pub val: f32,
+}
+
+impl Also {
+    #[must_use]
+    pub const fn ok(&self, score: f32) -> bool {
+        if self.ge {
+            score >= self.val
+        } else {
+            score <= self.val
+        }
+    }
+
+    // Report & cache name: `ssimu2_p5`, `butter_max`, `cvvdp`
+    #[must_use]
+    pub fn key(&self) -> String {
+        let m = self.metric.name();
+        if self.metric == Metric::Cvvdp {
+            m.to_owned()
+        } else {
+            format!("{m}_{}", self.agg.name())
+        }
+    }
+}
+
+// Harmonic mean leans to the lowest scores: the worst frames only where higher is better
+pub fn val_agg(m: Metric, agg: Agg) -> Result<(), Xerr> {
+    if matches!(agg, Agg::HMean) && m != Metric::Ssimu2 {
+        return Err(format!("hmean is only for SSIMULACRA2, not {}", m.name()).into());
+    }
+    Ok(())
+}
+
+// `ssimu2:p5>=78,butter:max<=2.5`; the stat defaults to `mean`, CVVDP takes none
+pub fn parse_also(s: &str) -> Result<Vec<Also>, Xerr> {
+    s.split(',')
+        .map(|t| {
+            let t = t.trim();
+            let bad = || {
+                Msg(format!(
+                    "Invalid --tq-and term: {t} (use e.g. butter:max<=2.5)"
+                ))
+            };
+            let (lhs, ge, val) = if let Some((l, v)) = t.split_once(">=") {
+                (l, true, v)
+            } else if let Some((l, v)) = t.split_once("<=") {
+                (l, false, v)
+            } else {
+                return Err(bad());
+            };
+            let lhs = lhs.trim();
+            let (m, stat) = lhs.split_once(':').unwrap_or((lhs, "mean"));
+            let metric = match m {
+                "ssimu2" => Metric::Ssimu2,
+                "butter" => Metric::Butter,
+                "cvvdp" if stat == "mean" => Metric::Cvvdp,
+                _ => return Err(bad()),
+            };
+            let agg = Agg::parse(stat)?;
+            val_agg(metric, agg)?;
+            let Ok(val) = val.trim().parse::<f32>() else {
+                return Err(bad());
+            };
+            let (lo, hi) = metric.bounds();
+            if !val.is_finite() || !(lo..=hi).contains(&val) {
+                return Err(format!(
+                    "Out of range --tq-and term: {t} (ssimu2 tops out at 100, cvvdp at 10, butter \
+                     starts at 0)"
+                )
+                .into());
+            }
+            Ok(Also {
+                metric,
+                agg,
+                ge,
+                val,
+            })
+        })
+        .collect()
+}
+
 #[derive(Clone)]
 pub struct Probe {
     pub crf: f32,
     pub score: f32,
+    // one per `--tq-and` term, in order
+    pub also: Vec<f32>,
 }
 
 #[derive(Clone)]
 pub struct ProbeLog {
     pub chnk_idx: u16,
     pub probes: Vec<(f32, f32, u64)>,
+    pub also: Vec<Vec<f32>>,
     pub final_crf: f32,
     pub final_score: f32,
     pub final_sz: u64,
@@ -163,19 +360,286 @@ pub fn interpolate_crf(probes: &[Probe], target: f32, round: u8) -> f32 {
     round_crf(result)
 }
 
+// Score at `crf` between a chunk's probes (sorted by CRF), linear between neighbours
+fn score_at(probes: &[(f32, f32)], crf: f32) -> f32 {
+    let i = probes.partition_point(|p| p.0 < crf);
+    match (i.checked_sub(1).and_then(|j| probes.get(j)), probes.get(i)) {
+        (Some(a), Some(b)) if b.0 > a.0 => (b.1 - a.1).mul_add((crf - a.0) / (b.0 - a.0), a.1),
+        (_, Some(b)) => b.1,
+        (Some(a), None) => a.1,
+        (None, None) => f32::NAN,
+    }
+}
+
+// CRFs `step` apart around `fc` still in tolerance; only lower ones with `down_only`
+fn crf_band(
+    probes: &mut [(f32, f32)],
+    fc: f32,
+    tq: (f32, f32),
+    step: f32,
+    down_only: bool,
+) -> (f32, f32) {
+    probes.sort_unstable_by(|a, b| a.0.total_cmp(&b.0));
+    let ok = |c: f32| (score_at(probes, c) - tq.0).abs() <= tq.1;
+    let (Some(first), Some(last)) = (probes.first(), probes.last()) else {
+        return (fc, fc);
+    };
+    if !ok(fc) {
+        return (fc, fc);
+    }
+    let mut lo = fc;
+    while lo - step >= first.0 - 1e-3 && ok(lo - step) {
+        lo -= step;
+    }
+    let mut hi = fc;
+    while !down_only && hi + step <= last.0 + 1e-3 && ok(hi + step) {
+        hi += step;
+    }
+    (lo, hi)
+}
+
+// `--crf-smooth`: CRFs within `delta` of their neighbours where each `tq` band allows
+pub fn smooth_crfs(
+    logs: &mut [(Vec<(f32, f32)>, f32)],
+    tq: (f32, f32),
+    step: f32,
+    down_only: bool,
+    delta: f32,
+) -> Vec<f32> {
+    let bands: Vec<(f32, f32)> = logs
+        .iter_mut()
+        .map(|l| crf_band(&mut l.0, l.1, tq, step, down_only))
+        .collect();
+    let mut crfs: Vec<f32> = logs.iter().map(|l| l.1).collect();
+    let pull = |x: &mut f32, prev: &mut Option<f32>, &(lo, hi): &(f32, f32)| {
+        if let Some(p) = *prev {
+            *x = x.clamp(p - delta, p + delta).clamp(lo, hi);
+        }
+        *prev = Some(*x);
+    };
+    for _ in 0..16 {
+        let before = crfs.clone();
+        let mut prev = None;
+        for (x, b) in crfs.iter_mut().zip(&bands) {
+            pull(x, &mut prev, b);
+        }
+        prev = None;
+        for (x, b) in crfs.iter_mut().zip(&bands).rev() {
+            pull(x, &mut prev, b);
+        }
+        if crfs == before {
+            break;
+        }
+    }
+    crfs.iter()
+        .zip(&bands)
+        .map(|(&c, &(lo, hi))| ((c / step).round() * step).clamp(lo, hi))
+        .collect()
+}
+
+// Finished chunks before a prediction is trusted
+const MIN_SEEDS: usize = 4;
+// Narrowed window is at least this far either side of the predicted CRF
+const MIN_HALF: f32 = 2.0;
+
+// First probe & window for a chunk from finished ones of like SCD activity & length
+pub struct CrfSeeds {
+    wt: Vec<f32>,
+    // (activity, frames, final CRF)
+    done: Mutex<Vec<(f32, f32, f32)>>,
+}
+
+impl CrfSeeds {
+    // Weights from the last SCD run on this source; without them only length counts
+    pub fn new(sc_file: &Path, frames: usize) -> Self {
+        Self::with_wt(load_wt(sc_file, frames))
+    }
+
+    pub const fn with_wt(wt: Vec<f32>) -> Self {
+        Self {
+            wt,
+            done: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn feat(&self, c: &Chunk) -> (f32, f32) {
+        let (mut sum, mut n) = (0.0, 0u32);
+        for &w in self.wt.get(c.start + 1..c.end).unwrap_or(&[]) {
+            if w >= 0.0 {
+                sum += w;
+                n += 1;
+            }
+        }
+        let act = if n == 0 { 0.0 } else { sum / n as f32 };
+        (act, (c.end - c.start) as f32)
+    }
+
+    pub fn add(&self, c: &Chunk, crf: f32) {
+        let (act, len) = self.feat(c);
+        self.done.lock().push((act, len, crf));
+    }
+
+    // `(crf, search_min, search_max)` inside `lo..=hi`, once enough chunks finished
+    pub fn predict(&self, c: &Chunk, lo: f32, hi: f32) -> Option<(f32, f32, f32)> {
+        let done = self.done.lock();
+        if done.len() < MIN_SEEDS {
+            return None;
+        }
+        let (act, len) = self.feat(c);
+        let n = done.len() as f32;
+        // activity in units of its variance so neither feature drowns the other
+        let mean = done.iter().map(|d| d.0).sum::<f32>() / n;
+        let var = done
+            .iter()
+            .map(|d| (d.0 - mean) * (d.0 - mean))
+            .sum::<f32>()
+            / n;
+        let var = var.max(1e-12);
+        let ws: Vec<f32> = done
+            .iter()
+            .map(|d| {
+                let da = d.0 - act;
+                let dl = (d.1 - len) / (d.1 + len);
+                1.0 / (da * da / var + dl.mul_add(4.0 * dl, 1.0))
+            })
+            .collect();
+        let tot: f32 = ws.iter().sum();
+        let crf = done.iter().zip(&ws).map(|(d, w)| w * d.2).sum::<f32>() / tot;
+        // weighted mean absolute deviation; ~0.8 of a standard deviation
+        let dev = done
+            .iter()
+            .zip(&ws)
+            .map(|(d, w)| w * (d.2 - crf).abs())
+            .sum::<f32>()
+            / tot;
+        let half = (2.5 * dev).max(MIN_HALF);
+        let crf = round_crf(crf).clamp(lo, hi);
+        Some((crf, (crf - half).max(lo), (crf + half).min(hi)))
+    }
+}
+
+// `-m` & `--tq-and` stats over every `stride`th frame or the frames `pick` marks
+pub struct ScoreSpec<'a> {
+    pub mode: Agg,
+    pub also: &'a [Also],
+    pub fps: f32,
+    pub stride: usize,
+    pub pick: Option<&'a [bool]>,
+    // `--audit`: gets the per-frame scores, the `-t` metric's then each term's
+    pub keep: Option<&'a RefCell<Vec<Vec<f32>>>>,
+}
+
+// `--tq-sample scdN`: a chunk's first frame, then those SCD weighs busiest
+pub fn scd_pick(wt: &[f32], stride: usize) -> Vec<bool> {
+    let mut order: Vec<usize> = (1..wt.len()).collect();
+    order.sort_by(|&a, &b| wt[b].total_cmp(&wt[a]));
+    let mut pick = vec![false; wt.len()];
+    for i in once(0).chain(order).take(wt.len().div_ceil(stride)) {
+        pick[i] = true;
+    }
+    pick
+}
+
+fn comp_metric(
+    vship: &VshipProcessor,
+    m: Metric,
+    inp_planes: [*const u8; 3],
+    out_planes: [*const u8; 3],
+    inp_strides: [i64; 3],
+    out_strides: [i64; 3],
+) -> f32 {
+    let comp = match m {
+        Metric::Ssimu2 => comp_ssimu2,
+        Metric::Butter => comp_butter,
+        Metric::Cvvdp => comp_cvvdp,
+    };
+    comp(vship, inp_planes, out_planes, inp_strides, out_strides)
+}
+
+// One score from per-frame ones; `worse_high` where a higher score is worse. `fps` sizes `winN`
+pub fn reduce(scores: &mut [f32], agg: Agg, worse_high: bool, fps: f32) -> f32 {
+    let len = scores.len().max(1);
+    let n = len as f32;
+    let mean = || scores.iter().sum::<f32>() / n;
+    match agg {
+        Agg::Mean => mean(),
+        Agg::HMean => n / scores.iter().map(|&s| 1.0 / s.max(1e-3)).sum::<f32>(),
+        Agg::Max => scores.iter().copied().fold(f32::MIN, f32::max),
+        Agg::Min => scores.iter().copied().fold(f32::MAX, f32::min),
+        Agg::Median => {
+            scores.sort_unstable_by(f32::total_cmp);
+            let hi = scores.get(len / 2).copied().unwrap_or(0.0);
+            if len.is_multiple_of(2) {
+                f32::midpoint(scores.get(len / 2 - 1).copied().unwrap_or(hi), hi)
+            } else {
+                hi
+            }
+        }
+        Agg::Pct(p) => {
+            if worse_high {
+                scores.sort_unstable_by(|x, y| y.total_cmp(x));
+            } else {
+                scores.sort_unstable_by(f32::total_cmp);
+            }
+            let cutoff = ((n * p / 100.0).ceil() as usize).clamp(1, len);
+            scores.iter().take(cutoff).sum::<f32>() / cutoff as f32
+        }
+        Agg::Dev(k) => {
+            let m = mean();
+            let sd = (scores.iter().map(|&s| (s - m) * (s - m)).sum::<f32>() / n).sqrt();
+            if worse_high {
+                k.mul_add(sd, m)
+            } else {
+                (-k).mul_add(sd, m)
+            }
+        }
+        Agg::Win(t) => {
+            // a scene shorter than the window is one window
+            let w = ((fps * t).round() as usize).clamp(1, len);
+            let mut sum: f32 = scores.iter().take(w).sum();
+            let mut worst = sum;
+            for (out, inp) in scores.iter().zip(scores.iter().skip(w)) {
+                sum += inp - out;
+                worst = if worse_high {
+                    worst.max(sum)
+                } else {
+                    worst.min(sum)
+                };
+            }
+            worst / w as f32
+        }
+    }
+}
+
+// CVVDP's last score is its pooled one; the rest reduce per-frame scores
+fn agg_also(scores: &mut [f32], a: &Also, fps: f32) -> f32 {
+    if a.metric == Metric::Cvvdp {
+        scores.last().copied().unwrap_or(0.0)
+    } else {
+        reduce(scores, a.agg, a.metric == Metric::Butter, fps)
+    }
+}
+
 macro_rules! calc_metric_impl {
-    ($name:ident, $is_10b:expr, $unpack:expr, $frame:expr, $compute:expr) => {
+    ($name:ident, $metric:expr, $is_10b:expr, $unpack:expr, $frame:expr, $compute:expr) => {
         pub fn $name(
             pkg: &WorkPkg,
             dec: &mut ProbeDec,
             pipe: &Pipeline,
             vship: &VshipProcessor,
-            metric_mode: &str,
+            spec: &ScoreSpec,
             unpacked_buf: &mut [u8],
             mp: &MetricProgs,
-        ) -> f32 {
-            let cvvdp_per_frame = pipe.reset_cvvdp && metric_mode.starts_with('p');
-            if pipe.reset_cvvdp {
+        ) -> (f32, Vec<f32>) {
+            let cvvdp_per_frame = pipe.reset_cvvdp && !matches!(spec.mode, Agg::Mean);
+            // per-frame scores of each other metric a `--tq-and` term needs
+            let mut extra: Vec<(Metric, Vec<f32>)> = Vec::new();
+            for a in spec.also {
+                if a.metric != $metric && extra.iter().all(|e| e.0 != a.metric) {
+                    extra.push((a.metric, Vec::with_capacity(pkg.frame_cnt)));
+                }
+            }
+            if pipe.reset_cvvdp || extra.iter().any(|e| e.0 == Metric::Cvvdp) {
                 vship.reset_cvvdp();
             }
 
@@ -186,7 +650,7 @@ macro_rules! calc_metric_impl {
                 mp.slot,
                 pkg.chnk.idx,
                 pkg.frame_cnt,
-                Some((mp.crf, mp.last_score)),
+                mp.crf.map(|c| (c, mp.last_score)),
             );
 
             let pix_sz = if $is_10b { 2 } else { 1 };
@@ -203,24 +667,41 @@ macro_rules! calc_metric_impl {
 
                     let input_frame = unsafe { from_raw_parts(src, frame_sz) };
                     src = unsafe { src.add(frame_sz) };
+                    // skipped frames are still decoded: the decoder only goes forward
                     let (output_planes, output_strides) = ($frame)(dec);
 
-                    let base = if $is_10b {
-                        ($unpack)(input_frame, unpacked_buf, fw, fh);
-                        unpacked_buf.as_ptr()
-                    } else {
-                        input_frame.as_ptr()
-                    };
-
-                    let input_planes = unsafe { [base, base.add(y_sz), base.add(y_sz + uv_sz)] };
-
-                    scores.push(($compute)(
-                        vship,
-                        input_planes,
-                        output_planes,
-                        [ys, cs, cs],
-                        output_strides,
-                    ));
+                    if spec.pick.map_or_else(
+                        || $frame_idx.is_multiple_of(spec.stride),
+                        |p| p.get($frame_idx).copied().unwrap_or(false),
+                    ) {
+                        let base = if $is_10b {
+                            ($unpack)(input_frame, unpacked_buf, fw, fh);
+                            unpacked_buf.as_ptr()
+                        } else {
+                            input_frame.as_ptr()
+                        };
+
+                        let input_planes =
+                            unsafe { [base, base.add(y_sz), base.add(y_sz + uv_sz)] };
+
+                        scores.push(($compute)(
+                            vship,
+                            input_planes,
+                            output_planes,
+                            [ys, cs, cs],
+                            output_strides,
+                        ));
+                        for e in &mut extra {
+                            e.1.push(comp_metric(
+                                vship,
+                                e.0,
+                                input_planes,
+                                output_planes,
+                                [ys, cs, cs],
+                                output_strides,
+                            ));
+                        }
+                    }
                 }};
             }
 
@@ -235,7 +716,30 @@ macro_rules! calc_metric_impl {
                 }
             }
 
-            aggregate_scores(&mut scores, pipe, metric_mode, cvvdp_per_frame)
+            let per_term = |a: &Also| {
+                extra
+                    .iter()
+                    .find(|e| e.0 == a.metric)
+                    .map_or_else(|| scores.clone(), |e| e.1.clone())
+            };
+            if let Some(k) = spec.keep {
+                let mut k = k.borrow_mut();
+                k.clear();
+                k.push(scores.clone());
+                k.extend(spec.also.iter().map(per_term));
+            }
+            let also = spec
+                .also
+                .iter()
+                .map(|a| {
+                    let mut src = per_term(a);
+                    agg_also(&mut src, a, spec.fps / spec.stride as f32)
+                })
+                .collect();
+            (
+                aggregate_scores(&mut scores, pipe, spec, cvvdp_per_frame),
+                also,
+            )
         }
     };
 }
@@ -243,42 +747,45 @@ macro_rules! calc_metric_impl {
 fn aggregate_scores(
     scores: &mut [f32],
     pipe: &Pipeline,
-    metric_mode: &str,
+    spec: &ScoreSpec,
     cvvdp_per_frame: bool,
 ) -> f32 {
     if pipe.reset_cvvdp && !cvvdp_per_frame {
         scores.last().copied().unwrap_or(0.0)
     } else if cvvdp_per_frame {
-        let percentile: f32 = unsafe {
-            metric_mode
-                .strip_prefix('p')
-                .and_then(|p| p.parse().ok())
-                .unwrap_unchecked()
-        };
+        // pooled on the linear scale JOD maps from, where higher is worse
         let mut q: Vec<f32> = scores.iter().map(|&s| inverse_jod(s)).collect();
-        q.sort_unstable_by(|a, b| b.total_cmp(a));
-        let cutoff = ((q.len() as f32 * percentile / 100.0).ceil() as usize).min(q.len());
-        jod(q[..cutoff].iter().sum::<f32>() / cutoff as f32)
-    } else if metric_mode == "mean" {
-        scores.iter().sum::<f32>() / scores.len() as f32
-    } else if let Some(p) = metric_mode.strip_prefix('p') {
-        let percentile: f32 = unsafe { p.parse().unwrap_unchecked() };
-        if pipe.sort_descending {
-            scores.sort_unstable_by(|a, b| b.total_cmp(a));
-        } else {
-            scores.sort_unstable_by(f32::total_cmp);
-        }
-        let cutoff = ((scores.len() as f32 * percentile / 100.0).ceil() as usize).min(scores.len());
-        scores[..cutoff].iter().sum::<f32>() / cutoff as f32
+        let agg = match spec.mode {
+            Agg::Min => Agg::Max,
+            Agg::Max => Agg::Min,
+            a => a,
+        };
+        jod(reduce(&mut q, agg, true, spec.fps))
     } else {
-        scores.iter().sum::<f32>() / scores.len() as f32
+        // `winN` windows span seconds: sampled scores come at `fps / stride`
+        reduce(
+            scores,
+            spec.mode,
+            pipe.sort_descending,
+            spec.fps / spec.stride as f32,
+        )
     }
 }
 
 macro_rules! make_metric_set {
-    ($compute:expr, $b8d:ident, $b8f:ident, $p10d:ident, $p10f:ident, $r10d:ident, $r10f:ident) => {
+    (
+        $metric:expr,
+        $compute:expr,
+        $b8d:ident,
+        $b8f:ident,
+        $p10d:ident,
+        $p10f:ident,
+        $r10d:ident,
+        $r10f:ident
+    ) => {
         calc_metric_impl!(
             $b8d,
+            $metric,
             false,
             |_: &[u8], _: &mut [u8], _: usize, _: usize| (),
             frame_dav1d,
@@ -286,6 +793,7 @@ macro_rules! make_metric_set {
         );
         calc_metric_impl!(
             $b8f,
+            $metric,
             false,
             |_: &[u8], _: &mut [u8], _: usize, _: usize| (),
             frame_ff,
@@ -293,6 +801,7 @@ macro_rules! make_metric_set {
         );
         calc_metric_impl!(
             $p10d,
+            $metric,
             true,
             |f: &[u8], b: &mut [u8], _w: usize, _h: usize| unpack_10b(f, b),
             frame_dav1d,
@@ -300,6 +809,7 @@ macro_rules! make_metric_set {
         );
         calc_metric_impl!(
             $p10f,
+            $metric,
             true,
             |f: &[u8], b: &mut [u8], _w: usize, _h: usize| unpack_10b(f, b),
             frame_ff,
@@ -307,6 +817,7 @@ macro_rules! make_metric_set {
         );
         calc_metric_impl!(
             $r10d,
+            $metric,
             true,
             |f: &[u8], b: &mut [u8], w: usize, h: usize| unpack_10b_rem(f, b, w, h),
             frame_dav1d,
@@ -314,6 +825,7 @@ macro_rules! make_metric_set {
         );
         calc_metric_impl!(
             $r10f,
+            $metric,
             true,
             |f: &[u8], b: &mut [u8], w: usize, h: usize| unpack_10b_rem(f, b, w, h),
             frame_ff,
@@ -323,6 +835,7 @@ macro_rules! make_metric_set {
 }
 
 make_metric_set!(
+    Metric::Ssimu2,
     comp_ssimu2,
     calc_ssimu2_8b_dav1d,
     calc_ssimu2_8b_ff,
@@ -332,6 +845,7 @@ make_metric_set!(
     calc_ssimu2_rem_ff
 );
 make_metric_set!(
+    Metric::Butter,
     comp_butter,
     calc_butter_8b_dav1d,
     calc_butter_8b_ff,
@@ -341,6 +855,7 @@ make_metric_set!(
     calc_butter_rem_ff
 );
 make_metric_set!(
+    Metric::Cvvdp,
     comp_cvvdp,
     calc_cvvdp_8b_dav1d,
     calc_cvvdp_8b_ff,
@@ -349,3 +864,16 @@ make_metric_set!(
     calc_cvvdp_rem_dav1d,
     calc_cvvdp_rem_ff
 );
+
+#[cfg(test)]
+pub mod test_access {
+    pub fn crf_band(
+        probes: &mut [(f32, f32)],
+        fc: f32,
+        tq: (f32, f32),
+        step: f32,
+        down_only: bool,
+    ) -> (f32, f32) {
+        super::crf_band(probes, fc, tq, step, down_only)
+    }
+}
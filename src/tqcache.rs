@@ -0,0 +1,172 @@
+#[cfg(target_os = "linux")]
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::{
+    fmt::Write as _,
+    hash::{Hash as _, Hasher as _},
+};
+
+use crate::{
+    Args,
+    chunk::Chunk,
+    fs::{File, OpenOptions, create_dir_all, read_to_string as read_to_str, rename, write},
+    io::Write as _,
+    manifest::src_key,
+    path::{Path, PathBuf},
+    process::var,
+    sync::Mutex,
+    tq::Probe,
+    util::Fnv,
+};
+
+// chunk start, end & zone params hash
+type ChnkKey = (usize, usize, u64);
+
+// ~8 MB
+const MAX_LINES: usize = 100_000;
+
+// TQ probe results kept across runs, one file per source (format in the guide, 2.11)
+pub struct ProbeCache {
+    cfg: u64,
+    known: BTreeMap<ChnkKey, Vec<(Probe, u64)>>,
+    file: Mutex<Option<File>>,
+    path: PathBuf,
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    var("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| var("HOME").map(|h| Path::new(&h).join(".cache")))
+        .map(|d| d.join("xav").join("tq"))
+}
+
+fn zone_key(c: &Chunk) -> ChnkKey {
+    let mut h = Fnv::new();
+    c.params.hash(&mut h);
+    (c.start, c.end, h.finish())
+}
+
+fn cfg_key(args: &Args, metric: &str) -> u64 {
+    let mut h = Fnv::new();
+    env!("XAV_V_VSHIP").hash(&mut h);
+    args.encoder.version().hash(&mut h);
+    args.alt_param
+        .as_deref()
+        .unwrap_or(&args.params)
+        .hash(&mut h);
+    format!("{:?}", args.dec_strat).hash(&mut h);
+    metric.hash(&mut h);
+    args.metric_mode.name().hash(&mut h);
+    args.disp.hash(&mut h);
+    for a in &args.tq_and {
+        a.key().hash(&mut h);
+    }
+    h.finish()
+}
+
+// `(config, chunk, probe, size)` of one cache line
+fn parse(line: &str) -> Option<(u64, ChnkKey, Probe, u64)> {
+    let mut it = line.split_whitespace();
+    let mut hex = || u64::from_str_radix(it.next()?, 16).ok();
+    let (c, start, end, zone) = (hex()?, hex()?, hex()?, hex()?);
+    let mut num = || it.next()?.parse::<f32>().ok();
+    let (crf, score) = (num()?, num()?);
+    let sz = it.next()?.parse().ok()?;
+    let also = it.filter_map(|s| s.parse().ok()).collect();
+    Some((
+        c,
+        (start as usize, end as usize, zone),
+        Probe { crf, score, also },
+        sz,
+    ))
+}
+
+// Rewritten through a temp file so a crash never leaves half a cache
+fn compact(path: &Path, lines: &[&str]) {
+    let mut out = String::new();
+    for l in lines {
+        out.push_str(l);
+        out.push('\n');
+    }
+    let tmp = path.with_extension("tmp");
+    if write(&tmp, out).is_ok() {
+        _ = rename(&tmp, path);
+    }
+}
+
+impl ProbeCache {
+    pub fn open(src: &Path, args: &Args, metric: &str) -> Option<Self> {
+        Self::open_in(&cache_dir()?, src, args, metric)
+    }
+
+    // `open` with the `xav/tq` dir given rather than found from the environment
+    pub fn open_in(dir: &Path, src: &Path, args: &Args, metric: &str) -> Option<Self> {
+        let path = dir.join(format!("{}.txt", src_key(src)?));
+        let cfg = cfg_key(args, metric);
+        let mut known: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        let txt = read_to_str(&path).unwrap_or_default();
+        let (mut lines, mut total) = (Vec::new(), 0usize);
+        let mut last: BTreeMap<(u64, ChnkKey, u32), usize> = BTreeMap::new();
+        for line in txt.lines() {
+            total += 1;
+            let Some((c, key, p, sz)) = parse(line) else {
+                continue;
+            };
+            if let Some(i) = last.insert((c, key, p.crf.to_bits()), lines.len()) {
+                lines[i] = "";
+            }
+            lines.push(line);
+            let also = p.also.len();
+            if c == cfg && also == args.tq_and.len() {
+                known.entry(key).or_default().push((p, sz));
+            }
+        }
+        lines.retain(|l| !l.is_empty());
+        let keep = &lines[lines.len().saturating_sub(MAX_LINES)..];
+        if keep.len() < total {
+            compact(&path, keep);
+        }
+        Some(Self {
+            cfg,
+            known,
+            file: Mutex::new(None),
+            path,
+        })
+    }
+
+    // Probes & their sizes already measured for this chunk, latest per CRF
+    pub fn get(&self, c: &Chunk) -> Vec<(Probe, u64)> {
+        let mut out: Vec<(Probe, u64)> = Vec::new();
+        for p in self.known.get(&zone_key(c)).into_iter().flatten().rev() {
+            if !out.iter().any(|o| (o.0.crf - p.0.crf).abs() < 1e-3) {
+                out.push(p.clone());
+            }
+        }
+        out
+    }
+
+    pub fn put(&self, c: &Chunk, p: &Probe, sz: u64) {
+        let (start, end, zone) = zone_key(c);
+        let mut line = format!(
+            "{:016x} {start:x} {end:x} {zone:016x} {} {} {sz}",
+            self.cfg, p.crf, p.score
+        );
+        for a in &p.also {
+            _ = write!(line, " {a}");
+        }
+        line.push('\n');
+        let mut f = self.file.lock();
+        if f.is_none() {
+            if let Some(dir) = self.path.parent() {
+                _ = create_dir_all(dir);
+            }
+            *f = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .ok();
+        }
+        if let Some(ref mut f) = *f {
+            _ = f.write_all(line.as_bytes());
+        }
+    }
+}
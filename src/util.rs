@@ -1,6 +1,8 @@
-use core::hash::Hasher;
+#[cfg(target_os = "linux")]
+use alloc::string::String;
 #[cfg(not(debug_assertions))]
 use core::hint::unreachable_unchecked;
+use core::{fmt::Write as _, hash::Hasher};
 
 pub const G: &str = "\x1b[1;92m";
 pub const R: &str = "\x1b[1;91m";
@@ -42,6 +44,24 @@ impl Hasher for Fnv {
     }
 }
 
+pub fn json_str(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if u32::from(c) < 0x20 => _ = write!(out, "\\u{:04x}", u32::from(c)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
 #[inline(always)]
 #[allow(
     clippy::panic,
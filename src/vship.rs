@@ -1,10 +1,15 @@
 use alloc::string::String;
 use core::{
     ffi::{CStr, c_void},
+    hash::{Hash, Hasher},
     mem::{MaybeUninit, zeroed},
     ops::{Deref, DerefMut},
     ptr::{NonNull, from_mut, null, null_mut},
     slice::{from_raw_parts, from_raw_parts_mut},
+    sync::atomic::{
+        AtomicBool,
+        Ordering::{Acquire, Release},
+    },
 };
 
 use crate::{
@@ -54,6 +59,16 @@ pub struct Disp {
     hdr: bool,
 }
 
+// Part of the probe cache key: another display model scores differently
+impl Hash for Disp {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        for v in self.v {
+            v.to_bits().hash(state);
+        }
+        self.hdr.hash(state);
+    }
+}
+
 impl Disp {
     const fn cs(&self) -> &'static str {
         if self.hdr { "HDR" } else { "SDR" }
@@ -416,7 +431,13 @@ pub struct VshipProcessor {
     butter_handler: Option<VshipButteraugliHandler>,
 }
 
+// Set once per process: a `--queue` does it up front, every later call is a no-op
+static DEVICE_SET: AtomicBool = AtomicBool::new(false);
+
 pub fn init_device() -> Result<(), Xerr> {
+    if DEVICE_SET.load(Acquire) {
+        return Ok(());
+    }
     unsafe {
         let mut errbuf = MaybeUninit::<[u8; 1024]>::uninit();
         let ret = Vship_SetDevice(0);
@@ -424,17 +445,18 @@ pub fn init_device() -> Result<(), Xerr> {
             vship_get_err(&mut errbuf);
             return Err(vship_err_str(&errbuf));
         }
-        Ok(())
     }
+    DEVICE_SET.store(true, Release);
+    Ok(())
 }
 
 impl VshipProcessor {
+    // `[ssimu2, cvvdp, butter]` handlers to set up; `--tq-and` can need more than one
     pub fn new(
         width: u32,
         height: u32,
         inf: &VidInf,
-        use_cvvdp: bool,
-        use_butter: bool,
+        [use_ssimu2, use_cvvdp, use_butter]: [bool; 3],
         disp: Option<Disp>,
     ) -> Result<Self, Xerr> {
         let fps = inf.fps_num as f32 / inf.fps_den as f32;
@@ -444,7 +466,7 @@ impl VshipProcessor {
 
             let mut errbuf = MaybeUninit::<[u8; 1024]>::uninit();
 
-            let handler = if !use_cvvdp && !use_butter {
+            let handler = if use_ssimu2 {
                 let mut handler = zeroed::<VshipSSIMU2Handler>();
                 let ret = Vship_SSIMU2Init(from_mut(&mut handler), src_colorspace, dis_colorspace);
                 if ret as i32 != 0 {
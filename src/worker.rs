@@ -3,6 +3,8 @@ use alloc::vec::Vec;
 
 use crate::chunk::Chunk;
 #[cfg(feature = "vship")]
+use crate::clk::Mono;
+#[cfg(feature = "vship")]
 use crate::tq::Probe;
 
 pub struct WorkPkg {
@@ -28,7 +30,19 @@ pub struct TQState {
     pub last_crf: f32,
     pub final_enc: bool,
     pub best_probe: Vec<u8>,
+    // `TQCtx::cost` of `best_probe`
     pub best_diff: f32,
+    // probe cache results & sizes not yet used, & the CRFs taken from them
+    pub known: Vec<(Probe, u64)>,
+    pub reused: Vec<f32>,
+    // predicted first probe; `narrowed` while the window is the prediction's, not `--qp`
+    pub seed: Option<f32>,
+    pub narrowed: bool,
+    // frame step probes are scored at; back to 1 once a `--tq-sample` pick is being verified
+    pub sample: usize,
+    // encoder worker that took the chunk & when, for the run report
+    pub worker: usize,
+    pub started: Mono,
 }
 
 impl WorkPkg {
@@ -4,16 +4,24 @@ use alloc::{
     vec::Vec,
 };
 use core::hint::cold_path;
+#[cfg(target_os = "linux")]
+use core::mem::replace;
 
-use crate::io::{BufRead as _, BufReader, IsTerminal as _, Read as _, Stdin, stdin};
 #[cfg(target_os = "linux")]
 use crate::sys::dup2;
+use crate::{
+    chunk::Chunk,
+    io::{BufRead as _, BufReader, IsTerminal as _, Read as _, Stdin, stdin},
+};
 #[cfg(target_os = "linux")]
 use crate::{
-    chunk::{Chunk, get_resume},
-    fs::{read, read_dir, read_link},
-    path::Path,
-    process::{Command, Stdio, id},
+    chunk::get_resume,
+    fs::{OpenOptions, create_dir_all, read, read_dir, read_link, read_to_string as read_to_str},
+    io::Write as _,
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio, id},
+    sync::Mutex,
+    thread::spawn,
 };
 
 #[cold]
@@ -28,19 +36,81 @@ pub struct Y4mInfo {
     pub is_10b: bool,
 }
 
+// Stream header for the 10-bit 4:2:0 frames xav feeds its encoders; each frame then follows `FRAME\n`
+pub fn y4m_header(width: usize, height: usize, fps: (u32, u32), full_range: bool) -> String {
+    format!(
+        "YUV4MPEG2 W{width} H{height} F{}:{} Ip A1:1 C420p10 XYSCSS=420P10 XCOLORRANGE={}\n",
+        fps.0,
+        fps.1,
+        if full_range { "FULL" } else { "LIMITED" }
+    )
+}
+
 pub struct PipeReader {
     reader: BufReader<Stdin>,
     pub frame_sz: usize,
     pub start_idx: usize,
     frame_header: [u8; 6],
+    #[cfg(target_os = "linux")]
+    pub cmd: Option<PipeCmd>,
+}
+
+// `--pipe-cmd`: the running pipeline & the frame its stream is at
+#[cfg(target_os = "linux")]
+pub struct PipeCmd {
+    tmpl: String,
+    child: Child,
+    at: usize,
+    last: usize,
+    // `--heavy-first`: each chunk gets a pipeline ending with it
+    per_chnk: bool,
+    // `logs/pipe.log`: every pipeline's stderr
+    log: PathBuf,
 }
 
+// The log of a `--pipe-cmd` whose stream ended before the chunks it was started for
+#[cfg(target_os = "linux")]
+static FAILED: Mutex<Option<PathBuf>> = Mutex::new(None);
+
 impl PipeReader {
+    // Only a `--pipe-cmd` stream can seek: done chunks are not read through
+    pub const fn seeks(&self) -> bool {
+        #[cfg(target_os = "linux")]
+        return self.cmd.is_some();
+        #[cfg(not(target_os = "linux"))]
+        false
+    }
+
+    // Positions a `--pipe-cmd` stream at `ch`, spawning the pipeline again from its first frame
+    // when the stream is elsewhere; false if that fails
+    pub fn seek(&mut self, ch: &Chunk) -> bool {
+        #[cfg(target_os = "linux")]
+        if let Some(ref mut pc) = self.cmd {
+            if pc.at != ch.start {
+                let end = if pc.per_chnk { ch.end - 1 } else { pc.last };
+                let Some(child) = spawn_cmd(&pc.tmpl, ch.start, end, &pc.log) else {
+                    *FAILED.lock() = Some(pc.log.clone());
+                    return false;
+                };
+                _ = replace(&mut pc.child, child).wait();
+                self.reader = BufReader::new(stdin());
+                let mut header = String::new();
+                _ = self.reader.read_line(&mut header);
+            }
+            pc.at = ch.end;
+        }
+        true
+    }
+
     pub fn read_frame(&mut self, dst: &mut [u8]) -> bool {
-        if self.reader.read_exact(&mut self.frame_header).is_err() {
-            return false;
+        let ok = self.reader.read_exact(&mut self.frame_header).is_ok()
+            && self.reader.read_exact(dst).is_ok();
+        #[cfg(target_os = "linux")]
+        if !ok && let Some(ref pc) = self.cmd {
+            cold_path();
+            *FAILED.lock() = Some(pc.log.clone());
         }
-        self.reader.read_exact(dst).is_ok()
+        ok
     }
 
     pub fn skip_frames(&mut self, cnt: usize) {
@@ -90,6 +160,8 @@ pub fn init_pipe(start_idx: usize) -> Option<(Y4mInfo, PipeReader)> {
         frame_sz,
         start_idx,
         frame_header: [0u8; 6],
+        #[cfg(target_os = "linux")]
+        cmd: None,
     };
 
     Some((info, pipe_reader))
@@ -118,6 +190,87 @@ pub fn vspipe_resume(chnks: &[Chunk], work_dir: &Path) -> Option<usize> {
     Some(first)
 }
 
+// `--pipe-cmd` through `sh -c` with `{start}` & `{end}` (inclusive) filled in; its stdout
+// becomes xav's stdin, so the old pipeline, which nothing else reads, ends on its next write
+#[cfg(target_os = "linux")]
+#[allow(clippy::literal_string_with_formatting_args)]
+fn spawn_cmd(tmpl: &str, start: usize, end: usize, log: &Path) -> Option<Child> {
+    let cmd = tmpl
+        .replace("{start}", &start.to_string())
+        .replace("{end}", &end.to_string());
+    let mut f = OpenOptions::new().create(true).append(true).open(log).ok();
+    if let Some(ref mut f) = f {
+        _ = f.write_all(format!("[xav] {cmd}\n").as_bytes());
+    }
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(cmd)
+        .stdin(Stdio::null())
+        .stderr(if f.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .spawn()
+        .ok()?;
+    if let Some(mut f) = f
+        && let Some(mut err) = child.stderr.take()
+    {
+        spawn(move || {
+            let mut buf = [0u8; 4096];
+            while let Ok(n @ 1..) = err.read(&mut buf) {
+                _ = f.write_all(&buf[..n]);
+            }
+        });
+    }
+    unsafe { dup2(child.stdout.take()?.as_raw_fd(), 0) };
+    Some(child)
+}
+
+// Set once a `--pipe-cmd` stream ended early: the run fails with the end of its stderr
+#[cfg(target_os = "linux")]
+pub fn pipe_failure() -> Option<String> {
+    let log = FAILED.lock().take()?;
+    let txt = read_to_str(&log).unwrap_or_default();
+    let lines: Vec<&str> = txt.lines().collect();
+    Some(format!(
+        "--pipe-cmd ended before the chunks it was started for; its stderr ({}):\n{}",
+        log.display(),
+        lines[lines.len().saturating_sub(20)..].join("\n")
+    ))
+}
+
+// Starts `--pipe-cmd` at the first unfinished chunk; a finished run still starts one for the
+// stream header. `per_chnk` ends it with that chunk, as each later one gets its own
+#[cfg(target_os = "linux")]
+pub fn pipe_cmd_start(
+    tmpl: &str,
+    chnks: &[Chunk],
+    work_dir: &Path,
+    per_chnk: bool,
+) -> Option<PipeCmd> {
+    let done: Vec<u16> = get_resume(work_dir)
+        .map(|r| r.chnks_done.iter().map(|c| c.idx).collect())
+        .unwrap_or_default();
+    let c0 = chnks
+        .iter()
+        .find(|c| !done.contains(&c.idx))
+        .or_else(|| chnks.first())?;
+    let last = chnks.last()?.end - 1;
+    let end = if per_chnk { c0.end - 1 } else { last };
+    _ = create_dir_all(work_dir.join("logs"));
+    let log = work_dir.join("logs").join("pipe.log");
+    Some(PipeCmd {
+        tmpl: tmpl.into(),
+        child: spawn_cmd(tmpl, c0.start, end, &log)?,
+        at: c0.start,
+        last,
+        per_chnk,
+        log,
+    })
+}
+
 #[cfg(target_os = "linux")]
 fn vspipe_argv() -> Option<Vec<String>> {
     let pipe = read_link("/proc/self/fd/0").ok()?;